@@ -1,8 +1,11 @@
 use reqwasm::http::{Request, Response};
 use serde::{Deserialize, Serialize};
 use shared::{
-    model::{Paste, PasteSummary},
-    PasteId, User,
+    model::{
+        ApiToken, Collection, CollectionPage, ListPastesQuery, NewApiToken, NewWebhook, Paste,
+        PastePage, PasteRevision, ShareLink, Webhook, WebhookFormat,
+    },
+    Id, PasteId, User, UserPasteId,
 };
 
 use crate::{Error, Result};
@@ -27,6 +30,10 @@ pub struct CreatePaste<'a> {
     pub id: Option<&'a PasteId>,
     pub pinned: bool,
     pub private: bool,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub tags: &'a [String],
+    #[serde(skip_serializing_if = "str::is_empty")]
+    pub description: &'a str,
 }
 
 #[allow(dead_code)] // Only used in !SSR
@@ -44,16 +51,63 @@ pub async fn create_paste(content: CreatePaste<'_>) -> Result<PasteId> {
     Ok(resp.json::<PasteId>().await?)
 }
 
+#[derive(Serialize)]
+struct ImportUrl<'a> {
+    url: &'a str,
+}
+
+/// Imports a build shared on another site, see [`crate::consts::IMPORT_HOSTS`].
+#[cfg(feature = "browser")]
+pub async fn import_url(url: &str) -> Result<PasteId> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post("/api/internal/import/url")
+        .body(serde_json::to_string(&ImportUrl { url })?)
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json::<PasteId>().await?)
+}
+
+/// Appends the share token of the current page to an API path,
+/// private pastes opened through a share link are only accessible with the token.
+///
+/// The token is forwarded as it appears in the page URL, still percent encoded.
+fn with_share_token(path: &str) -> String {
+    let token = web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .and_then(|search| {
+            search
+                .trim_start_matches('?')
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("share="))
+                .filter(|token| !token.is_empty())
+                .map(str::to_owned)
+        });
+
+    match token {
+        Some(token) => format!("{path}?share={token}"),
+        None => path.to_owned(),
+    }
+}
+
 pub async fn get_paste(id: &PasteId) -> Result<Paste> {
     let _in_flight = crate::progress::start_request();
-    let path = id.to_json_url();
+    let path = with_share_token(&id.to_json_url().into_cow());
 
-    let resp = Request::get(&path.into_cow()).send().await?;
+    let resp = Request::get(&path).send().await?;
 
     if resp.status() == 404 {
         return Err(Error::NotFound("paste", id.to_string()));
     }
 
+    if resp.status() == 410 {
+        return Err(Error::Gone("paste", id.to_string()));
+    }
+
     if !resp.ok() {
         return Err(handle_error_response(resp).await);
     }
@@ -62,7 +116,7 @@ pub async fn get_paste(id: &PasteId) -> Result<Paste> {
 }
 
 #[cfg(feature = "browser")]
-pub async fn delete_paste(id: &shared::UserPasteId) -> Result<()> {
+pub async fn delete_paste(id: &UserPasteId) -> Result<()> {
     let _in_flight = crate::progress::start_request();
     let resp = Request::delete(&id.to_paste_api_url().into_cow())
         .send()
@@ -75,9 +129,97 @@ pub async fn delete_paste(id: &shared::UserPasteId) -> Result<()> {
     Ok(())
 }
 
-pub async fn get_user(user: &User) -> Result<Vec<PasteSummary>> {
+pub async fn get_paste_revisions(id: &UserPasteId) -> Result<Vec<PasteRevision>> {
     let _in_flight = crate::progress::start_request();
-    let resp = Request::get(&user.to_api_url().into_cow()).send().await?;
+    let path = with_share_token(&id.to_revisions_api_url().into_cow());
+    let resp = Request::get(&path).send().await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[cfg(feature = "browser")]
+pub async fn restore_paste_revision(id: &UserPasteId, revision: u64) -> Result<PasteId> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post(&id.to_revision_restore_api_url(revision).into_cow())
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json::<PasteId>().await?)
+}
+
+#[derive(Serialize)]
+struct RenamePaste<'a> {
+    id: &'a str,
+}
+
+#[allow(dead_code)] // Only used in !SSR
+pub async fn rename_paste(id: &UserPasteId, new_id: &str) -> Result<PasteId> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post(&id.to_rename_api_url().into_cow())
+        .body(serde_json::to_string(&RenamePaste { id: new_id })?)
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json::<PasteId>().await?)
+}
+
+#[derive(Serialize)]
+pub struct ForkPaste<'a> {
+    #[serde(skip_serializing_if = "str::is_empty")]
+    pub custom_id: &'a str,
+}
+
+#[cfg(feature = "browser")]
+pub async fn fork_paste(id: &PasteId, fork: ForkPaste<'_>) -> Result<PasteId> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post(&id.to_fork_api_url().into_cow())
+        .body(serde_json::to_string(&fork)?)
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json::<PasteId>().await?)
+}
+
+#[derive(Serialize)]
+struct ReportPaste<'a> {
+    reason: &'a str,
+}
+
+#[cfg(feature = "browser")]
+pub async fn report_paste(id: &PasteId, reason: &str) -> Result<()> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post(&id.to_report_api_url().into_cow())
+        .body(serde_json::to_string(&ReportPaste { reason })?)
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(())
+}
+
+pub async fn get_user(user: &User, query: &ListPastesQuery) -> Result<PastePage> {
+    let _in_flight = crate::progress::start_request();
+    let url = format!("{}{}", user.to_api_url(), query.to_query_string());
+    let resp = Request::get(&url).send().await?;
 
     if resp.status() == 404 {
         return Err(Error::NotFound("user", user.to_string()));
@@ -90,6 +232,235 @@ pub async fn get_user(user: &User) -> Result<Vec<PasteSummary>> {
     Ok(resp.json().await?)
 }
 
+pub async fn get_collection(user: &User, id: &Id) -> Result<CollectionPage> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::get(&user.to_collection_api_url(id).into_cow())
+        .send()
+        .await?;
+
+    if resp.status() == 404 {
+        return Err(Error::NotFound("collection", format!("{user}:{id}")));
+    }
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[cfg(feature = "browser")]
+pub async fn get_collections(user: &User) -> Result<Vec<Collection>> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::get(&user.to_collections_api_url().into_cow())
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[derive(Serialize)]
+pub struct SaveCollection<'a> {
+    /// Id of an existing collection, a new collection is created without an id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<&'a Id>,
+    pub title: &'a str,
+    pub description: &'a str,
+    pub private: bool,
+    pub pastes: &'a [Id],
+}
+
+#[cfg(feature = "browser")]
+pub async fn save_collection(collection: SaveCollection<'_>) -> Result<Collection> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post("/api/internal/collections")
+        .body(serde_json::to_string(&collection)?)
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[cfg(feature = "browser")]
+pub async fn delete_collection(id: &Id) -> Result<()> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::delete(&format!("/api/internal/collections/{id}"))
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "browser")]
+pub async fn get_api_tokens() -> Result<Vec<ApiToken>> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::get("/api/internal/tokens").send().await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[derive(Serialize)]
+struct CreateApiToken<'a> {
+    name: &'a str,
+}
+
+#[cfg(feature = "browser")]
+pub async fn create_api_token(name: &str) -> Result<NewApiToken> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post("/api/internal/tokens")
+        .body(serde_json::to_string(&CreateApiToken { name })?)
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[cfg(feature = "browser")]
+pub async fn revoke_api_token(id: &str) -> Result<()> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::delete(&format!("/api/internal/tokens/{id}"))
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "browser")]
+pub async fn get_webhooks() -> Result<Vec<Webhook>> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::get("/api/internal/webhooks").send().await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[derive(Serialize)]
+struct CreateWebhook<'a> {
+    url: &'a str,
+    format: WebhookFormat,
+}
+
+#[cfg(feature = "browser")]
+pub async fn create_webhook(url: &str, format: WebhookFormat) -> Result<NewWebhook> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post("/api/internal/webhooks")
+        .body(serde_json::to_string(&CreateWebhook { url, format })?)
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[cfg(feature = "browser")]
+pub async fn delete_webhook(id: &str) -> Result<()> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::delete(&format!("/api/internal/webhooks/{id}"))
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "browser")]
+pub async fn get_paste_share(id: &UserPasteId) -> Result<Option<ShareLink>> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::get(&id.to_share_api_url().into_cow())
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[cfg(feature = "browser")]
+pub async fn create_paste_share(id: &UserPasteId) -> Result<ShareLink> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post(&id.to_share_api_url().into_cow())
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(resp.json().await?)
+}
+
+#[cfg(feature = "browser")]
+pub async fn revoke_paste_share(id: &UserPasteId) -> Result<()> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::delete(&id.to_share_api_url().into_cow())
+        .send()
+        .await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "browser")]
+pub async fn logout() -> Result<()> {
+    let resp = Request::post("/api/internal/logout").send().await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "browser")]
+pub async fn logout_everywhere() -> Result<()> {
+    let _in_flight = crate::progress::start_request();
+    let resp = Request::post("/api/internal/logout/all").send().await?;
+
+    if !resp.ok() {
+        return Err(handle_error_response(resp).await);
+    }
+
+    Ok(())
+}
+
 async fn handle_error_response(resp: Response) -> Error {
     if let Ok(err) = resp.json::<ErrorResponse>().await {
         Error::ApiError(err.code, err.message)
@@ -0,0 +1,118 @@
+use shared::model::ApiToken;
+use sycamore::prelude::*;
+
+use crate::utils::{async_callback, pretty_date_ts, view_if};
+
+/// Management of the personal API tokens of the current user.
+///
+/// Tokens are only loaded in the browser, they are never rendered server side.
+#[component]
+pub fn ApiTokens<G: Html>(cx: Scope) -> View<G> {
+    let tokens = create_signal(cx, Vec::<ApiToken>::new());
+    let new_token = create_signal(cx, None::<String>);
+    let name = create_signal(cx, String::new());
+
+    #[cfg(feature = "browser")]
+    if G::IS_BROWSER {
+        sycamore::futures::spawn_local_scoped(cx, async move {
+            match crate::api::get_api_tokens().await {
+                Ok(t) => tokens.set(t),
+                Err(err) => tracing::error!("loading API tokens failed: {:?}", err),
+            }
+        });
+    }
+
+    let on_create = async_callback!(
+        cx,
+        {
+            let token_name = name.get();
+            match crate::api::create_api_token(&token_name).await {
+                Err(err) => tracing::error!("creating API token failed: {:?}", err),
+                Ok(created) => {
+                    tokens.modify().push(created.info);
+                    new_token.set(Some(created.token));
+                    name.set(String::new());
+                }
+            }
+        },
+        !name.get().trim().is_empty()
+    );
+
+    let new_token_view = create_memo(cx, || match &*new_token.get() {
+        Some(token) => {
+            let token = token.clone();
+            view! { cx,
+                div(class="flex flex-col gap-1 text-sm") {
+                    span(class="text-amber-50") {
+                        "Copy your new token now, it will not be shown again:"
+                    }
+                    code(class="break-all bg-slate-600 p-2 rounded-sm select-all") { (token) }
+                }
+            }
+        }
+        None => view! { cx, },
+    });
+
+    let is_empty = create_memo(cx, || tokens.get().is_empty());
+    let empty = view_if!(cx, is_empty, {
+        span(class="text-sm text-slate-400") { "There are no API tokens yet .." }
+    });
+
+    view! { cx,
+        details(class="mb-4") {
+            summary(class="cursor-pointer text-sky-400") { "API Tokens" }
+            div(class="flex flex-col gap-3 mt-3") {
+                p(class="text-sm text-slate-400") {
+                    "API tokens authenticate uploads and deletions of your builds, "
+                    "send them as a bearer token in the "
+                    code { "Authorization" }
+                    " header."
+                }
+                Indexed(
+                    iterable=tokens,
+                    view=move |cx, token| {
+                        let id = create_ref(cx, token.id);
+                        let on_revoke = async_callback!(
+                            cx,
+                            {
+                                match crate::api::revoke_api_token(id).await {
+                                    Err(err) => tracing::error!("revoking API token failed: {:?}", err),
+                                    Ok(()) => tokens.modify().retain(|t| &t.id != id),
+                                }
+                            },
+                            {
+                                let message = "Are you sure you want to revoke this token?".to_owned();
+                                web_sys::window()
+                                    .unwrap()
+                                    .confirm_with_message(&message)
+                                    .unwrap_or_default()
+                            }
+                        );
+                        view! { cx,
+                            div(class="flex gap-4 items-center even:bg-slate-700 p-2") {
+                                span(class="flex-auto text-amber-50") { (token.name) }
+                                span(class="text-sm text-slate-400") { (pretty_date_ts(token.created)) }
+                                button(on:click=on_revoke, class="btn btn-secondary", title="Revoke this token") {
+                                    "Revoke"
+                                }
+                            }
+                        }
+                    }
+                )
+                (&*empty.get())
+                div(class="flex gap-2 items-center") {
+                    input(
+                        class="input flex-auto",
+                        type="text",
+                        maxlength=50,
+                        placeholder="Token name, e.g. CI",
+                        aria-label="Token name",
+                        bind:value=name
+                    ) {}
+                    button(on:click=on_create, class="btn btn-primary") { "Create Token" }
+                }
+                (&*new_token_view.get())
+            }
+        }
+    }
+}
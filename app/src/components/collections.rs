@@ -0,0 +1,106 @@
+use shared::{model::Collection, User};
+use sycamore::prelude::*;
+
+use crate::{
+    session::SessionValue,
+    utils::{async_callback, memo_cond},
+};
+
+#[derive(Prop)]
+pub struct CollectionsProps {
+    user: User,
+}
+
+/// Lists the collections of a user and lets the owner create new collections.
+///
+/// Collections are only loaded in the browser, they are never rendered server side.
+#[component]
+pub fn Collections<G: Html>(cx: Scope, CollectionsProps { user }: CollectionsProps) -> View<G> {
+    let collections = create_signal(cx, Vec::<Collection>::new());
+    let title = create_signal(cx, String::new());
+
+    let user = create_ref(cx, user);
+    let session = use_context::<SessionValue>(cx);
+    let is_current_user = create_memo(cx, || {
+        Some(user.as_str()) == session.get().user().map(|u| u.name.as_str())
+    });
+
+    #[cfg(feature = "browser")]
+    if G::IS_BROWSER {
+        sycamore::futures::spawn_local_scoped(cx, async move {
+            match crate::api::get_collections(user).await {
+                Ok(c) => collections.set(c),
+                Err(err) => tracing::error!("loading collections failed: {:?}", err),
+            }
+        });
+    }
+
+    let on_create = async_callback!(
+        cx,
+        {
+            let title = title.get();
+            let collection = crate::api::SaveCollection {
+                id: None,
+                title: title.trim(),
+                description: "",
+                private: false,
+                pastes: &[],
+            };
+            match crate::api::save_collection(collection).await {
+                Err(err) => tracing::error!("creating collection failed: {:?}", err),
+                Ok(created) => {
+                    sycamore_router::navigate(&user.to_collection_url(&created.id).into_cow())
+                }
+            }
+        },
+        !title.get().trim().is_empty()
+    );
+
+    let create = memo_cond!(
+        cx,
+        is_current_user,
+        view! { cx,
+            div(class="flex gap-2 items-center") {
+                input(
+                    class="input",
+                    type="text",
+                    maxlength=90,
+                    placeholder="New collection",
+                    aria-label="Title of the new collection",
+                    bind:value=title
+                ) {}
+                button(on:click=on_create, class="btn btn-secondary") { "Create" }
+            }
+        },
+        view! { cx, }
+    );
+
+    let is_empty = create_memo(cx, || collections.get().is_empty());
+    let is_visible = create_memo(cx, || !*is_empty.get() || *is_current_user.get());
+
+    let content = memo_cond!(
+        cx,
+        is_visible,
+        view! { cx,
+            div(class="flex flex-wrap gap-2 items-center mb-4") {
+                span(class="text-amber-50") { "Collections:" }
+                Indexed(
+                    iterable=collections,
+                    view=move |cx, collection| {
+                        let href = user.to_collection_url(&collection.id).to_string();
+                        let private = if collection.private { " (private)" } else { "" };
+                        view! { cx,
+                            a(href=href, class="text-sky-400 hover:underline") {
+                                (collection.title) (private)
+                            }
+                        }
+                    }
+                )
+                (&*create.get())
+            }
+        },
+        view! { cx, }
+    );
+
+    view! { cx, (&*content.get()) }
+}
@@ -5,7 +5,7 @@ use shared::{validation, Id, UserPasteId};
 use sycamore::{prelude::*, reactive::use_context};
 use wasm_bindgen::JsCast;
 
-use crate::{session::SessionValue, svg::SPINNER, utils::memo_cond};
+use crate::{components::ShareLink, session::SessionValue, svg::SPINNER, utils::memo_cond};
 
 #[derive(Default)]
 pub enum CreatePasteProps {
@@ -17,6 +17,8 @@ pub enum CreatePasteProps {
         title: Option<String>,
         rank: Option<NonZeroU8>,
         private: bool,
+        tags: Vec<String>,
+        description: String,
     },
 }
 
@@ -51,6 +53,20 @@ impl CreatePasteProps {
         }
     }
 
+    fn tags(&self) -> String {
+        match self {
+            Self::Update { tags, .. } => tags.join(", "),
+            _ => String::new(),
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            Self::Update { description, .. } => description.clone(),
+            _ => String::new(),
+        }
+    }
+
     fn is_update(&self) -> bool {
         matches!(self, Self::Update { .. })
     }
@@ -82,6 +98,8 @@ pub fn CreatePaste<G: Html>(cx: Scope, props: CreatePasteProps) -> View<G> {
     );
     let pinned = create_signal(cx, props.rank().is_some());
     let private = create_signal(cx, props.private());
+    let tags = create_signal(cx, props.tags());
+    let description = create_signal(cx, props.description());
 
     let session = use_context::<SessionValue>(cx);
 
@@ -131,9 +149,28 @@ pub fn CreatePaste<G: Html>(cx: Scope, props: CreatePasteProps) -> View<G> {
         let custom_id = custom_id.get();
         let pinned = *pinned.get();
         let private = *private.get();
+        let tags = validation::user::normalize_tags(tags.get().split(','));
+        let description = description.get();
 
         let future = async move {
-            let id = props.paste_id().map(|e| e.clone().into());
+            let mut id = props.paste_id().map(|e| e.clone().into());
+
+            // A changed id on an existing paste moves the paste first, then updates it.
+            if let Some(up) = props
+                .paste_id()
+                .filter(|up| !custom_id.is_empty() && up.id.as_str() != custom_id.as_str())
+            {
+                match api::rename_paste(up, &custom_id).await {
+                    Err(err) => {
+                        loading.set(false);
+                        error.set(err.to_string());
+                        tracing::info!("{:?}", err);
+                        return;
+                    }
+                    Ok(renamed) => id = Some(renamed),
+                }
+            }
+
             let title = if custom_title.is_empty() {
                 &*title
             } else {
@@ -148,6 +185,8 @@ pub fn CreatePaste<G: Html>(cx: Scope, props: CreatePasteProps) -> View<G> {
                 content: &value,
                 pinned,
                 private,
+                tags: &tags,
+                description: description.trim(),
             };
             match api::create_paste(params).await {
                 Err(err) => {
@@ -178,7 +217,11 @@ pub fn CreatePaste<G: Html>(cx: Scope, props: CreatePasteProps) -> View<G> {
             let id = custom_id.get().is_empty() || custom_id.get().parse::<Id>().is_ok();
             let title = custom_title.get().is_empty()
                 || validation::user::is_valid_custom_title(&custom_title.get()).is_valid();
-            if !id || !title {
+            let tags = validation::user::normalize_tags(tags.get().split(','));
+            let tags = validation::user::is_valid_tags(&tags).is_valid();
+            let description =
+                validation::user::is_valid_description(description.get().trim()).is_valid();
+            if !id || !title || !tags || !description {
                 return true;
             }
         }
@@ -214,6 +257,25 @@ pub fn CreatePaste<G: Html>(cx: Scope, props: CreatePasteProps) -> View<G> {
         custom_id.set(value);
     };
 
+    let id_title = match is_update {
+        true => "Id of the build, links to the previous Id keep working",
+        false => "Id of the build, reusing an Id overwrites the previous build",
+    };
+
+    // Share links only grant access to private builds.
+    let share = memo_cond!(
+        cx,
+        private,
+        match props.paste_id() {
+            Some(id) => view! { cx,
+                div(title="Anyone with the link can view this private build") { "Share" }
+                ShareLink(id=id.clone())
+            },
+            None => view! { cx, },
+        },
+        view! { cx, }
+    );
+
     let as_user_content = memo_cond!(
         cx,
         as_user,
@@ -232,7 +294,7 @@ pub fn CreatePaste<G: Html>(cx: Scope, props: CreatePasteProps) -> View<G> {
                     bind:value=custom_title,
                 ) {}
 
-                div(title="Id of the build, reusing an Id overwrites the previous build") { "Id" }
+                div(title=id_title) { "Id" }
                 input(
                     class="input",
                     type="text",
@@ -241,7 +303,6 @@ pub fn CreatePaste<G: Html>(cx: Scope, props: CreatePasteProps) -> View<G> {
                     pattern="[a-zA-Z0-9\\-_]*",
                     aria-label="Build ID",
                     placeholder="<auto generated>",
-                    readonly=is_update,
                     value=custom_id.get(),
                     on:input=on_custom_id) { }
 
@@ -262,6 +323,28 @@ pub fn CreatePaste<G: Html>(cx: Scope, props: CreatePasteProps) -> View<G> {
                         data-private=props.private(),
                     ) {}
                 }
+
+                div(title="Comma separated tags, e.g. budget, mapping, uber") { "Tags" }
+                input(
+                    class="input",
+                    type="text",
+                    aria-label="Tags",
+                    placeholder="budget, mapping, uber",
+                    data-tags=props.tags(),
+                    bind:value=tags,
+                ) {}
+
+                div(title="Short notes about this build") { "Notes" }
+                textarea(
+                    class="input resize-y",
+                    rows=2,
+                    maxlength=300,
+                    aria-label="Notes",
+                    data-marker-description="",
+                    bind:value=description,
+                ) { (props.description()) }
+
+                (&*share.get())
             }
         },
         view! { cx, }
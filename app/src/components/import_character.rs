@@ -0,0 +1,48 @@
+use sycamore::prelude::*;
+
+/// Imports a build from a character of the current user.
+///
+/// Importing requires a login with access to the characters of the account,
+/// the login is a full page navigation and not handled by the router.
+#[component]
+pub fn ImportCharacter<G: Html>(cx: Scope) -> View<G> {
+    let name = create_signal(cx, String::new());
+
+    let href = create_memo(cx, || {
+        let name = name.get();
+        let name = name.trim();
+        match is_valid_name(name) {
+            true => Some(format!("/login/character/{name}")),
+            false => None,
+        }
+    });
+    let btn_class = create_memo(cx, || match href.get().is_some() {
+        true => "btn btn-primary",
+        false => "btn btn-primary pointer-events-none opacity-50",
+    });
+
+    view! { cx,
+        details(class="mb-4") {
+            summary(class="cursor-pointer text-sky-400") { "Import Character" }
+            div(class="flex flex-col gap-3 mt-3") {
+                p(class="text-sm text-slate-400") {
+                    "Creates a private build from one of your characters, "
+                    "you will be asked to grant access to your characters."
+                }
+                div(class="flex flex-wrap items-center gap-3") {
+                    input(class="input flex-1", placeholder="Character name", bind:value=name) {}
+                    a(
+                        class=*btn_class.get(),
+                        href=href.get().as_ref().clone().unwrap_or_else(|| "#".to_owned()),
+                        rel="external"
+                    ) { "Import" }
+                }
+            }
+        }
+    }
+}
+
+/// Character names only consist of letters and underscores.
+fn is_valid_name(name: &str) -> bool {
+    (3..=23).contains(&name.len()) && name.bytes().all(|c| c.is_ascii_alphabetic() || c == b'_')
+}
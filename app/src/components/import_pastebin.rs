@@ -1,36 +1,52 @@
 use sycamore::prelude::*;
 
-use crate::{svg, utils::memo_cond};
+use crate::{consts::IMPORT_HOSTS, svg, utils::memo_cond};
 
+/// Imports a build shared on another site, see [`IMPORT_HOSTS`].
 #[component]
 pub fn ImportPastebin<G: Html>(cx: Scope) -> View<G> {
     let value = create_signal(cx, String::new());
     let loading = create_signal(cx, false);
+    let error = create_signal(cx, String::new());
 
-    let pastebin_id = create_memo(cx, || {
-        value
-            .get()
-            .strip_prefix("https://pastebin.com/")
-            .filter(|candidate| is_pastebin_id(candidate))
-            .map(|id| id.to_owned())
-    });
+    let is_supported = create_memo(cx, || is_supported_url(value.get().trim()));
 
-    let btn_disabled = create_memo(cx, || *loading.get() || pastebin_id.get().is_none());
+    let btn_disabled = create_memo(cx, || *loading.get() || !*is_supported.get());
 
-    let submit = |_| {
-        if let Some(id) = pastebin_id.get().as_ref() {
-            loading.set(true);
-            sycamore_router::navigate(id);
+    let submit = move |ev: web_sys::Event| {
+        ev.prevent_default();
+        if *btn_disabled.get() {
+            return;
         }
+
+        error.set(String::new());
+        loading.set(true);
+
+        #[cfg(feature = "browser")]
+        sycamore::futures::spawn_local_scoped(cx, async move {
+            let url = value.get();
+            match crate::api::import_url(url.trim()).await {
+                Err(err) => {
+                    loading.set(false);
+                    error.set(err.to_string());
+                    tracing::info!("{:?}", err);
+                }
+                Ok(id) => sycamore_router::navigate(&id.to_url().into_cow()),
+            }
+        });
     };
 
+    let on_input = |_| error.set(String::new());
+
     let btn_content = memo_cond!(cx, loading, svg::SPINNER, "Import");
 
     view! { cx,
         div(class="flex flex-col gap-y-1") {
-            div(class="dark:text-slate-200 text-slate-800") { "Import from pastebin.com" }
+            div(class="dark:text-slate-200 text-slate-800") {
+                "Import from pastebin.com, poe.ninja, rentry.co, poedb.tw or a GitHub gist"
+            }
             form(class="flex flex-wrap items-center justify-end gap-3") {
-                input(class="input flex-1 basis-[14rem]", bind:value=value) {}
+                input(class="input flex-1 basis-[14rem]", bind:value=value, on:input=on_input) {}
                 button(
                     class="btn btn-primary min-w-[100px]",
                     type="submit",
@@ -39,13 +55,25 @@ pub fn ImportPastebin<G: Html>(cx: Scope) -> View<G> {
                     dangerously_set_inner_html=&btn_content.get()
                 ) {}
             }
+            div(class="text-red-500") { (*error.get()) }
         }
     }
 }
 
-fn is_pastebin_id(candidate: &str) -> bool {
-    candidate.len() == 8
-        && candidate
-            .bytes()
-            .all(|c| matches!(c, b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9'))
+/// Whether the URL points to a build on a supported site.
+///
+/// The server does the full validation, this only checks the host.
+fn is_supported_url(url: &str) -> bool {
+    let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let Some((host, path)) = rest.split_once('/') else {
+        return false;
+    };
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    IMPORT_HOSTS.contains(&host) && !path.trim_matches('/').is_empty()
 }
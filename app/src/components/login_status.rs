@@ -11,9 +11,18 @@ pub fn LoginStatus<G: Html>(cx: Scope) -> View<G> {
     let session = use_context::<SessionValue>(cx);
 
     let name = create_memo(cx, move || {
-        let logout = |e: Event| {
-            session.set(Session::logout());
+        let logout = move |e: Event| {
             e.prevent_default();
+
+            // The session is also cleared when revoking it fails,
+            // the user expects to be logged out either way.
+            #[cfg(feature = "browser")]
+            sycamore::futures::spawn_local_scoped(cx, async move {
+                if let Err(err) = crate::api::logout().await {
+                    tracing::error!("logout failed: {:?}", err);
+                }
+                session.set(Session::logout());
+            });
         };
 
         match &*session.get() {
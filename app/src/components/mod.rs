@@ -1,4 +1,7 @@
+mod api_tokens;
+mod collections;
 mod create_paste;
+mod import_character;
 mod import_pastebin;
 mod login_status;
 mod paste_history;
@@ -12,11 +15,16 @@ mod pob_item_set;
 mod pob_loadout;
 mod pob_tree_preview;
 mod popup;
+mod share_link;
 mod static_popup;
 mod tree_node;
 mod view_paste;
+mod webhooks;
 
+pub use self::api_tokens::ApiTokens;
+pub use self::collections::Collections;
 pub use self::create_paste::{CreatePaste, CreatePasteProps};
+pub use self::import_character::ImportCharacter;
 pub use self::import_pastebin::ImportPastebin;
 pub use self::login_status::LoginStatus;
 pub use self::paste_history::PasteHistory;
@@ -24,12 +32,14 @@ pub use self::paste_toolbox::PasteToolbox;
 pub use self::pob_colored_select::PobColoredSelect;
 pub use self::pob_colored_text::PobColoredText;
 pub use self::pob_gear_preview::PobGearPreview;
-pub use self::pob_gems::PobGems;
+pub use self::pob_gems::{PobGems, PobMainSkill};
 pub use self::pob_item::PobItem;
 pub use self::pob_item_set::PobItemSet;
 pub use self::pob_loadout::PobLoadout;
 pub use self::pob_tree_preview::PobTreePreview;
 pub use self::popup::Popup;
+pub use self::share_link::ShareLink;
 pub use self::static_popup::StaticPopup;
 pub use self::tree_node::TreeNode;
 pub use self::view_paste::{ViewPaste, ViewPasteProps};
+pub use self::webhooks::Webhooks;
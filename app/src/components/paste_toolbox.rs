@@ -56,6 +56,12 @@ pub fn PasteToolbox<'a, G: Html>(
                 ev.stop_propagation();
                 ev.prevent_default();
             };
+            let history_href = id.to_history_url().into_cow();
+            let on_history = move |ev: web_sys::Event| {
+                sycamore_router::navigate(&history_href);
+                ev.stop_propagation();
+                ev.prevent_default();
+            };
 
             view! { cx,
                 div(class="flex justify-end gap-2 h-4") {
@@ -67,6 +73,11 @@ pub fn PasteToolbox<'a, G: Html>(
                       class="w-4 cursor-pointer",
                       title="Edit",
                       dangerously_set_inner_html=svg::PEN) {}
+                    a(on:click=on_history,
+                      href="javascript:void(0)",
+                      class="w-4 cursor-pointer",
+                      title="History",
+                      dangerously_set_inner_html=svg::HISTORY) {}
                     span(on:click=on_delete_cb,
                          class="w-4 text-red-600 cursor-pointer",
                          title="Delete",
@@ -78,6 +78,21 @@ pub fn PobGems<'a, G: Html>(cx: Scope<'a>, build: &'a Build) -> View<G> {
     }
 }
 
+/// The main skill group of the current skill set.
+#[component]
+pub fn PobMainSkill<'a, G: Html>(cx: Scope<'a>, build: &'a Build) -> View<G> {
+    let skill = build
+        .current_skill_set()
+        .and_then(|ss| ss.skills.into_iter().find(|skill| skill.is_selected));
+
+    match skill {
+        Some(skill) => view! { cx,
+            div(class="leading-[1.35rem]") { (render_skill(cx, skill, build.data())) }
+        },
+        None => view! { cx, div() { "No Main Skill" } },
+    }
+}
+
 #[derive(Debug)]
 struct PopupGem {
     id: String,
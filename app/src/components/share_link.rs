@@ -0,0 +1,86 @@
+use shared::{model::ShareLink as Link, UserPasteId};
+use sycamore::prelude::*;
+
+use crate::{
+    consts::SELF_URL,
+    utils::{async_callback, view_if},
+};
+
+#[derive(Prop)]
+pub struct ShareLinkProps {
+    id: UserPasteId,
+}
+
+/// Management of the unlisted share link of a private paste.
+///
+/// The link is only loaded in the browser, it is never rendered server side.
+#[component]
+pub fn ShareLink<G: Html>(cx: Scope, ShareLinkProps { id }: ShareLinkProps) -> View<G> {
+    let id = create_ref(cx, id);
+    let link = create_signal(cx, None::<Link>);
+
+    #[cfg(feature = "browser")]
+    if G::IS_BROWSER {
+        sycamore::futures::spawn_local_scoped(cx, async move {
+            match crate::api::get_paste_share(id).await {
+                Ok(l) => link.set(l),
+                Err(err) => tracing::error!("loading share link failed: {:?}", err),
+            }
+        });
+    }
+
+    let on_create = async_callback!(
+        cx,
+        {
+            match crate::api::create_paste_share(id).await {
+                Err(err) => tracing::error!("creating share link failed: {:?}", err),
+                Ok(l) => link.set(Some(l)),
+            }
+        },
+        true
+    );
+
+    let on_revoke = async_callback!(
+        cx,
+        {
+            match crate::api::revoke_paste_share(id).await {
+                Err(err) => tracing::error!("revoking share link failed: {:?}", err),
+                Ok(()) => link.set(None),
+            }
+        },
+        {
+            let message = "Are you sure you want to revoke the share link?".to_owned();
+            web_sys::window()
+                .unwrap()
+                .confirm_with_message(&message)
+                .unwrap_or_default()
+        }
+    );
+
+    let url = create_memo(cx, || {
+        link.get()
+            .as_ref()
+            .map(|link| format!("{SELF_URL}{}?share={}", id.to_paste_url(), link.token))
+    });
+    let has_link = create_memo(cx, || url.get().is_some());
+    let revoke = view_if!(cx, has_link, {
+        button(on:click=on_revoke, class="btn btn-secondary") { "Revoke" }
+    });
+
+    view! { cx,
+        div(class="flex gap-2 items-center") {
+            input(
+                class="input flex-auto",
+                type="text",
+                readonly=true,
+                aria-label="Share link",
+                placeholder="No share link",
+                value=url.get().as_deref().unwrap_or_default().to_owned(),
+            ) {}
+            button(on:click=on_create, class="btn btn-secondary", title="Create a new link, the previous link stops working") {
+                "New Link"
+            }
+            (&*revoke.get())
+        }
+    }
+}
@@ -9,7 +9,8 @@ use crate::{
     components::{PobColoredText, PobGearPreview, PobGems, PobLoadout, PobTreePreview},
     consts::{IMG_ONERROR_HIDDEN, SELF_URL},
     pob::{self, Element},
-    utils::{self, view_cond, IteratorExt},
+    session::SessionValue,
+    utils::{self, view_cond, view_if, IteratorExt},
 };
 
 pub struct ViewPasteProps<'a> {
@@ -128,6 +129,73 @@ pub fn ViewPaste<'a, G: Html>(
         *copy_state.get() == CopyState::Ready
     );
 
+    let session = use_context::<SessionValue>(cx);
+    let is_logged_in = create_memo(cx, || session.get().is_logged_in());
+
+    let paste_id = create_ref(cx, id.clone());
+    let fork_id = create_signal(cx, String::new());
+    let on_fork = utils::async_callback!(
+        cx,
+        {
+            let custom_id = fork_id.get();
+            let fork = crate::api::ForkPaste {
+                custom_id: &custom_id,
+            };
+            match crate::api::fork_paste(paste_id, fork).await {
+                Err(err) => tracing::error!("forking failed: {:?}", err),
+                Ok(id) => sycamore_router::navigate(&id.to_url().into_cow()),
+            }
+        },
+        {
+            let message = "Fork this build into your profile, leave empty for a random id:";
+            match web_sys::window()
+                .unwrap()
+                .prompt_with_message_and_default(message, "")
+            {
+                Ok(Some(custom_id)) => {
+                    fork_id.set(custom_id.trim().to_owned());
+                    true
+                }
+                _ => false,
+            }
+        }
+    );
+    let fork = view_if!(cx, is_logged_in, {
+        button(
+            on:click=on_fork,
+            title="Fork this build into your profile",
+            class="hover:underline hover:cursor-pointer px-3 py-2 text-sm inline-flex"
+        ) { "Fork" }
+    });
+
+    let report_reason = create_signal(cx, String::new());
+    let on_report = utils::async_callback!(
+        cx,
+        {
+            let reason = report_reason.get();
+            match crate::api::report_paste(paste_id, &reason).await {
+                Err(err) => tracing::error!("reporting failed: {:?}", err),
+                Ok(()) => {
+                    let message = "Thank you, the build will be reviewed.";
+                    let _ = web_sys::window().unwrap().alert_with_message(message);
+                }
+            }
+        },
+        {
+            let message = "Report this build to the moderators, please describe the problem:";
+            match web_sys::window()
+                .unwrap()
+                .prompt_with_message_and_default(message, "")
+            {
+                Ok(Some(reason)) if !reason.trim().is_empty() => {
+                    report_reason.set(reason.trim().to_owned());
+                    true
+                }
+                _ => false,
+            }
+        }
+    );
+
     let btn_copy_name = create_memo(cx, || copy_state.get().name());
     let btn_copy_disabled = create_memo(cx, || *copy_state.get() != CopyState::Ready);
 
@@ -176,6 +244,12 @@ pub fn ViewPaste<'a, G: Html>(
                     (build.content)
                 }
                 div(class="flex gap-3 self-end") {
+                    button(
+                        on:click=on_report,
+                        title="Report this build to the moderators",
+                        class="hover:underline hover:cursor-pointer px-3 py-2 text-sm inline-flex text-slate-500"
+                    ) { "Report" }
+                    (&*fork.get())
                     button(
                         on:click=copy_to_clipboard,
                         disabled=*btn_copy_disabled.get(),
@@ -255,6 +329,8 @@ fn push_paste_to_history<G: Html>(
             last_modified,
             rank: None,
             private: false,
+            tags: Vec::new(),
+            description: String::new(),
         };
 
         sycamore::futures::spawn_local_scoped(cx, async move {
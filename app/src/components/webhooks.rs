@@ -0,0 +1,133 @@
+use shared::model::{Webhook, WebhookFormat};
+use sycamore::prelude::*;
+
+use crate::utils::{async_callback, pretty_date_ts, view_if};
+
+/// Management of the outgoing webhooks of the current user.
+///
+/// Webhooks are only loaded in the browser, they are never rendered server side.
+#[component]
+pub fn Webhooks<G: Html>(cx: Scope) -> View<G> {
+    let webhooks = create_signal(cx, Vec::<Webhook>::new());
+    let new_secret = create_signal(cx, None::<String>);
+    let url = create_signal(cx, String::new());
+    let discord = create_signal(cx, false);
+
+    #[cfg(feature = "browser")]
+    if G::IS_BROWSER {
+        sycamore::futures::spawn_local_scoped(cx, async move {
+            match crate::api::get_webhooks().await {
+                Ok(w) => webhooks.set(w),
+                Err(err) => tracing::error!("loading webhooks failed: {:?}", err),
+            }
+        });
+    }
+
+    let on_create = async_callback!(
+        cx,
+        {
+            let webhook_url = url.get();
+            let format = if *discord.get() {
+                WebhookFormat::Discord
+            } else {
+                WebhookFormat::Json
+            };
+            match crate::api::create_webhook(webhook_url.trim(), format).await {
+                Err(err) => tracing::error!("creating webhook failed: {:?}", err),
+                Ok(created) => {
+                    webhooks.modify().push(created.info);
+                    new_secret.set(Some(created.secret));
+                    url.set(String::new());
+                }
+            }
+        },
+        url.get().trim().starts_with("https://")
+    );
+
+    let new_secret_view = create_memo(cx, || match &*new_secret.get() {
+        Some(secret) => {
+            let secret = secret.clone();
+            view! { cx,
+                div(class="flex flex-col gap-1 text-sm") {
+                    span(class="text-amber-50") {
+                        "Copy the signing secret now, it will not be shown again:"
+                    }
+                    code(class="break-all bg-slate-600 p-2 rounded-sm select-all") { (secret) }
+                }
+            }
+        }
+        None => view! { cx, },
+    });
+
+    let is_empty = create_memo(cx, || webhooks.get().is_empty());
+    let empty = view_if!(cx, is_empty, {
+        span(class="text-sm text-slate-400") { "There are no webhooks yet .." }
+    });
+
+    view! { cx,
+        details(class="mb-4") {
+            summary(class="cursor-pointer text-sky-400") { "Webhooks" }
+            div(class="flex flex-col gap-3 mt-3") {
+                p(class="text-sm text-slate-400") {
+                    "Webhooks are notified when one of your builds is created, updated or deleted. "
+                    "Payloads are signed with HMAC-SHA256, the signature is sent in the "
+                    code { "X-Pobbin-Signature-256" }
+                    " header."
+                }
+                Indexed(
+                    iterable=webhooks,
+                    view=move |cx, webhook| {
+                        let id = create_ref(cx, webhook.id);
+                        let on_delete = async_callback!(
+                            cx,
+                            {
+                                match crate::api::delete_webhook(id).await {
+                                    Err(err) => tracing::error!("deleting webhook failed: {:?}", err),
+                                    Ok(()) => webhooks.modify().retain(|w| &w.id != id),
+                                }
+                            },
+                            {
+                                let message = "Are you sure you want to delete this webhook?".to_owned();
+                                web_sys::window()
+                                    .unwrap()
+                                    .confirm_with_message(&message)
+                                    .unwrap_or_default()
+                            }
+                        );
+                        let format = match webhook.format {
+                            WebhookFormat::Json => "JSON",
+                            WebhookFormat::Discord => "Discord",
+                        };
+                        view! { cx,
+                            div(class="flex gap-4 items-center even:bg-slate-700 p-2") {
+                                span(class="flex-auto text-amber-50 break-all") { (webhook.url) }
+                                span(class="text-sm text-slate-400") { (format) }
+                                span(class="text-sm text-slate-400") { (pretty_date_ts(webhook.created)) }
+                                button(on:click=on_delete, class="btn btn-secondary", title="Delete this webhook") {
+                                    "Delete"
+                                }
+                            }
+                        }
+                    }
+                )
+                (&*empty.get())
+                div(class="flex gap-2 items-center") {
+                    input(
+                        class="input flex-auto",
+                        type="url",
+                        maxlength=500,
+                        placeholder="https://discord.com/api/webhooks/...",
+                        aria-label="Webhook URL",
+                        bind:value=url
+                    ) {}
+                    label(class="text-sm whitespace-nowrap") {
+                        input(type="checkbox", class="mx-2", bind:checked=discord) {}
+                        "Discord"
+                    }
+                    button(on:click=on_create, class="btn btn-primary") { "Add Webhook" }
+                }
+                (&*new_secret_view.get())
+            }
+        }
+    }
+}
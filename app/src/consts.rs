@@ -24,6 +24,17 @@ pub const LINK_WHITELIST: [&str; 13] = [
     "youtube.com",
 ];
 
+/// Hosts of the sites builds can be imported from.
+pub const IMPORT_HOSTS: [&str; 7] = [
+    "pastebin.com",
+    "poe.ninja",
+    "pastebinp.com",
+    "rentry.co",
+    "poedb.tw",
+    "gist.github.com",
+    "gist.githubusercontent.com",
+];
+
 pub const POE_WIKI: &str = "https://www.poewiki.net/wiki/";
 
 pub const SELF_URL: &str = "https://pobb.in";
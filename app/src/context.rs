@@ -1,5 +1,5 @@
 use shared::{
-    model::{data, PasteMetadata, PasteSummary},
+    model::{data, CollectionPage, ListPastesQuery, PasteMetadata, PastePage, PasteRevision},
     PasteId, UserPasteId,
 };
 
@@ -40,10 +40,20 @@ impl Context {
         }
     }
 
-    pub fn user(name: shared::User, pastes: Vec<PasteSummary>) -> Self {
+    pub fn user(name: shared::User, page: PastePage, query: ListPastesQuery) -> Self {
         Self {
             route: Ok(Route::User(name)),
-            inner: Inner::User(pastes),
+            inner: Inner::User(page, query),
+        }
+    }
+
+    pub fn user_collection(page: CollectionPage) -> Self {
+        Self {
+            route: Ok(Route::UserCollection(
+                page.user.clone(),
+                page.collection.id.clone(),
+            )),
+            inner: Inner::Collection(page),
         }
     }
 
@@ -55,6 +65,17 @@ impl Context {
         }
     }
 
+    pub fn embed(id: PasteId, paste: shared::model::Paste) -> Self {
+        let route = match id {
+            PasteId::Paste(id) => Route::Embed(id),
+            PasteId::UserPaste(up) => Route::UserEmbed(up.user, up.id),
+        };
+        Self {
+            route: Ok(route),
+            inner: paste.into(),
+        }
+    }
+
     pub fn user_paste_edit(up: UserPasteId, paste: shared::model::Paste) -> Self {
         Self {
             route: Ok(Route::UserEditPaste(up.user, up.id)),
@@ -62,6 +83,13 @@ impl Context {
         }
     }
 
+    pub fn user_paste_history(up: UserPasteId, revisions: Vec<PasteRevision>) -> Self {
+        Self {
+            route: Ok(Route::UserPasteHistory(up.user, up.id)),
+            inner: Inner::Revisions(revisions),
+        }
+    }
+
     pub fn route(&self) -> Result<&Route, &crate::Error> {
         self.route.as_ref()
     }
@@ -81,9 +109,23 @@ impl Context {
         }
     }
 
-    pub fn get_user(&self) -> Option<&Vec<PasteSummary>> {
+    pub fn into_user(self) -> Option<(PastePage, ListPastesQuery)> {
+        match self.inner {
+            Inner::User(page, query) => Some((page, query)),
+            _ => None,
+        }
+    }
+
+    pub fn into_collection(self) -> Option<CollectionPage> {
+        match self.inner {
+            Inner::Collection(page) => Some(page),
+            _ => None,
+        }
+    }
+
+    pub fn into_revisions(self) -> Option<Vec<PasteRevision>> {
         match self.inner {
-            Inner::User(ref pastes) => Some(pastes),
+            Inner::Revisions(revisions) => Some(revisions),
             _ => None,
         }
     }
@@ -99,7 +141,9 @@ pub struct Paste {
 enum Inner {
     None,
     Paste(Paste),
-    User(Vec<PasteSummary>),
+    User(PastePage, ListPastesQuery),
+    Collection(CollectionPage),
+    Revisions(Vec<PasteRevision>),
 }
 
 impl From<shared::model::Paste> for Inner {
@@ -9,6 +9,10 @@ pub enum Error {
     #[error("The requested {0} with id {1} does not exist")]
     NotFound(&'static str, String),
 
+    /// Error indicating a page cannot be loaded, because a critical resource has been removed.
+    #[error("The requested {0} with id {1} has been removed")]
+    Gone(&'static str, String),
+
     #[error("{0}: {1}")]
     UnhandledStatus(u16, String),
 
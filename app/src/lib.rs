@@ -63,6 +63,14 @@ pub fn App<G: Html>(cx: Scope, ctx: Option<Context>) -> View<G> {
     provide_session::<G>(cx);
     provide_storage::<G>(cx);
 
+    if is_embed(ctx.as_ref()) {
+        return view! { cx,
+            main(class="w-full") {
+                router::Router(ctx)
+            }
+        };
+    }
+
     let view: View<G> = view! { cx,
         progress::Progress()
         div(class="min-h-screen flex flex-col gap-10") {
@@ -108,3 +116,13 @@ pub fn App<G: Html>(cx: Scope, ctx: Option<Context>) -> View<G> {
 
     view
 }
+
+/// Whether the current page is embedded in another site.
+fn is_embed(ctx: Option<&Context>) -> bool {
+    match ctx {
+        Some(ctx) => ctx.route().is_ok_and(|route| route.is_embed()),
+        None => web_sys::window()
+            .and_then(|window| window.location().pathname().ok())
+            .is_some_and(|path| path.starts_with("/embed/")),
+    }
+}
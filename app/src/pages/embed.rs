@@ -0,0 +1,133 @@
+use std::convert::TryInto;
+
+use ::pob::PathOfBuildingExt;
+use shared::PasteId;
+use sycamore::prelude::*;
+
+use crate::{
+    build::Build,
+    components::{PobGearPreview, PobMainSkill},
+    consts::{IMG_ONERROR_HIDDEN, SELF_URL},
+    future::LocalBoxFuture,
+    meta, pob,
+    router::RoutedComponent,
+    utils::{deserialize_attribute, find_text, serialize_for_attribute, IteratorExt},
+    Meta, Result,
+};
+
+/// Compact view of a paste for embedding in other sites.
+pub struct EmbedPage {
+    id: PasteId,
+    title: Option<String>,
+    build: Build,
+}
+
+impl RoutedComponent for EmbedPage {
+    type RouteArg = PasteId;
+
+    fn from_context(id: Self::RouteArg, ctx: crate::Context) -> Result<Self> {
+        let mut paste = ctx.into_paste().unwrap();
+        let title = paste.metadata.take().map(|m| m.title);
+
+        Ok(Self {
+            id,
+            title,
+            build: paste.try_into()?,
+        })
+    }
+
+    fn from_hydration(id: Self::RouteArg, element: web_sys::Element) -> Result<Self> {
+        let content = find_text(&element, "[data-marker-content]").unwrap_or_default();
+        let title = find_text(&element, "[data-marker-title]");
+        let data = deserialize_attribute(&element, "data-data").unwrap_or_default();
+
+        let build = Build::new(content, data)?;
+        Ok(Self { id, title, build })
+    }
+
+    fn from_dynamic<'a>(id: Self::RouteArg) -> LocalBoxFuture<'a, Result<Self>> {
+        Box::pin(async move {
+            let mut paste = crate::api::get_paste(&id).await?;
+            let title = paste.metadata.take().map(|x| x.title);
+
+            Ok(Self {
+                id,
+                title,
+                build: paste.try_into()?,
+            })
+        })
+    }
+
+    fn meta(&self) -> Result<Meta> {
+        let pob = self.build.pob();
+
+        let title = self.title.clone().unwrap_or_else(|| pob::title(pob)).into();
+        let description = meta::get_paste_summary(pob).join("\n").into();
+        let image = format!("{SELF_URL}{}", self.id.to_url().join(pob::preview::PATH)).into();
+        let color = meta::get_color(pob.ascendancy_or_class());
+
+        Ok(Meta {
+            title,
+            description,
+            image,
+            color,
+            ..Default::default()
+        })
+    }
+
+    fn render<G: Html>(self, cx: Scope) -> View<G> {
+        view! { cx, EmbedPageComponent(self) }
+    }
+}
+
+#[component]
+fn EmbedPageComponent<G: Html>(cx: Scope, EmbedPage { id, title, build }: EmbedPage) -> View<G> {
+    let build = create_ref(cx, build);
+    let data = serialize_for_attribute::<G>(build.data());
+
+    let title = title.unwrap_or_else(|| pob::title(build.pob()));
+    let href = format!("{SELF_URL}{}", id.to_url());
+    let src = crate::assets::ascendancy_image(build.ascendancy_or_class());
+
+    let summary = [
+        pob::summary::core_stats(build.pob()),
+        pob::summary::defense(build.pob()),
+        pob::summary::offense(build.pob()),
+    ]
+    .into_iter()
+    .map(|stats| {
+        stats
+            .into_iter()
+            .filter_map(|stat| stat.render_to_view(cx))
+            .collect_view()
+    })
+    .map(|stats| view! { cx, div(class="flex-row gap-x-5") { (stats) } })
+    .collect_view();
+
+    view! { cx,
+        div(data-data=data) {}
+        div(class="hidden", data-marker-content="") { (build.content) }
+        div(class="flex flex-col gap-y-2 p-3") {
+            h1(class="flex items-center text-lg dark:text-slate-100 text-slate-900") {
+                img(src=src,
+                    class="asc-image rounded-full mr-3",
+                    alt="Ascendancy Thumbnail",
+                    onerror=IMG_ONERROR_HIDDEN) {}
+                // Leave the iframe, the router would navigate inside of it.
+                a(href=href, target="_blank", rel="external noopener",
+                    class="hover:underline", data-marker-title="") { (title) }
+            }
+            (summary)
+            div(class="flex flex-wrap gap-x-6 gap-y-4 mt-3") {
+                div(class="flex-auto w-60") {
+                    h2(class="text-lg dark:text-slate-100 text-slate-900 mb-2 border-b border-solid") { "Gear" }
+                    PobGearPreview(build)
+                }
+                div(class="flex-auto w-60") {
+                    h2(class="text-lg dark:text-slate-100 text-slate-900 mb-2 border-b border-solid") { "Main Skill" }
+                    PobMainSkill(build)
+                }
+            }
+        }
+    }
+}
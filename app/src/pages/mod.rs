@@ -1,11 +1,17 @@
+pub(crate) mod embed;
 pub(crate) mod index;
 pub(crate) mod paste;
 pub(crate) mod user;
+pub(crate) mod user_collection;
 pub(crate) mod user_edit;
 pub(crate) mod user_paste;
+pub(crate) mod user_paste_history;
 
+pub use self::embed::EmbedPage;
 pub use self::index::IndexPage;
 pub use self::paste::PastePage;
 pub use self::user::UserPage;
+pub use self::user_collection::UserCollectionPage;
 pub use self::user_edit::UserEditPastePage;
 pub use self::user_paste::UserPastePage;
+pub use self::user_paste_history::UserPasteHistoryPage;
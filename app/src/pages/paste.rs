@@ -7,6 +7,7 @@ use sycamore::prelude::*;
 use crate::{
     build::Build,
     components::{ViewPaste, ViewPasteProps},
+    consts::SELF_URL,
     future::LocalBoxFuture,
     meta, pob,
     router::RoutedComponent,
@@ -85,15 +86,17 @@ impl RoutedComponent for PastePage {
 
         let description = meta::get_paste_summary(pob).join("\n").into();
 
-        let image = crate::assets::ascendancy_image(pob.ascendancy_or_class()).into();
+        let image = format!("{SELF_URL}/{}/{}", self.id, pob::preview::PATH).into();
         let color = meta::get_color(pob.ascendancy_or_class());
 
+        let oembed = format!("/oembed.json?url={SELF_URL}/{}", self.id).into();
+
         Ok(Meta {
             title,
             description,
             image,
             color,
-            ..Default::default()
+            oembed,
         })
     }
 
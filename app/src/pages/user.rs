@@ -1,56 +1,66 @@
-use shared::{model::PasteSummary, User};
+use shared::{
+    model::{ListPastesQuery, PastePage, PasteSort, PasteSummary},
+    GameVersion, User,
+};
 use sycamore::prelude::*;
 
 use crate::{
-    components::PasteToolbox,
+    components::{ApiTokens, Collections, ImportCharacter, PasteToolbox, Webhooks},
     consts::{IMG_ONERROR_HIDDEN, IMG_ONERROR_INVISIBLE},
     future::LocalBoxFuture,
     router::RoutedComponent,
+    session::SessionValue,
     utils::{
-        deserialize_attribute, memo_cond, open_in_new_tab, pretty_date_ts, serialize_for_attribute,
+        async_callback, deserialize_attribute, memo_cond, open_in_new_tab, pretty_date_ts,
+        serialize_for_attribute, IteratorExt,
     },
     Meta, Result,
 };
 
 pub struct UserPage {
     name: User,
-    pastes: Vec<PasteSummary>,
+    page: PastePage,
+    query: ListPastesQuery,
 }
 
 impl RoutedComponent for UserPage {
     type RouteArg = User;
 
     fn from_context(name: Self::RouteArg, ctx: crate::Context) -> Result<Self> {
-        Ok(Self {
-            name,
-            pastes: ctx.get_user().unwrap().to_vec(),
-        })
+        let (page, query) = ctx.into_user().unwrap();
+        Ok(Self { name, page, query })
     }
 
     fn from_hydration(name: Self::RouteArg, element: web_sys::Element) -> Result<Self> {
-        let pastes = deserialize_attribute(&element, "data-ssr").unwrap_or_default();
+        let page = deserialize_attribute(&element, "data-ssr").unwrap_or_default();
+        let query = deserialize_attribute(&element, "data-query").unwrap_or_default();
 
-        Ok(Self { name, pastes })
+        Ok(Self { name, page, query })
     }
 
     fn from_dynamic<'a>(name: Self::RouteArg) -> LocalBoxFuture<'a, Result<Self>> {
         Box::pin(async move {
-            let pastes = crate::api::get_user(&name).await?;
-            Ok(Self { name, pastes })
+            let query = web_sys::window()
+                .and_then(|window| window.location().search().ok())
+                .map(|search| ListPastesQuery::from_query_string(&search))
+                .unwrap_or_default();
+            let page = crate::api::get_user(&name, &query).await?;
+            Ok(Self { name, page, query })
         })
     }
 
     fn meta(&self) -> Result<Meta> {
-        let Self { name, pastes } = self;
+        let Self { name, page, .. } = self;
         let title = format!("{name}'s builds").into();
 
-        let mut summary = pastes
+        let mut summary = page
+            .pastes
             .iter()
             .take(3)
             .map(|paste| format!("\u{27A4} {}", paste.title))
             .collect::<Vec<_>>();
-        if pastes.len() > 3 {
-            summary.push(format!("\u{27A4} .. {} more builds", pastes.len() - 3));
+        if page.total > 3 {
+            summary.push(format!("\u{27A4} .. {} more builds", page.total - 3));
         }
         if summary.is_empty() {
             summary.push("\u{27A4} there aren't any builds yet".to_owned());
@@ -73,10 +83,69 @@ impl RoutedComponent for UserPage {
 }
 
 #[component]
-pub fn UserPageComponent<G: Html>(cx: Scope, UserPage { name, pastes }: UserPage) -> View<G> {
-    let data_ssr = serialize_for_attribute::<G>(&pastes);
+pub fn UserPageComponent<G: Html>(cx: Scope, UserPage { name, page, query }: UserPage) -> View<G> {
+    let data_ssr = serialize_for_attribute::<G>(&page);
+    let data_query = serialize_for_attribute::<G>(&query);
+
+    let user_url = name.to_url().to_string();
+    let next_page = page.next.map(|next| {
+        let query = query.with_cursor(Some(next)).to_query_string();
+        format!("{user_url}{query}")
+    });
+    let first_page = query.cursor.is_some().then(|| {
+        let query = query.with_cursor(None).to_query_string();
+        format!("{user_url}{query}")
+    });
+    let total = page.total;
+    let filter = view! { cx, UserPasteFilter(name=name.clone(), query=query) };
+    let collections = view! { cx, Collections(user=name.clone()) };
+
+    let session = use_context::<SessionValue>(cx);
+    let owner = create_ref(cx, name.clone());
+    let is_current_user = create_memo(cx, || {
+        Some(owner.as_str()) == session.get().user().map(|u| u.name.as_str())
+    });
+    let on_logout_everywhere = async_callback!(
+        cx,
+        {
+            match crate::api::logout_everywhere().await {
+                Err(err) => tracing::error!("logging out everywhere failed: {:?}", err),
+                Ok(()) => {
+                    session.set(crate::session::Session::logout());
+                    sycamore_router::navigate("/");
+                }
+            }
+        },
+        {
+            let message = "Are you sure you want to log out on all devices?".to_owned();
+            web_sys::window()
+                .unwrap()
+                .confirm_with_message(&message)
+                .unwrap_or_default()
+        }
+    );
+    let account = memo_cond!(
+        cx,
+        is_current_user,
+        view! { cx,
+            div(class="flex flex-wrap gap-4 items-start justify-between") {
+                ApiTokens {}
+                Webhooks {}
+                ImportCharacter {}
+                button(
+                    on:click=on_logout_everywhere,
+                    class="btn btn-secondary mb-4",
+                    title="Invalidate all sessions, including this one"
+                ) {
+                    "Log out everywhere"
+                }
+            }
+        },
+        view! { cx, }
+    );
 
-    let p = pastes
+    let p = page
+        .pastes
         .into_iter()
         .map(|summary| {
             let deleted = create_signal(cx, false);
@@ -99,18 +168,127 @@ pub fn UserPageComponent<G: Html>(cx: Scope, UserPage { name, pastes }: UserPage
         }
     };
 
+    // Pagination links are external, the router does not re-create
+    // the page when only the query changes.
+    let first_page = match first_page {
+        Some(href) => view! { cx,
+            a(href=href, rel="external", class="text-sky-400 hover:underline") { "First page" }
+        },
+        None => view! { cx, },
+    };
+    let next_page = match next_page {
+        Some(href) => view! { cx,
+            a(href=href, rel="external", class="text-sky-400 hover:underline") { "Next page" }
+        },
+        None => view! { cx, },
+    };
+
     view! { cx,
         h1(class="text-amber-50 text-xl mb-4") {
             span { (name) }
             span { "'s builds" }
+            span(class="ml-2 text-sm text-slate-400") { "(" (total) ")" }
         }
-        div(data-ssr=data_ssr, class="flex flex-col gap-2") {
+        (&*account.get())
+        (collections)
+        (filter)
+        div(data-ssr=data_ssr, data-query=data_query, class="flex flex-col gap-2") {
             (p)
         }
+        div(class="flex justify-between mt-4") {
+            div { (first_page) }
+            div { (next_page) }
+        }
     }
 }
 
-fn summary_to_view<'a, G: GenericNode + Html>(
+#[derive(Prop)]
+struct UserPasteFilterProps {
+    name: User,
+    query: ListPastesQuery,
+}
+
+/// Filter form for the user's pastes.
+///
+/// The form is submitted as a regular GET request, the query parameters
+/// are interpreted by [`ListPastesQuery::from_query_string`].
+#[component]
+fn UserPasteFilter<G: Html>(
+    cx: Scope,
+    UserPasteFilterProps { name, query }: UserPasteFilterProps,
+) -> View<G> {
+    let session = use_context::<SessionValue>(cx);
+    let name = create_ref(cx, name);
+    let is_current_user = create_memo(cx, || {
+        Some(name.as_str()) == session.get().user().map(|u| u.name.as_str())
+    });
+
+    let sort = PasteSort::ALL
+        .into_iter()
+        .map(|sort| {
+            let label = match sort {
+                PasteSort::Pinned => "Pinned first",
+                PasteSort::Newest => "Newest",
+                PasteSort::Oldest => "Oldest",
+                PasteSort::Title => "Title",
+            };
+            let selected = sort == query.sort;
+            view! { cx, option(value=sort.as_str(), selected=selected) { (label) } }
+        })
+        .collect_view();
+
+    let game_version = [("", "All Games"), ("1", "PoE 1"), ("2", "PoE 2")]
+        .into_iter()
+        .map(|(value, label)| {
+            let selected = match query.game_version {
+                Some(GameVersion::One) => value == "1",
+                Some(GameVersion::Two) => value == "2",
+                None => value.is_empty(),
+            };
+            view! { cx, option(value=value, selected=selected) { (label) } }
+        })
+        .collect_view();
+
+    let private = query.private;
+    let visibility = memo_cond!(
+        cx,
+        is_current_user,
+        {
+            let options = [("", "All"), ("false", "Public"), ("true", "Private")]
+                .into_iter()
+                .map(|(value, label)| {
+                    let selected = private.map(|p| p.to_string()).as_deref().unwrap_or("") == value;
+                    view! { cx, option(value=value, selected=selected) { (label) } }
+                })
+                .collect_view();
+            view! { cx,
+                select(name="private", class="input", aria-label="Visibility") { (options) }
+            }
+        },
+        view! { cx, }
+    );
+
+    let class = query.class.unwrap_or_default();
+    let skill = query.skill.unwrap_or_default();
+    let tag = query.tag.unwrap_or_default();
+
+    view! { cx,
+        form(method="get", action=name.to_url().to_string(), class="flex flex-wrap gap-2 mb-4 items-center") {
+            input(class="input", type="text", name="class", value=class,
+                placeholder="Class or Ascendancy", aria-label="Class or Ascendancy") {}
+            input(class="input", type="text", name="skill", value=skill,
+                placeholder="Main Skill", aria-label="Main Skill") {}
+            input(class="input", type="text", name="tag", value=tag,
+                placeholder="Tag", aria-label="Tag") {}
+            select(name="game_version", class="input", aria-label="Game Version") { (game_version) }
+            (&*visibility.get())
+            select(name="sort", class="input", aria-label="Sort") { (sort) }
+            button(type="submit", class="btn btn-primary") { "Filter" }
+        }
+    }
+}
+
+pub(crate) fn summary_to_view<'a, G: GenericNode + Html>(
     cx: Scope<'a>,
     summary: &'a PasteSummary,
     on_delete: &'a Signal<bool>,
@@ -129,6 +307,32 @@ fn summary_to_view<'a, G: GenericNode + Html>(
     let main_skill_image = crate::assets::item_image_url(summary.game_version, &main_skill_name);
     let main_skill_alt = main_skill_name.clone();
 
+    // Tags link to the user page filtered by the tag.
+    let user_url = id.user.to_url();
+    let tags = summary
+        .tags
+        .iter()
+        .map(|tag| {
+            let query = ListPastesQuery {
+                tag: Some(tag.clone()),
+                ..Default::default()
+            };
+            let href = format!("{user_url}{}", query.to_query_string());
+            let tag = tag.clone();
+            view! { cx,
+                a(href=href, rel="external", class="text-xs text-sky-400 bg-slate-600 rounded-sm px-1 hover:underline",
+                    on:click=|event: web_sys::Event| event.stop_propagation()) { (tag) }
+            }
+        })
+        .collect_view();
+    let description = match summary.description.as_str() {
+        "" => view! { cx, },
+        description => {
+            let description = description.to_owned();
+            view! { cx, span(class="text-sm text-slate-400") { (description) } }
+        }
+    };
+
     let pinned = summary.rank.is_some();
     let opacity = if summary.private { "0.5" } else { "1" };
     view! { cx,
@@ -158,6 +362,8 @@ fn summary_to_view<'a, G: GenericNode + Html>(
                         }
                         span { (main_skill_name) }
                     }
+                    (description)
+                    div(class="flex flex-wrap gap-1 empty:hidden") { (tags) }
                 }
                 div(class="flex-1 sm:flex-initial flex flex-col items-end justify-between
                            gap-2 whitespace-nowrap self-end md:self-center cursor-auto",
@@ -0,0 +1,322 @@
+use shared::{
+    model::{CollectionPage, PasteSummary},
+    Id, User,
+};
+use sycamore::prelude::*;
+
+use crate::{
+    future::LocalBoxFuture,
+    pages::user::summary_to_view,
+    router::RoutedComponent,
+    session::SessionValue,
+    utils::{async_callback, deserialize_attribute, memo_cond, serialize_for_attribute},
+    Meta, Result,
+};
+
+pub struct UserCollectionPage {
+    page: CollectionPage,
+}
+
+impl RoutedComponent for UserCollectionPage {
+    type RouteArg = (User, Id);
+
+    fn from_context(_: Self::RouteArg, ctx: crate::Context) -> Result<Self> {
+        Ok(Self {
+            page: ctx.into_collection().unwrap(),
+        })
+    }
+
+    fn from_hydration((user, id): Self::RouteArg, element: web_sys::Element) -> Result<Self> {
+        let page = deserialize_attribute(&element, "data-ssr")
+            .ok_or_else(|| crate::Error::NotFound("collection", format!("{user}:{id}")))?;
+        Ok(Self { page })
+    }
+
+    fn from_dynamic<'a>((user, id): Self::RouteArg) -> LocalBoxFuture<'a, Result<Self>> {
+        Box::pin(async move {
+            let page = crate::api::get_collection(&user, &id).await?;
+            Ok(Self { page })
+        })
+    }
+
+    fn meta(&self) -> Result<Meta> {
+        let CollectionPage {
+            user,
+            collection,
+            pastes,
+        } = &self.page;
+        let title = format!("{} by {user}", collection.title).into();
+
+        let mut summary = Vec::new();
+        if !collection.description.is_empty() {
+            summary.push(collection.description.clone());
+        }
+        summary.extend(
+            pastes
+                .iter()
+                .take(3)
+                .map(|paste| format!("\u{27A4} {}", paste.title)),
+        );
+        if pastes.len() > 3 {
+            summary.push(format!("\u{27A4} .. {} more builds", pastes.len() - 3));
+        }
+        if pastes.is_empty() {
+            summary.push("\u{27A4} there aren't any builds yet".to_owned());
+        }
+
+        Ok(Meta {
+            title,
+            description: summary.join("\n").into(),
+            image: crate::assets::logo().into(),
+            ..Default::default()
+        })
+    }
+
+    fn render<G: Html>(self, cx: Scope) -> View<G> {
+        view! { cx, UserCollectionPageComponent(self) }
+    }
+}
+
+#[component]
+fn UserCollectionPageComponent<G: Html>(
+    cx: Scope,
+    UserCollectionPage { page }: UserCollectionPage,
+) -> View<G> {
+    let data_ssr = serialize_for_attribute::<G>(&page);
+
+    let CollectionPage {
+        user,
+        collection,
+        pastes,
+    } = page;
+
+    let session = use_context::<SessionValue>(cx);
+    let owner = create_ref(cx, user.clone());
+    let is_current_user = create_memo(cx, || {
+        Some(owner.as_str()) == session.get().user().map(|u| u.name.as_str())
+    });
+
+    let props = create_ref(
+        cx,
+        CollectionEditorProps {
+            user: user.clone(),
+            id: collection.id.clone(),
+            title: collection.title.clone(),
+            description: collection.description.clone(),
+            private: collection.private,
+            pastes: collection.pastes.clone(),
+            summaries: pastes.clone(),
+        },
+    );
+    let editor = memo_cond!(
+        cx,
+        is_current_user,
+        view! { cx, CollectionEditor(props.clone()) },
+        view! { cx, }
+    );
+
+    let total = pastes.len();
+    let p = pastes
+        .into_iter()
+        .map(|summary| {
+            let deleted = create_signal(cx, false);
+            let summary = create_ref(cx, summary);
+            let content = memo_cond!(
+                cx,
+                deleted,
+                view! { cx, },
+                summary_to_view(cx, summary, deleted)
+            );
+            view! { cx, (&*content.get()) }
+        })
+        .collect::<Vec<_>>();
+
+    let p = if !p.is_empty() {
+        View::new_fragment(p)
+    } else {
+        view! { cx,
+            span(class="text-center") { "There is nothing here .." }
+        }
+    };
+
+    let user_url = user.to_url().to_string();
+    let private = collection.private;
+
+    view! { cx,
+        h1(class="text-amber-50 text-xl") {
+            span { (collection.title) }
+            span(class="ml-2 text-sm text-slate-400") { "(" (total) ")" }
+            (if private {
+                view! { cx, span(class="ml-2 text-sm text-slate-400") { "private" } }
+            } else {
+                view! { cx, }
+            })
+        }
+        div(class="text-sm text-slate-400 mb-4") {
+            "by "
+            a(href=user_url, class="text-sky-400 hover:underline") { (user) }
+        }
+        p(class="text-slate-200 whitespace-pre-line mb-4") { (collection.description) }
+        (&*editor.get())
+        div(data-ssr=data_ssr, class="flex flex-col gap-2") {
+            (p)
+        }
+    }
+}
+
+#[derive(Prop, Clone)]
+struct CollectionEditorProps {
+    user: User,
+    id: Id,
+    title: String,
+    description: String,
+    private: bool,
+    pastes: Vec<Id>,
+    summaries: Vec<PasteSummary>,
+}
+
+/// Editing of a collection, only rendered for the owner of the collection.
+#[component]
+fn CollectionEditor<G: Html>(cx: Scope, props: CollectionEditorProps) -> View<G> {
+    let CollectionEditorProps {
+        user,
+        id,
+        title,
+        description,
+        private,
+        pastes,
+        summaries,
+    } = props;
+
+    let id = create_ref(cx, id);
+    let title = create_signal(cx, title);
+    let description = create_signal(cx, description);
+    let private = create_signal(cx, private);
+    let pastes = create_signal(cx, pastes);
+    let new_paste = create_signal(cx, String::new());
+    let summaries = create_ref(cx, summaries);
+    let user = create_ref(cx, user);
+
+    let on_add = move |_| {
+        // Accept plain ids as well as links to a build.
+        let value = new_paste.get();
+        let id = value.trim().trim_end_matches('/').rsplit('/').next();
+        if let Some(id) = id.and_then(|id| id.parse::<Id>().ok()) {
+            if !pastes.get().contains(&id) {
+                pastes.modify().push(id);
+            }
+            new_paste.set(String::new());
+        }
+    };
+
+    let on_save = async_callback!(
+        cx,
+        {
+            let (title, description, pastes) = (title.get(), description.get(), pastes.get());
+            let collection = crate::api::SaveCollection {
+                id: Some(id),
+                title: &title,
+                description: &description,
+                private: *private.get(),
+                pastes: &pastes,
+            };
+            match crate::api::save_collection(collection).await {
+                Err(err) => tracing::error!("saving collection failed: {:?}", err),
+                Ok(_) => {
+                    let _ = web_sys::window().unwrap().location().reload();
+                }
+            }
+        },
+        !title.get().trim().is_empty()
+    );
+
+    let on_delete = async_callback!(
+        cx,
+        {
+            match crate::api::delete_collection(id).await {
+                Err(err) => tracing::error!("deleting collection failed: {:?}", err),
+                Ok(()) => sycamore_router::navigate(&user.to_url().into_cow()),
+            }
+        },
+        {
+            let message = "Are you sure you want to delete this collection?".to_owned();
+            web_sys::window()
+                .unwrap()
+                .confirm_with_message(&message)
+                .unwrap_or_default()
+        }
+    );
+
+    view! { cx,
+        details(class="mb-4") {
+            summary(class="cursor-pointer text-sky-400") { "Edit collection" }
+            div(class="flex flex-col gap-3 mt-3") {
+                input(
+                    class="input",
+                    type="text",
+                    maxlength=90,
+                    placeholder="Title",
+                    aria-label="Title",
+                    bind:value=title
+                ) {}
+                textarea(
+                    class="input resize-y",
+                    rows=3,
+                    maxlength=1000,
+                    placeholder="Description",
+                    aria-label="Description",
+                    bind:value=description
+                ) {}
+                label(class="text-sm") {
+                    input(type="checkbox", class="mr-2", bind:checked=private) {}
+                    "Private, only visible to you"
+                }
+                Indexed(
+                    iterable=pastes,
+                    view=move |cx, paste| {
+                        let label = summaries
+                            .iter()
+                            .find(|summary| summary.id.id() == paste.as_str())
+                            .map(|summary| summary.title.clone())
+                            .unwrap_or_else(|| paste.to_string());
+                        let paste = create_ref(cx, paste);
+                        let position = move || pastes.get().iter().position(|p| p == paste);
+                        let on_up = move |_| {
+                            if let Some(i) = position().filter(|&i| i > 0) {
+                                pastes.modify().swap(i, i - 1);
+                            }
+                        };
+                        let on_down = move |_| {
+                            if let Some(i) = position().filter(|&i| i + 1 < pastes.get().len()) {
+                                pastes.modify().swap(i, i + 1);
+                            }
+                        };
+                        let on_remove = move |_| pastes.modify().retain(|p| p != paste);
+                        view! { cx,
+                            div(class="flex gap-2 items-center even:bg-slate-700 p-2") {
+                                span(class="flex-auto text-amber-50") { (label) }
+                                button(on:click=on_up, class="btn btn-secondary", title="Move up") { "\u{2191}" }
+                                button(on:click=on_down, class="btn btn-secondary", title="Move down") { "\u{2193}" }
+                                button(on:click=on_remove, class="btn btn-secondary", title="Remove from collection") { "Remove" }
+                            }
+                        }
+                    }
+                )
+                div(class="flex gap-2 items-center") {
+                    input(
+                        class="input flex-auto",
+                        type="text",
+                        placeholder="Build id or link",
+                        aria-label="Build id or link",
+                        bind:value=new_paste
+                    ) {}
+                    button(on:click=on_add, class="btn btn-secondary") { "Add Build" }
+                }
+                div(class="flex gap-2 justify-between") {
+                    button(on:click=on_delete, class="btn btn-secondary") { "Delete Collection" }
+                    button(on:click=on_save, class="btn btn-primary") { "Save" }
+                }
+            }
+        }
+    }
+}
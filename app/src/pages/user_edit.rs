@@ -17,6 +17,8 @@ pub struct UserEditPastePage {
     content: String,
     rank: Option<NonZeroU8>,
     private: bool,
+    tags: Vec<String>,
+    description: String,
 }
 
 impl RoutedComponent for UserEditPastePage {
@@ -29,6 +31,16 @@ impl RoutedComponent for UserEditPastePage {
             content: paste.content,
             rank: paste.metadata.as_ref().and_then(|m| m.rank),
             private: paste.metadata.as_ref().is_some_and(|m| m.private),
+            tags: paste
+                .metadata
+                .as_ref()
+                .map(|m| m.tags.clone())
+                .unwrap_or_default(),
+            description: paste
+                .metadata
+                .as_ref()
+                .map(|m| m.description.clone())
+                .unwrap_or_default(),
             title: paste.metadata.map(|m| m.title),
         })
     }
@@ -38,6 +50,10 @@ impl RoutedComponent for UserEditPastePage {
         let title = find_text(&element, "[data-marker-title]");
         let rank = find_attribute(&element, "data-rank");
         let private = find_attribute(&element, "data-private").unwrap_or_default();
+        let tags = find_attribute::<String>(&element, "data-tags")
+            .map(|tags| tags.split(',').map(|tag| tag.trim().to_owned()).collect())
+            .unwrap_or_default();
+        let description = find_text(&element, "[data-marker-description]").unwrap_or_default();
 
         Ok(Self {
             id: UserPasteId { user, id },
@@ -45,6 +61,8 @@ impl RoutedComponent for UserEditPastePage {
             title,
             rank,
             private,
+            tags,
+            description,
         })
     }
 
@@ -57,6 +75,16 @@ impl RoutedComponent for UserEditPastePage {
                 content: paste.content,
                 rank: paste.metadata.as_ref().and_then(|m| m.rank),
                 private: paste.metadata.as_ref().is_some_and(|m| m.private),
+                tags: paste
+                    .metadata
+                    .as_ref()
+                    .map(|m| m.tags.clone())
+                    .unwrap_or_default(),
+                description: paste
+                    .metadata
+                    .as_ref()
+                    .map(|m| m.description.clone())
+                    .unwrap_or_default(),
                 title: paste.metadata.map(|x| x.title),
             })
         })
@@ -77,6 +105,8 @@ impl RoutedComponent for UserEditPastePage {
             title,
             rank,
             private,
+            tags,
+            description,
         } = self;
         let props = CreatePasteProps::Update {
             id,
@@ -84,6 +114,8 @@ impl RoutedComponent for UserEditPastePage {
             title,
             rank,
             private,
+            tags,
+            description,
         };
         view! { cx,
             CreatePaste(props)
@@ -1,12 +1,13 @@
 use std::{borrow::Cow, convert::TryInto};
 
 use ::pob::{PathOfBuilding as _, PathOfBuildingExt as _};
-use shared::{GameVersion, Id, User, UserPasteId};
+use shared::{GameVersion, Id, PasteId, User, UserPasteId};
 use sycamore::prelude::*;
 
 use crate::{
     build::Build,
     components::{PasteToolbox, ViewPaste, ViewPasteProps},
+    consts::SELF_URL,
     future::LocalBoxFuture,
     meta, pob,
     router::RoutedComponent,
@@ -18,6 +19,7 @@ use crate::{
 pub struct UserPastePage {
     id: UserPasteId,
     title: Option<String>,
+    forked_from: Option<PasteId>,
     last_modified: u64,
     build: Build,
 }
@@ -27,11 +29,16 @@ impl RoutedComponent for UserPastePage {
 
     fn from_context((user, id): Self::RouteArg, ctx: crate::Context) -> Result<Self> {
         let mut paste = ctx.into_paste().unwrap();
-        let title = paste.metadata.take().map(|m| m.title);
+        let (title, forked_from) = paste
+            .metadata
+            .take()
+            .map(|m| (Some(m.title), m.forked_from))
+            .unwrap_or_default();
 
         Ok(Self {
             id: UserPasteId { user, id },
             title,
+            forked_from,
             last_modified: paste.last_modified,
             build: paste.try_into()?,
         })
@@ -40,6 +47,7 @@ impl RoutedComponent for UserPastePage {
     fn from_hydration((user, id): Self::RouteArg, element: web_sys::Element) -> Result<Self> {
         let content = find_text(&element, "[data-marker-content]").unwrap_or_default();
         let title = find_text(&element, "[data-marker-title]");
+        let forked_from = find_attribute(&element, "data-forked-from");
         let last_modified = find_attribute(&element, "data-last-modified").unwrap_or_default();
         let data = deserialize_attribute(&element, "data-data").unwrap_or_default();
 
@@ -47,6 +55,7 @@ impl RoutedComponent for UserPastePage {
         Ok(Self {
             id: UserPasteId { user, id },
             title,
+            forked_from,
             last_modified,
             build,
         })
@@ -56,11 +65,16 @@ impl RoutedComponent for UserPastePage {
         let id = UserPasteId { user, id }.into();
         Box::pin(async move {
             let mut paste = crate::api::get_paste(&id).await?;
-            let title = paste.metadata.take().map(|x| x.title);
+            let (title, forked_from) = paste
+                .metadata
+                .take()
+                .map(|x| (Some(x.title), x.forked_from))
+                .unwrap_or_default();
 
             Ok(Self {
                 id: id.unwrap_user(),
                 title,
+                forked_from,
                 last_modified: paste.last_modified,
                 build: paste.try_into()?,
             })
@@ -85,10 +99,14 @@ impl RoutedComponent for UserPastePage {
 
         let description = meta::get_paste_summary(pob).join("\n").into();
 
-        let image = crate::assets::ascendancy_image(pob.ascendancy_or_class()).into();
+        let image = format!(
+            "{SELF_URL}{}",
+            self.id.to_paste_url().join(pob::preview::PATH)
+        )
+        .into();
         let color = meta::get_color(pob.ascendancy_or_class());
 
-        let oembed = format!("/oembed.json?user={}", self.id.user).into();
+        let oembed = format!("/oembed.json?url={SELF_URL}{}", self.id.to_paste_url()).into();
 
         Ok(Meta {
             title,
@@ -110,6 +128,7 @@ fn UserPastePageComponent<G: Html>(
     UserPastePage {
         id,
         title,
+        forked_from,
         last_modified,
         build,
     }: UserPastePage,
@@ -120,6 +139,24 @@ fn UserPastePageComponent<G: Html>(
 
     let data = serialize_for_attribute::<G>(build.data());
 
+    let forked_from = match forked_from {
+        Some(source) => {
+            let href = source.to_url().into_cow();
+            let data = source.to_string();
+            let name = match source {
+                PasteId::Paste(id) => id.to_string(),
+                PasteId::UserPaste(up) => format!("{}/{}", up.user, up.id),
+            };
+            view! { cx,
+                div(class="text-sm text-slate-400 mb-2", data-forked-from=data) {
+                    "forked from "
+                    a(href=href, class="text-sky-400 hover:underline") { (name) }
+                }
+            }
+        }
+        None => view! { cx, },
+    };
+
     let name = id.user.clone();
     let props = ViewPasteProps {
         id: id.clone().into(),
@@ -143,6 +180,7 @@ fn UserPastePageComponent<G: Html>(
             }
             PasteToolbox(id=id, on_delete=deleted)
         }
+        (forked_from)
         ViewPaste(props)
     }
 }
@@ -0,0 +1,184 @@
+use shared::{model::PasteRevision, Id, User, UserPasteId};
+use sycamore::{prelude::*, reactive::use_context};
+
+use crate::{
+    consts::IMG_ONERROR_INVISIBLE,
+    future::LocalBoxFuture,
+    router::RoutedComponent,
+    session::SessionValue,
+    svg,
+    utils::{async_callback, deserialize_attribute, pretty_date_ts, serialize_for_attribute},
+    Meta, Result,
+};
+
+pub struct UserPasteHistoryPage {
+    id: UserPasteId,
+    revisions: Vec<PasteRevision>,
+}
+
+impl RoutedComponent for UserPasteHistoryPage {
+    type RouteArg = (User, Id);
+
+    fn from_context((user, id): Self::RouteArg, ctx: crate::Context) -> Result<Self> {
+        Ok(Self {
+            id: UserPasteId { user, id },
+            revisions: ctx.into_revisions().unwrap(),
+        })
+    }
+
+    fn from_hydration((user, id): Self::RouteArg, element: web_sys::Element) -> Result<Self> {
+        let revisions = deserialize_attribute(&element, "data-ssr").unwrap_or_default();
+
+        Ok(Self {
+            id: UserPasteId { user, id },
+            revisions,
+        })
+    }
+
+    fn from_dynamic<'a>((user, id): Self::RouteArg) -> LocalBoxFuture<'a, Result<Self>> {
+        let id = UserPasteId { user, id };
+        Box::pin(async move {
+            let revisions = crate::api::get_paste_revisions(&id).await?;
+            Ok(Self { id, revisions })
+        })
+    }
+
+    fn meta(&self) -> Result<Meta> {
+        let title = format!("History of {}", self.id).into();
+
+        let description = match self.revisions.len() {
+            0 => "\u{27A4} there are no previous versions".to_owned(),
+            1 => "\u{27A4} 1 previous version".to_owned(),
+            n => format!("\u{27A4} {n} previous versions"),
+        }
+        .into();
+
+        Ok(Meta {
+            title,
+            description,
+            image: crate::assets::logo().into(),
+            ..Default::default()
+        })
+    }
+
+    fn render<G: Html>(self, cx: Scope) -> View<G> {
+        view! { cx, UserPasteHistoryPageComponent(self) }
+    }
+}
+
+#[component]
+fn UserPasteHistoryPageComponent<G: Html>(
+    cx: Scope,
+    UserPasteHistoryPage { id, revisions }: UserPasteHistoryPage,
+) -> View<G> {
+    let data_ssr = serialize_for_attribute::<G>(&revisions);
+    let id = create_ref(cx, id);
+    let back_to_paste = create_ref(cx, id.to_paste_url().into_cow());
+
+    let p = revisions
+        .into_iter()
+        .map(|revision| revision_to_view(cx, id, revision))
+        .collect::<Vec<_>>();
+
+    let p = if !p.is_empty() {
+        View::new_fragment(p)
+    } else {
+        view! { cx,
+            span(class="text-center") { "There are no previous versions of this build .." }
+        }
+    };
+
+    view! { cx,
+        a(href=back_to_paste, class="flex items-center mb-4 text-sky-400") {
+            span(dangerously_set_inner_html=svg::BACK, class="h-[16px] mr-2")
+            span() { "Back to the build" }
+        }
+        h1(class="text-amber-50 text-xl mb-4") {
+            span { "History of " }
+            span { (id.id.to_string()) }
+        }
+        div(data-ssr=data_ssr, class="flex flex-col gap-2") {
+            (p)
+        }
+    }
+}
+
+fn revision_to_view<'a, G: Html>(
+    cx: Scope<'a>,
+    id: &'a UserPasteId,
+    revision: PasteRevision,
+) -> View<G> {
+    let session = use_context::<SessionValue>(cx);
+    let is_current_user = create_memo(cx, || {
+        Some(id.user.as_str()) == session.get().user().map(|u| u.name.as_str())
+    });
+
+    let metadata = revision.metadata;
+    let revision = revision.revision;
+
+    let image = crate::assets::ascendancy_image(metadata.ascendancy_or_class);
+    let color = crate::meta::get_color(metadata.ascendancy_or_class);
+    let raw_url = id.to_revision_raw_url(revision).into_cow();
+    let json_url = id.to_revision_json_url(revision).into_cow();
+    let version = metadata.version.unwrap_or_default();
+    let main_skill_name = metadata.main_skill_name.unwrap_or_default();
+
+    let on_restore = async_callback!(
+        cx,
+        {
+            match crate::api::restore_paste_revision(id, revision).await {
+                Err(err) => tracing::error!("restoring revision failed: {:?}", err),
+                Ok(id) => sycamore_router::navigate(&id.to_url().into_cow()),
+            }
+        },
+        {
+            let message = "Are you sure you want to restore this version?".to_owned();
+            web_sys::window()
+                .unwrap()
+                .confirm_with_message(&message)
+                .unwrap_or_default()
+        }
+    );
+
+    let restore = create_memo(cx, move || {
+        if *is_current_user.get() {
+            view! { cx,
+                button(on:click=on_restore, class="btn btn-primary", title="Restore this version") {
+                    "Restore"
+                }
+            }
+        } else {
+            view! { cx, }
+        }
+    });
+
+    view! { cx,
+        div(class="p-3 md:p-0 md:pr-3 even:bg-slate-700 border-solid border-l-4 border-[color:var(--col)]",
+            style=format!("--col: {color}")
+        ) {
+            div(class="flex flex-wrap gap-4 items-center") {
+                img(src=image,
+                    class="asc-image rounded-full md:rounded-l-none md:h-[70px] md:w-[90px]",
+                    alt=format!("{} Thumbnail", metadata.ascendancy_or_class.as_str()),
+                    onerror=IMG_ONERROR_INVISIBLE) {}
+                div(class="flex-auto basis-52 text-slate-200 flex flex-col gap-2") {
+                    div(class="text-amber-50") {
+                        span { (metadata.title) }
+                        sup(class="ml-1") { (version) }
+                    }
+                    div { (main_skill_name) }
+                }
+                div(class="flex-1 sm:flex-initial flex flex-col items-end gap-2 whitespace-nowrap") {
+                    div(class="flex gap-3 items-center text-sm") {
+                        a(href=raw_url, rel="external", class="hover:underline") { "Raw" }
+                        a(href=json_url, rel="external", class="hover:underline") { "JSON" }
+                        (&*restore.get())
+                    }
+                    div(class="text-right text-sm text-slate-400") {
+                        (pretty_date_ts(revision))
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Machine readable summary of a build, see [`shared::model::summary`].
+use ::pob::{Item, PathOfBuilding, PathOfBuildingExt, Stat};
+use shared::model::{data, summary::*};
+
+use crate::pob;
+
+/// Summarizes a build.
+///
+/// `nodes` are the allocated keystones and masteries of the active tree,
+/// see [`shared::model::data::Nodes`].
+pub fn summarize(
+    title: &str,
+    pob: &impl PathOfBuilding,
+    nodes: Option<&data::Nodes>,
+) -> BuildSummary {
+    let keystones = nodes
+        .map(|nodes| nodes.keystones.iter().map(|k| k.name.clone()).collect())
+        .unwrap_or_default();
+    let masteries = nodes
+        .map(|nodes| nodes.masteries.iter().map(to_mastery).collect())
+        .unwrap_or_default();
+
+    BuildSummary {
+        version: VERSION,
+        title: title.to_owned(),
+        game_version: pob.game_version(),
+        class: pob.class(),
+        ascendancy: pob.ascendancy(),
+        level: pob.level(),
+        tree_version: pob.max_tree_version(),
+        main_skill: main_skill(pob),
+        stats: stats(pob),
+        keystones,
+        masteries,
+        gear: gear(pob),
+    }
+}
+
+fn main_skill(pob: &impl PathOfBuilding) -> Option<MainSkill> {
+    let skill_sets = pob.skill_sets();
+    let skill_set = skill_sets
+        .iter()
+        .find(|ss| ss.is_selected)
+        .or_else(|| skill_sets.first())?;
+    let skill = skill_set.skills.iter().find(|skill| skill.is_selected)?;
+
+    let (supports, gems) = skill
+        .gems
+        .iter()
+        .filter(|gem| gem.is_enabled)
+        .partition::<Vec<_>, _>(|gem| gem.is_support);
+    let to_gems = |gems: Vec<&::pob::Gem>| {
+        gems.into_iter()
+            .map(|gem| Gem {
+                name: gem.name.to_owned(),
+                level: gem.level,
+                quality: gem.quality,
+            })
+            .collect()
+    };
+
+    Some(MainSkill {
+        name: pob.main_skill_name().map(str::to_owned),
+        gems: to_gems(gems),
+        supports: to_gems(supports),
+    })
+}
+
+fn stats(pob: &impl PathOfBuilding) -> Stats {
+    let stat = |stat| pob.stat_parse::<f32>(stat).filter(|v| v.is_finite());
+
+    Stats {
+        dps: pob::dps(pob),
+        life: stat(Stat::Life),
+        energy_shield: stat(Stat::EnergyShield),
+        mana: stat(Stat::Mana),
+        ward: stat(Stat::Ward),
+        ehp: stat(Stat::TotalEhp),
+        resistances: Resistances {
+            fire: stat(Stat::FireResistance),
+            cold: stat(Stat::ColdResistance),
+            lightning: stat(Stat::LightningResistance),
+            chaos: stat(Stat::ChaosResistance),
+        },
+        max_hits: MaxHits {
+            physical: stat(Stat::MaxHitPhysical),
+            fire: stat(Stat::MaxHitFire),
+            cold: stat(Stat::MaxHitCold),
+            lightning: stat(Stat::MaxHitLightning),
+            chaos: stat(Stat::MaxHitChaos),
+        },
+    }
+}
+
+fn to_mastery(node: &data::Node) -> Mastery {
+    Mastery {
+        name: node.name.clone(),
+        effects: node.stats.iter().map(|stat| stat.text.clone()).collect(),
+    }
+}
+
+fn gear(pob: &impl PathOfBuilding) -> Vec<GearItem> {
+    let item_sets = pob.item_sets();
+    let Some(item_set) = item_sets
+        .iter()
+        .find(|set| set.is_selected)
+        .or_else(|| item_sets.first())
+    else {
+        return Vec::new();
+    };
+
+    pob::markup::slots(&item_set.gear)
+        .into_iter()
+        .filter_map(|(slot, item)| {
+            let item = Item::parse(item?).ok()?;
+            Some(GearItem {
+                slot: slot.to_owned(),
+                name: item.name.map(str::to_owned),
+                base: item.base.to_owned(),
+                rarity: to_rarity(item.rarity),
+            })
+        })
+        .collect()
+}
+
+fn to_rarity(rarity: ::pob::Rarity) -> Rarity {
+    match rarity {
+        ::pob::Rarity::Normal => Rarity::Normal,
+        ::pob::Rarity::Magic => Rarity::Magic,
+        ::pob::Rarity::Rare => Rarity::Rare,
+        ::pob::Rarity::Unique => Rarity::Unique,
+        ::pob::Rarity::Relic => Rarity::Relic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mastery_effects() {
+        let node = data::Node {
+            name: "Life Mastery".to_owned(),
+            stats: vec![data::NodeStat {
+                id: 1,
+                text: "+50 to maximum Life".to_owned(),
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            to_mastery(&node),
+            Mastery {
+                name: "Life Mastery".to_owned(),
+                effects: vec!["+50 to maximum Life".to_owned()],
+            }
+        );
+    }
+}
@@ -3,6 +3,7 @@ use std::{borrow::Cow, marker::PhantomData};
 use sycamore::prelude::*;
 use thousands::Separable;
 
+use super::markup::Markup;
 use crate::components::{PobColoredText, StaticPopup};
 
 pub struct Element<'a> {
@@ -113,6 +114,10 @@ impl<'a> Element<'a> {
         self.render_priv(StringRenderer::new())
     }
 
+    pub fn render_to_markup(self, markup: Markup) -> Option<String> {
+        self.render_priv(MarkupRenderer::new(markup))
+    }
+
     pub fn render_to_view<G: Html>(self, cx: Scope) -> Option<View<G>> {
         self.render_priv(ViewRenderer::new(cx))
     }
@@ -316,6 +321,60 @@ impl Renderer for StringRenderer {
     }
 }
 
+/// Renders elements as Markdown or BBCode, stats are highlighted in bold.
+struct MarkupRenderer {
+    markup: Markup,
+    bold: bool,
+    views: Vec<String>,
+}
+
+impl MarkupRenderer {
+    fn new(markup: Markup) -> Self {
+        Self {
+            markup,
+            bold: false,
+            views: Vec::new(),
+        }
+    }
+}
+
+impl Renderer for MarkupRenderer {
+    type Output = String;
+
+    fn push<T>(&mut self, fragment: T)
+    where
+        T: Into<Fragment>,
+    {
+        let fragment = fragment.into();
+        let value = self.markup.escape(&fragment.value);
+        let s = match fragment.typ {
+            FragmentType::Text => value.into_owned(),
+            FragmentType::Super => format!(" ({value})"),
+        };
+        self.views.push(s);
+    }
+
+    fn push_sub(&mut self, element: Self) {
+        self.views.push(element.finish());
+    }
+
+    fn sub(&mut self, _: Formatting) -> Self {
+        Self {
+            markup: self.markup,
+            bold: true,
+            views: Vec::new(),
+        }
+    }
+
+    fn finish(self) -> Self::Output {
+        let s = self.views.join("");
+        match self.bold {
+            true => self.markup.bold(&s),
+            false => s,
+        }
+    }
+}
+
 struct ViewRenderer<'a, G: GenericNode> {
     cx: Scope<'a>,
     formatting: Formatting,
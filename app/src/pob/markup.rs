@@ -0,0 +1,270 @@
+//! Text summaries of a build for forums and social platforms.
+//!
+//! Renders the same information as the paste page, core stats, defences, offence,
+//! the main skill with its links, the equipped gear and allocated keystones.
+use std::borrow::Cow;
+
+use ::pob::{Gear, Item, PathOfBuilding, PathOfBuildingExt};
+use shared::model::data;
+
+use crate::pob;
+
+/// Markup language of a summary.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Markup {
+    /// Markdown as supported by Reddit and Discord.
+    Markdown,
+    /// BBCode as supported by the official forum.
+    BbCode,
+}
+
+impl Markup {
+    /// File extension and path suffix of the markup.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::BbCode => "bbcode",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::BbCode => "text/plain; charset=utf-8",
+        }
+    }
+
+    pub(crate) fn escape<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        let special: &[char] = match self {
+            Self::Markdown => &['\\', '*', '_', '`', '[', ']', '<', '>', '#', '|', '~', '^'],
+            Self::BbCode => &['[', ']'],
+        };
+
+        if !s.contains(special) {
+            return Cow::Borrowed(s);
+        }
+
+        let mut result = String::with_capacity(s.len() + 8);
+        for c in s.chars() {
+            match (self, c) {
+                // BBCode has no escape sequences, brackets are replaced instead.
+                (Self::BbCode, '[') => result.push('('),
+                (Self::BbCode, ']') => result.push(')'),
+                (Self::Markdown, c) if special.contains(&c) => {
+                    result.push('\\');
+                    result.push(c);
+                }
+                (_, c) => result.push(c),
+            }
+        }
+        Cow::Owned(result)
+    }
+
+    pub(crate) fn bold(&self, s: &str) -> String {
+        match self {
+            Self::Markdown => format!("**{s}**"),
+            Self::BbCode => format!("[b]{s}[/b]"),
+        }
+    }
+
+    fn link(&self, text: &str, url: &str) -> String {
+        match self {
+            Self::Markdown => format!("[{text}]({url})"),
+            Self::BbCode => format!("[url={url}]{text}[/url]"),
+        }
+    }
+
+    fn list(&self, items: &[String]) -> String {
+        match self {
+            Self::Markdown => items
+                .iter()
+                .map(|item| format!("* {item}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Self::BbCode => {
+                let items = items
+                    .iter()
+                    .map(|item| format!("[*]{item}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("[list]\n{items}\n[/list]")
+            }
+        }
+    }
+
+    /// Separates paragraphs, Markdown requires an empty line.
+    fn paragraphs(&self, paragraphs: Vec<String>) -> String {
+        let mut result = paragraphs.join("\n\n");
+        result.push('\n');
+        result
+    }
+}
+
+/// Renders the summary of a build.
+///
+/// `keystones` are the allocated keystones of the active tree,
+/// see [`shared::model::data::Nodes`].
+pub fn render(
+    markup: Markup,
+    title: &str,
+    url: &str,
+    pob: &impl PathOfBuilding,
+    keystones: &[data::Node],
+) -> String {
+    let mut paragraphs = Vec::new();
+
+    let heading = markup.bold(&markup.link(&markup.escape(title), url));
+    let subtitle = format!(
+        "Level {} {}",
+        pob.level(),
+        pob.ascendancy_or_class().as_str()
+    );
+    paragraphs.push(format!("{heading}\n{subtitle}"));
+
+    let stats = [
+        pob::summary::core_stats(pob),
+        pob::summary::defense(pob),
+        pob::summary::offense(pob),
+        pob::summary::config(pob),
+    ]
+    .into_iter()
+    .map(|line| {
+        line.into_iter()
+            .filter_map(|stat| stat.render_to_markup(markup))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    })
+    .filter(|line| !line.is_empty())
+    .collect::<Vec<_>>();
+    paragraphs.push(markup.list(&stats));
+
+    if let Some(skill) = main_skill(markup, pob) {
+        paragraphs.push(format!("{} {skill}", markup.bold("Main Skill:")));
+    }
+
+    let keystones = keystones
+        .iter()
+        .map(|keystone| markup.escape(&keystone.name).into_owned())
+        .collect::<Vec<_>>();
+    if !keystones.is_empty() {
+        paragraphs.push(format!(
+            "{} {}",
+            markup.bold("Keystones:"),
+            keystones.join(", ")
+        ));
+    }
+
+    let gear = gear(markup, pob);
+    if !gear.is_empty() {
+        paragraphs.push(format!("{}\n{}", markup.bold("Gear:"), markup.list(&gear)));
+    }
+
+    markup.paragraphs(paragraphs)
+}
+
+/// Gems linked in the main skill, the active gems first.
+fn main_skill(markup: Markup, pob: &impl PathOfBuilding) -> Option<String> {
+    let skill_sets = pob.skill_sets();
+    let skill_set = skill_sets
+        .iter()
+        .find(|ss| ss.is_selected)
+        .or_else(|| skill_sets.first())?;
+    let skill = skill_set.skills.iter().find(|skill| skill.is_selected)?;
+
+    let mut gems = skill
+        .gems
+        .iter()
+        .filter(|gem| gem.is_enabled)
+        .collect::<Vec<_>>();
+    gems.sort_by_key(|gem| gem.is_support);
+
+    let gems = gems
+        .into_iter()
+        .map(|gem| {
+            format!(
+                "{} ({}/{})",
+                markup.escape(gem.name),
+                gem.level,
+                gem.quality
+            )
+        })
+        .collect::<Vec<_>>();
+
+    Some(gems.join(" - ")).filter(|gems| !gems.is_empty())
+}
+
+/// Equipped gear of the selected item set, one entry per slot.
+fn gear(markup: Markup, pob: &impl PathOfBuilding) -> Vec<String> {
+    let item_sets = pob.item_sets();
+    let Some(item_set) = item_sets
+        .iter()
+        .find(|set| set.is_selected)
+        .or_else(|| item_sets.first())
+    else {
+        return Vec::new();
+    };
+
+    slots(&item_set.gear)
+        .into_iter()
+        .filter_map(|(slot, item)| {
+            let item = Item::parse(item?).ok()?;
+            let name = match item.name {
+                Some(name) if item.rarity.is_unique() => {
+                    format!("{} {}", markup.bold(&markup.escape(name)), item.base)
+                }
+                Some(name) => format!("{}, {}", markup.escape(name), item.base),
+                None => item.base.to_owned(),
+            };
+            Some(format!("{slot}: {name}"))
+        })
+        .collect()
+}
+
+pub(crate) fn slots<'a>(gear: &Gear<'a>) -> [(&'static str, Option<&'a str>); 18] {
+    [
+        ("Weapon 1", gear.weapon1),
+        ("Weapon 2", gear.weapon2),
+        ("Helmet", gear.helmet),
+        ("Body Armour", gear.body_armour),
+        ("Gloves", gear.gloves),
+        ("Boots", gear.boots),
+        ("Amulet", gear.amulet),
+        ("Ring 1", gear.ring1),
+        ("Ring 2", gear.ring2),
+        ("Belt", gear.belt),
+        ("Flask 1", gear.flask1),
+        ("Flask 2", gear.flask2),
+        ("Flask 3", gear.flask3),
+        ("Flask 4", gear.flask4),
+        ("Flask 5", gear.flask5),
+        ("Charm 1", gear.charm1),
+        ("Charm 2", gear.charm2),
+        ("Charm 3", gear.charm3),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_markdown() {
+        let markup = Markup::Markdown;
+        assert_eq!(markup.escape("Life"), "Life");
+        assert_eq!(markup.escape("*bold* [x]"), "\\*bold\\* \\[x\\]");
+    }
+
+    #[test]
+    fn escape_bbcode() {
+        let markup = Markup::BbCode;
+        assert_eq!(markup.escape("Death's Oath"), "Death's Oath");
+        assert_eq!(markup.escape("[b]x[/b]"), "(b)x(/b)");
+    }
+
+    #[test]
+    fn list() {
+        let items = ["a".to_owned(), "b".to_owned()];
+        assert_eq!(Markup::Markdown.list(&items), "* a\n* b");
+        assert_eq!(Markup::BbCode.list(&items), "[list]\n[*]a\n[*]b\n[/list]");
+    }
+}
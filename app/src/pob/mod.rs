@@ -1,8 +1,11 @@
 use pob::{Keystone, PathOfBuilding, PathOfBuildingExt, Stat};
 use thousands::Separable;
 
+pub mod build_summary;
 mod element;
 pub mod formatting;
+pub mod markup;
+pub mod preview;
 pub mod summary;
 
 pub use self::element::Element;
@@ -18,6 +21,19 @@ macro_rules! push_if {
     }};
 }
 
+/// Full DPS of the build, falls back to the combined DPS of the main skill or its minions.
+pub fn dps<T: PathOfBuilding>(pob: &T) -> Option<f32> {
+    // TODO: real minion support
+    let is_minion = pob.minion_stat(Stat::CombinedDps).is_some();
+
+    pob.stat_parse(Stat::FullDps)
+        .filter(|&dps: &f32| dps.is_finite() && dps > 0.0)
+        .or_else(|| match is_minion {
+            true => pob.minion_stat_parse(Stat::CombinedDps),
+            false => pob.stat_parse(Stat::CombinedDps),
+        })
+}
+
 pub fn is_crit<T: PathOfBuilding>(pob: &T) -> bool {
     !pob.has_keystone(Keystone::ElementalOverload) && pob.stat_at_least(Stat::CritChance, 20.0)
 }
@@ -95,6 +111,35 @@ pub fn formatted_max_hit<T: PathOfBuilding>(pob: &T) -> Option<String> {
     Some(result).filter(|r| !r.is_empty())
 }
 
+/// Short tags describing the general archetype of a build, e.g. `CI` or `Totem`.
+pub fn archetypes<T: PathOfBuilding>(pob: &T) -> Vec<&'static str> {
+    let mut tags = Vec::new();
+
+    push_if!(tags, is_low_life(pob), "LL");
+    push_if!(tags, is_hybrid(pob), "Hybrid");
+    push_if!(tags, pob.has_keystone(Keystone::ChaosInoculation), "CI");
+    push_if!(tags, pob.has_keystone(Keystone::MindOverMatter), "MoM");
+    push_if!(tags, is_crit(pob), "Crit");
+    push_if!(
+        tags,
+        pob.main_skill_supported_by_any(&[
+            "Cast On Critical Strike",
+            "Awakened Cast On Critical Strike"
+        ]),
+        "CoC"
+    );
+
+    if pob.main_skill_supported_by_any(&["Spell Totem", "Ballista Totem"]) {
+        tags.push("Totem");
+    } else if pob.main_skill_supported_by_any(&["Blastchain Main", "High-Impact Mine"]) {
+        tags.push("Mine");
+    } else if pob.main_skill_supported_by("Trap") {
+        tags.push("Trap");
+    }
+
+    tags
+}
+
 #[derive(Default)]
 pub struct TitleConfig {
     pub no_level: bool,
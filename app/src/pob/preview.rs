@@ -0,0 +1,332 @@
+//! Open Graph preview card of a build.
+//!
+//! The card is rendered as a self contained SVG document, which is rasterized
+//! to PNG by the worker, most sites do not display SVG images.
+//! It shows the ascendancy portrait, title, main skill, key stats and a thumbnail
+//! of the allocated passive tree.
+use std::borrow::Cow;
+
+use ::pob::{PathOfBuilding, PathOfBuildingExt, TreeSpec};
+
+use crate::pob;
+
+/// Width of the card, the size recommended for Open Graph images.
+pub const WIDTH: u32 = 1200;
+/// Height of the card.
+pub const HEIGHT: u32 = 630;
+
+/// Path of the card relative to the paste.
+pub const PATH: &str = "preview.png";
+
+pub const CONTENT_TYPE: &str = "image/png";
+
+/// Maximum amount of characters in a line of text.
+const TEXT_WIDTH: usize = 52;
+const TREE_BOX: (f32, f32, f32, f32) = (810.0, 220.0, 342.0, 362.0);
+
+/// Information shown on a card.
+pub struct Card<'a, T> {
+    pub title: &'a str,
+    pub author: Option<&'a str>,
+    pub pob: &'a T,
+    /// Ascendancy portrait, usually a data URL.
+    ///
+    /// External images are not loaded when the card is displayed as an image.
+    pub portrait: Option<&'a str>,
+    pub tree: Option<Tree>,
+}
+
+/// Positions of passive nodes for the tree thumbnail.
+#[derive(Debug, Default, PartialEq)]
+pub struct Tree {
+    view_box: (f32, f32, f32, f32),
+    nodes: Vec<(f32, f32)>,
+    allocated: Vec<(f32, f32)>,
+}
+
+impl Tree {
+    /// Extracts node positions from a tree SVG asset, see [`tree_asset`].
+    ///
+    /// Ascendancy nodes and masteries are skipped.
+    pub fn from_svg(svg: &str, allocated: &[u32]) -> Option<Self> {
+        let root = svg.split_once('>')?.0;
+        let view_box = attribute(root, "viewBox")?
+            .split_whitespace()
+            .map(|v| v.parse().ok())
+            .collect::<Option<Vec<f32>>>()?;
+        let [x, y, w, h] = view_box[..] else {
+            return None;
+        };
+
+        let mut tree = Self {
+            view_box: (x, y, w, h),
+            ..Default::default()
+        };
+
+        for circle in svg.split("<circle ").skip(1) {
+            let Some((tag, _)) = circle.split_once('>') else {
+                continue;
+            };
+            if attribute(tag, "class").is_some_and(|c| c != "keystone") {
+                continue;
+            }
+
+            let position = |name| attribute(tag, name).and_then(|v| v.parse::<f32>().ok());
+            let (Some(cx), Some(cy)) = (position("cx"), position("cy")) else {
+                continue;
+            };
+            let id = attribute(tag, "id")
+                .and_then(|id| id.strip_prefix('n'))
+                .and_then(|id| id.parse::<u32>().ok());
+
+            match id {
+                Some(id) if allocated.contains(&id) => tree.allocated.push((cx, cy)),
+                _ => tree.nodes.push((cx, cy)),
+            }
+        }
+
+        Some(tree)
+    }
+}
+
+/// Path of the tree SVG asset of a tree spec.
+pub fn tree_asset(pob: &impl PathOfBuilding, spec: &TreeSpec) -> &'static str {
+    crate::tree::SvgTree::url(pob.game_version(), spec)
+}
+
+/// URL of the ascendancy portrait of a build.
+pub fn portrait_url(pob: &impl PathOfBuilding) -> &'static str {
+    crate::assets::ascendancy_image(pob.ascendancy_or_class())
+}
+
+/// Renders the card as SVG document.
+pub fn render(card: &Card<'_, impl PathOfBuilding>) -> String {
+    let pob = card.pob;
+    let color = crate::meta::get_color(pob.ascendancy_or_class());
+
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif">"##
+    );
+    svg.push_str(&format!(
+        r##"<rect width="{WIDTH}" height="{HEIGHT}" fill="#0f172a"/><rect width="{WIDTH}" height="10" fill="{color}"/>"##
+    ));
+
+    if let Some(portrait) = card.portrait {
+        svg.push_str(&format!(
+            r##"<image href="{}" x="48" y="48" width="140" height="140"/>"##,
+            escape(portrait)
+        ));
+    }
+
+    svg.push_str(&text(
+        220.0,
+        100.0,
+        44,
+        "#f1f5f9",
+        &truncate(card.title, 42),
+    ));
+    let subtitle = format!(
+        "Level {} {}",
+        pob.level(),
+        pob.ascendancy_or_class().as_str()
+    );
+    svg.push_str(&text(220.0, 145.0, 28, color, &subtitle));
+    if let Some(author) = card.author {
+        svg.push_str(&text(220.0, 185.0, 24, "#94a3b8", &format!("by {author}")));
+    }
+
+    let mut y = 260.0;
+    if let Some(skill) = main_skill(pob) {
+        svg.push_str(&text(48.0, y, 30, "#fbbf24", &truncate(&skill, TEXT_WIDTH)));
+        y += 56.0;
+    }
+
+    let lines = [
+        pob::summary::core_stats(pob),
+        pob::summary::defense(pob),
+        pob::summary::offense(pob),
+    ]
+    .into_iter()
+    .flat_map(|line| {
+        let stats = line
+            .into_iter()
+            .filter_map(|stat| stat.render_to_string())
+            .collect::<Vec<_>>();
+        wrap(&stats, TEXT_WIDTH)
+    });
+    for line in lines.take(6) {
+        svg.push_str(&text(48.0, y, 26, "#e2e8f0", &line));
+        y += 44.0;
+    }
+
+    if let Some(tree) = &card.tree {
+        svg.push_str(&thumbnail(tree));
+    }
+
+    svg.push_str(&format!(
+        r##"<text x="{}" y="{}" font-size="24" fill="#64748b" text-anchor="end">pobb.in</text>"##,
+        WIDTH - 48,
+        HEIGHT - 16,
+    ));
+    svg.push_str("</svg>");
+
+    svg
+}
+
+/// Names of the active gems of the main skill.
+fn main_skill(pob: &impl PathOfBuilding) -> Option<String> {
+    let skill_sets = pob.skill_sets();
+    let skill_set = skill_sets
+        .iter()
+        .find(|ss| ss.is_selected)
+        .or_else(|| skill_sets.first())?;
+    let skill = skill_set.skills.iter().find(|skill| skill.is_selected)?;
+
+    let gems = skill
+        .gems
+        .iter()
+        .filter(|gem| gem.is_enabled && !gem.is_support)
+        .map(|gem| gem.name)
+        .collect::<Vec<_>>();
+
+    Some(gems.join(" / ")).filter(|gems| !gems.is_empty())
+}
+
+fn thumbnail(tree: &Tree) -> String {
+    let (bx, by, bw, bh) = TREE_BOX;
+    let (vx, vy, vw, vh) = tree.view_box;
+    let scale = (bw / vw).min(bh / vh);
+    // Center the tree in the box.
+    let ox = bx + (bw - vw * scale) / 2.0;
+    let oy = by + (bh - vh * scale) / 2.0;
+
+    let circles = |nodes: &[(f32, f32)], r: f32| {
+        nodes
+            .iter()
+            .map(|(x, y)| {
+                let x = ox + (x - vx) * scale;
+                let y = oy + (y - vy) * scale;
+                format!(r#"<circle cx="{x:.1}" cy="{y:.1}" r="{r}"/>"#)
+            })
+            .collect::<String>()
+    };
+
+    format!(
+        r##"<rect x="{}" y="{}" width="{}" height="{}" rx="16" fill="#1e293b"/><g fill="#475569">{}</g><g fill="#38bdf8">{}</g>"##,
+        bx - 12.0,
+        by - 12.0,
+        bw + 24.0,
+        bh + 24.0,
+        circles(&tree.nodes, 1.2),
+        circles(&tree.allocated, 2.6),
+    )
+}
+
+fn text(x: f32, y: f32, size: u32, color: &str, content: &str) -> String {
+    format!(
+        r#"<text x="{x}" y="{y}" font-size="{size}" fill="{color}">{}</text>"#,
+        escape(content)
+    )
+}
+
+/// Greedily joins stats into lines of at most `width` characters.
+fn wrap(stats: &[String], width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for stat in stats {
+        if !line.is_empty() && line.chars().count() + stat.chars().count() + 3 > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push_str(" · ");
+        }
+        line.push_str(stat);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn truncate(s: &str, width: usize) -> Cow<'_, str> {
+    match s.char_indices().nth(width) {
+        Some((index, _)) => Cow::Owned(format!("{}…", s[..index].trim_end())),
+        None => Cow::Borrowed(s),
+    }
+}
+
+fn escape(s: &str) -> Cow<'_, str> {
+    if !s.contains(['&', '<', '>', '"']) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut result = String::with_capacity(s.len() + 8);
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            c => result.push(c),
+        }
+    }
+    Cow::Owned(result)
+}
+
+/// Value of an attribute in an XML tag.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag
+        .match_indices(name)
+        .map(|(index, _)| index)
+        .find(|&index| {
+            (index == 0 || tag[..index].ends_with(char::is_whitespace))
+                && tag[index + name.len()..].starts_with("=\"")
+        })?
+        + name.len()
+        + 2;
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SVG: &str = r#"<svg viewBox="-100 -50 200 100" xmlns="http://www.w3.org/2000/svg">
+<circle cx="10" cy="-20" id="n6" r="50" data-name="Twin Terrors" data-kind="Notable" />
+<circle cx="5" cy="4" id="n89" r="50" class="mastery" data-name="Mine Mastery" />
+<circle cx="-30" cy="15" id="n94" r="50" data-name="Evasion" data-kind="Normal" />
+<circle cx="60" cy="-7" id="n193" r="45" class="ascendancy Ascendant" data-name="Strength" />
+<circle cx="1" cy="2" id="n200" r="50" class="keystone" data-name="Iron Reflexes" />
+</svg>"#;
+
+    #[test]
+    fn tree_from_svg() {
+        let tree = Tree::from_svg(SVG, &[6, 193, 200]).unwrap();
+        assert_eq!(tree.view_box, (-100.0, -50.0, 200.0, 100.0));
+        assert_eq!(tree.allocated, vec![(10.0, -20.0), (1.0, 2.0)]);
+        assert_eq!(tree.nodes, vec![(-30.0, 15.0)]);
+
+        assert_eq!(Tree::from_svg("<svg>", &[]), None);
+    }
+
+    #[test]
+    fn attributes() {
+        let tag = r#"circle cx="1" data-id="x" id="n2" class="a b""#;
+        assert_eq!(attribute(tag, "cx"), Some("1"));
+        assert_eq!(attribute(tag, "id"), Some("n2"));
+        assert_eq!(attribute(tag, "class"), Some("a b"));
+        assert_eq!(attribute(tag, "cy"), None);
+    }
+
+    #[test]
+    fn wrap_and_escape() {
+        let stats = ["Life: 5000", "ES: 100", "Mana: 200"].map(str::to_owned);
+        assert_eq!(wrap(&stats, 22), vec!["Life: 5000 · ES: 100", "Mana: 200"]);
+        assert_eq!(escape("a<b> & \"c\""), "a&lt;b&gt; &amp; &quot;c&quot;");
+        assert_eq!(truncate("abcdef", 3), "abc…");
+        assert_eq!(truncate("abc", 3), "abc");
+    }
+}
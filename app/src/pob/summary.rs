@@ -181,13 +181,7 @@ pub fn offense(pob: &impl PathOfBuilding) -> Vec<Element<'_>> {
     // TODO: real minion support
     let is_minion = pob.minion_stat(Stat::CombinedDps).is_some();
 
-    let dps = pob
-        .stat_parse(Stat::FullDps)
-        .filter(|&dps: &f32| dps.is_finite() && dps > 0.0)
-        .or_else(|| match is_minion {
-            true => pob.minion_stat_parse(Stat::CombinedDps),
-            false => pob.stat_parse(Stat::CombinedDps),
-        });
+    let dps = pob::dps(pob);
 
     Element::new("DPS")
         .color(AMBER_50)
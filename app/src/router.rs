@@ -1,4 +1,4 @@
-use shared::{Id, User};
+use shared::{Id, PasteId, User, UserPasteId};
 use sycamore::prelude::*;
 use sycamore_router::{HistoryIntegration, Router as DynRouter};
 use web_sys::Element;
@@ -21,14 +21,30 @@ pub enum Route {
     Paste(Id),
     #[to("/u/<name>")]
     User(User),
+    #[to("/u/<name>/c/<collection>")]
+    UserCollection(User, Id),
     #[to("/u/<name>/<id>")]
     UserPaste(User, Id),
     #[to("/u/<name>/<id>/edit")]
     UserEditPaste(User, Id),
+    #[to("/u/<name>/<id>/history")]
+    UserPasteHistory(User, Id),
+    #[to("/embed/<id>")]
+    Embed(Id),
+    #[to("/embed/u/<name>/<id>")]
+    UserEmbed(User, Id),
     #[not_found]
     NotFound,
 }
 
+impl Route {
+    /// Whether the route renders a compact view for embedding in other sites,
+    /// embedded views are rendered without header and footer.
+    pub fn is_embed(&self) -> bool {
+        matches!(self, Self::Embed(_) | Self::UserEmbed(..))
+    }
+}
+
 pub trait RoutedComponent
 where
     Self: Sized,
@@ -134,8 +150,11 @@ enum Page {
     Index(pages::IndexPage),
     Paste(pages::PastePage),
     User(pages::UserPage),
+    UserCollection(pages::UserCollectionPage),
     UserPaste(pages::UserPastePage),
     UserEditPaste(pages::UserEditPastePage),
+    UserPasteHistory(pages::UserPasteHistoryPage),
+    Embed(pages::EmbedPage),
     Error(u16, String),
 }
 
@@ -148,10 +167,18 @@ impl Page {
                     Self::Paste(pages::PastePage::from_context(arg.clone(), ctx)?),
                 Ok(Route::User(arg)) =>
                     Self::User(pages::UserPage::from_context(arg.clone(), ctx)?),
+                Ok(Route::UserCollection(user, id)) =>
+                    Self::UserCollection(pages::UserCollectionPage::from_context((user.clone(), id.clone()), ctx)?),
                 Ok(Route::UserPaste(user, id)) =>
                     Self::UserPaste(pages::UserPastePage::from_context((user.clone(), id.clone()), ctx)?),
                 Ok(Route::UserEditPaste(user, id)) =>
                     Self::UserEditPaste(pages::UserEditPastePage::from_context((user.clone(), id.clone()), ctx)?),
+                Ok(Route::UserPasteHistory(user, id)) =>
+                    Self::UserPasteHistory(pages::UserPasteHistoryPage::from_context((user.clone(), id.clone()), ctx)?),
+                Ok(Route::Embed(id)) =>
+                    Self::Embed(pages::EmbedPage::from_context(PasteId::Paste(id.clone()), ctx)?),
+                Ok(Route::UserEmbed(user, id)) =>
+                    Self::Embed(pages::EmbedPage::from_context(UserPasteId { user: user.clone(), id: id.clone() }.into(), ctx)?),
                 Ok(Route::NotFound) => Self::not_found(),
                 Err(err) => Self::resolve_err(err),
             })
@@ -203,12 +230,24 @@ impl Page {
                 Route::Index => Self::Index(pages::IndexPage::from_hydration((), element)?),
                 Route::Paste(arg) => Self::Paste(pages::PastePage::from_hydration(arg.clone(), element)?),
                 Route::User(arg) => Self::User(pages::UserPage::from_hydration(arg.clone(), element)?),
+                Route::UserCollection(user, id) => Self::UserCollection(
+                    pages::UserCollectionPage::from_hydration((user.clone(), id.clone()), element)?
+                ),
                 Route::UserPaste(user, id) => Self::UserPaste(
                     pages::UserPastePage::from_hydration((user.clone(), id.clone()), element)?
                 ),
                 Route::UserEditPaste(user, id) => Self::UserEditPaste(
                     pages::UserEditPastePage::from_hydration((user.clone(), id.clone()), element)?
                 ),
+                Route::UserPasteHistory(user, id) => Self::UserPasteHistory(
+                    pages::UserPasteHistoryPage::from_hydration((user.clone(), id.clone()), element)?
+                ),
+                Route::Embed(id) => Self::Embed(
+                    pages::EmbedPage::from_hydration(PasteId::Paste(id.clone()), element)?
+                ),
+                Route::UserEmbed(user, id) => Self::Embed(
+                    pages::EmbedPage::from_hydration(UserPasteId { user: user.clone(), id: id.clone() }.into(), element)?
+                ),
                 Route::NotFound => Self::not_found(),
             })
         };
@@ -227,12 +266,24 @@ impl Page {
                 Route::User(arg) => {
                     Self::User(pages::UserPage::from_dynamic(arg.clone()).await?)
                 },
+                Route::UserCollection(user, id) => {
+                    Self::UserCollection(pages::UserCollectionPage::from_dynamic((user.clone(), id.clone())).await?)
+                },
                 Route::UserPaste(user, id) => {
                     Self::UserPaste(pages::UserPastePage::from_dynamic((user.clone(), id.clone())).await?)
                 },
                 Route::UserEditPaste(user, id) => {
                     Self::UserEditPaste(pages::UserEditPastePage::from_dynamic((user.clone(), id.clone())).await?)
                 },
+                Route::UserPasteHistory(user, id) => {
+                    Self::UserPasteHistory(pages::UserPasteHistoryPage::from_dynamic((user.clone(), id.clone())).await?)
+                },
+                Route::Embed(id) => {
+                    Self::Embed(pages::EmbedPage::from_dynamic(PasteId::Paste(id.clone())).await?)
+                },
+                Route::UserEmbed(user, id) => {
+                    Self::Embed(pages::EmbedPage::from_dynamic(UserPasteId { user: user.clone(), id: id.clone() }.into()).await?)
+                },
                 Route::NotFound => Self::not_found(),
             })
         };
@@ -266,6 +317,7 @@ impl Page {
         // e.g. not found page displaying the resource type
         match err {
             Error::NotFound(_, _) => Self::Error(404, "Not Found".to_owned()),
+            Error::Gone(_, _) => Self::Error(410, "This build has been removed".to_owned()),
             // TODO: rethink this, if this happens because of a pastebin.com build this is fine and
             // a 400 status code, if this happens on an uploaded paste, this is a problem.
             Error::PobError(_) => Self::Error(400, "Invalid Build Code".to_owned()),
@@ -278,8 +330,11 @@ impl Page {
             Self::Index(ref page) => page.meta(),
             Self::Paste(ref page) => page.meta(),
             Self::User(ref page) => page.meta(),
+            Self::UserCollection(ref page) => page.meta(),
             Self::UserPaste(ref page) => page.meta(),
             Self::UserEditPaste(ref page) => page.meta(),
+            Self::UserPasteHistory(ref page) => page.meta(),
+            Self::Embed(ref page) => page.meta(),
             Self::Error(_, message) => Ok(Meta::error(message)),
         }
     }
@@ -322,8 +377,11 @@ fn render<G: Html>(cx: Scope, page: Page) -> View<G> {
         Page::Index(page) => page.render(cx),
         Page::Paste(page) => page.render(cx),
         Page::User(page) => page.render(cx),
+        Page::UserCollection(page) => page.render(cx),
         Page::UserPaste(page) => page.render(cx),
         Page::UserEditPaste(page) => page.render(cx),
+        Page::UserPasteHistory(page) => page.render(cx),
+        Page::Embed(page) => page.render(cx),
         Page::Error(status_code, message) => view! { cx,
             // This needs to be in a component to not interfere with hydration.
             // A new hydration level is introduced per component, this
@@ -5,6 +5,9 @@ use sycamore::{prelude::*, reactive::provide_context};
 
 use crate::consts::MAX_SESSION_DURATION;
 
+/// Maximum length of the signing key id, see `Dangerous` in the worker.
+const MAX_KEY_ID_LEN: usize = 8;
+
 // TODO move this into shared
 #[derive(Debug, Deserialize, Serialize)]
 pub struct User {
@@ -54,6 +57,12 @@ impl Session {
             None => return Err("invalid format, missing signature".into()),
         };
 
+        // Newer sessions contain a short key id between the timestamp and signature.
+        let session = match session.rsplit_once('.') {
+            Some((session, key_id)) if key_id.len() <= MAX_KEY_ID_LEN => session,
+            _ => session,
+        };
+
         let (session, ts) = match session.rsplit_once('.') {
             Some((session, ts)) => (session, ts),
             None => return Err("invalid format, missing timestamp".into()),
@@ -19,7 +19,7 @@ pub use self::items::{Influence, Item, Mod, Rarity};
 pub use self::passives::Keystone;
 pub use self::serde::SerdePathOfBuilding;
 pub use self::stats::Stat;
-pub use self::utils::decompress;
+pub use self::utils::{compress, decompress};
 
 pub trait PathOfBuilding {
     fn game_version(&self) -> GameVersion;
@@ -41,7 +41,8 @@ pub(crate) struct Build {
     pub class_name: Class,
     #[serde(default, deserialize_with = "deserialize_ascendancy")]
     pub ascend_class_name: Option<Ascendancy>,
-    #[serde(rename = "$value")]
+    /// Stats calculated by Path of Building, missing for builds not yet opened in PoB.
+    #[serde(default, rename = "$value")]
     pub stats: Vec<StatType>,
     pub main_socket_group: u8,
     #[serde(default, deserialize_with = "deserialize_str_none")]
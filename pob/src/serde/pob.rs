@@ -721,4 +721,12 @@ mod tests {
         let pob = SerdePathOfBuilding::from_xml(V316_EMPTY).unwrap();
         assert!(pob.loadouts().is_empty());
     }
+
+    #[test]
+    fn export_roundtrip() {
+        let export = crate::compress(V316_EMPTY);
+        let pob = SerdePathOfBuilding::from_export(&export).unwrap();
+        assert_eq!(1, pob.level());
+        assert_eq!(Class::Scion, pob.class());
+    }
 }
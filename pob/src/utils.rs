@@ -1,6 +1,6 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use flate2::bufread::ZlibDecoder;
+use flate2::{bufread::ZlibDecoder, write::ZlibEncoder, Compression};
 
 use crate::{Error, Result};
 
@@ -9,6 +9,17 @@ pub fn decompress(data: &str) -> Result<String> {
     deflate(&data)
 }
 
+/// Encodes the XML of a build as a Path of Building export code.
+pub fn compress(xml: &str) -> String {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(xml.as_bytes())
+        .expect("writing to a vec never fails");
+    let data = encoder.finish().expect("writing to a vec never fails");
+
+    base64::encode_config(data, base64::URL_SAFE)
+}
+
 fn decode(data: &str) -> Result<Vec<u8>> {
     base64::decode_config(data.trim(), base64::URL_SAFE).map_err(Error::Base64Decode)
 }
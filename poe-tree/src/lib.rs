@@ -73,6 +73,18 @@ macro_rules! gen {
                 unreachable!("no version enabled")
             }
 
+            /// Tree version as used by Path of Building, e.g. `3_25`.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Self::$version => &stringify!($module)["tree".len()..],
+                    )*
+                    #[allow(unreachable_patterns)]
+                    _ => unreachable!("no version enabled"),
+                }
+            }
+
             fn get_node(&self, _id: u32) -> Option<&'static Node> {
                 match self {
                     $(
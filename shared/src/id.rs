@@ -120,6 +120,47 @@ impl UserPasteId {
         self.to_paste_url().join("raw")
     }
 
+    /// Returns the URL to the API managing the share link of the paste.
+    pub fn to_share_api_url(&self) -> UrlSafe<'static> {
+        self.to_paste_api_url().join("share")
+    }
+
+    /// Returns the URL to the API which moves the paste to a new id.
+    pub fn to_rename_api_url(&self) -> UrlSafe<'static> {
+        self.to_paste_api_url().join("rename")
+    }
+
+    /// Returns the URL to the frontend revision history page.
+    pub fn to_history_url(&self) -> UrlSafe<'static> {
+        self.to_paste_url().join("history")
+    }
+
+    /// Returns the URL to the API listing all revisions of the paste.
+    pub fn to_revisions_api_url(&self) -> UrlSafe<'static> {
+        self.to_paste_api_url().join("revisions")
+    }
+
+    /// Returns the URL to restore a specific revision of the paste.
+    pub fn to_revision_restore_api_url(&self, revision: u64) -> UrlSafe<'static> {
+        self.to_revisions_api_url()
+            .join(revision.to_string().as_str())
+            .join("restore")
+    }
+
+    pub fn to_revision_raw_url(&self, revision: u64) -> UrlSafe<'static> {
+        self.to_paste_url()
+            .join("revisions")
+            .join(revision.to_string().as_str())
+            .join("raw")
+    }
+
+    pub fn to_revision_json_url(&self, revision: u64) -> UrlSafe<'static> {
+        self.to_paste_url()
+            .join("revisions")
+            .join(revision.to_string().as_str())
+            .join("json")
+    }
+
     pub fn to_json_url(&self) -> UrlSafe<'static> {
         self.to_paste_url().join("json")
     }
@@ -182,6 +223,36 @@ impl PasteId {
         }
     }
 
+    /// Returns the URL to the compact, embeddable view of the paste.
+    pub fn to_embed_url(&self) -> UrlSafe<'static> {
+        let embed = UrlSafe::SLASH.join("embed");
+        match self {
+            Self::Paste(id) => embed.join(id.as_str()),
+            Self::UserPaste(up) => embed.join("u").join(&*up.user).join(&*up.id),
+        }
+    }
+
+    pub fn to_paste_api_url(&self) -> UrlSafe<'static> {
+        match self {
+            Self::Paste(id) => UrlSafe::SLASH
+                .join("api")
+                .join("internal")
+                .join("paste")
+                .join(id.as_str()),
+            Self::UserPaste(up) => up.to_paste_api_url(),
+        }
+    }
+
+    /// Returns the URL to the API which forks the paste into the current user's profile.
+    pub fn to_fork_api_url(&self) -> UrlSafe<'static> {
+        self.to_paste_api_url().join("fork")
+    }
+
+    /// Returns the URL to the API which reports the paste for review by a moderator.
+    pub fn to_report_api_url(&self) -> UrlSafe<'static> {
+        self.to_paste_api_url().join("report")
+    }
+
     pub fn to_raw_url(&self) -> UrlSafe<'static> {
         match self {
             Self::Paste(id) => UrlSafe::SLASH.join(id.as_str()).join("raw"),
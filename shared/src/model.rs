@@ -1,8 +1,8 @@
-use std::num::NonZeroU8;
+use std::{fmt::Write as _, num::NonZeroU8};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{AscendancyOrClass, GameVersion, PasteId, UrlSafe};
+use crate::{AscendancyOrClass, GameVersion, Id, PasteId, UrlSafe, User};
 
 #[derive(Debug)]
 pub struct ListPaste {
@@ -21,7 +21,7 @@ pub struct Paste {
     pub data: data::Data,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PasteMetadata {
     pub title: String,
     #[serde(default, skip_serializing_if = "is_v1")]
@@ -35,6 +35,15 @@ pub struct PasteMetadata {
     pub rank: Option<NonZeroU8>,
     #[serde(default, skip_serializing_if = "is_false")]
     pub private: bool,
+    /// The paste this paste was forked from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<PasteId>,
+    /// User defined tags, normalized with [`crate::validation::user::normalize_tags`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Short user defined notes.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -53,6 +62,308 @@ pub struct PasteSummary {
     pub rank: Option<NonZeroU8>,
     #[serde(default, skip_serializing_if = "is_false")]
     pub private: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+/// A previous version of a user paste.
+///
+/// Revisions are created whenever a user paste is overwritten.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PasteRevision {
+    /// Identifier of the revision, this is the time the revision was originally
+    /// last modified in milliseconds.
+    pub revision: u64,
+    pub metadata: PasteMetadata,
+}
+
+/// A single page of a user's pastes.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PastePage {
+    pub pastes: Vec<PasteSummary>,
+    /// Cursor to the next page, `None` if this is the last page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+    /// Total amount of pastes matching the query.
+    pub total: usize,
+}
+
+/// Sort order of a user's pastes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteSort {
+    /// Pinned pastes first, then the most recently modified.
+    #[default]
+    Pinned,
+    Newest,
+    Oldest,
+    Title,
+}
+
+impl PasteSort {
+    pub const ALL: [Self; 4] = [Self::Pinned, Self::Newest, Self::Oldest, Self::Title];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pinned => "pinned",
+            Self::Newest => "newest",
+            Self::Oldest => "oldest",
+            Self::Title => "title",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|sort| sort.as_str() == s)
+    }
+}
+
+/// Filters, sorting and pagination of a user's pastes.
+///
+/// The query is transported as query parameters of the user page
+/// and the user API.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ListPastesQuery {
+    /// Ascendancy or class, a class also matches all its ascendancies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    /// Main skill name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_version: Option<GameVersion>,
+    /// Only private or only public pastes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private: Option<bool>,
+    /// User defined tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub sort: PasteSort,
+    /// Cursor returned with the previous page, see [`PastePage::next`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+impl ListPastesQuery {
+    /// Parses the query from a query string, with or without the leading `?`.
+    ///
+    /// Unknown or invalid parameters are ignored.
+    pub fn from_query_string(query: &str) -> Self {
+        let mut result = Self::default();
+
+        let query = query.strip_prefix('?').unwrap_or(query);
+        for pair in query.split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = value.replace('+', " ");
+            let value = percent_encoding::percent_decode_str(&value).decode_utf8_lossy();
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+
+            match key {
+                "class" => result.class = Some(value.to_owned()),
+                "skill" => result.skill = Some(value.to_owned()),
+                "game_version" => {
+                    result.game_version = match value {
+                        "1" => Some(GameVersion::One),
+                        "2" => Some(GameVersion::Two),
+                        _ => None,
+                    }
+                }
+                "private" => result.private = value.parse().ok(),
+                "tag" => result.tag = Some(value.to_lowercase()),
+                "sort" => result.sort = PasteSort::from_name(value).unwrap_or_default(),
+                "cursor" => result.cursor = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    /// Serializes the query into a query string including the leading `?`.
+    ///
+    /// Returns an empty string for the default query.
+    pub fn to_query_string(&self) -> String {
+        let mut s = String::new();
+        let mut push = |key: &str, value: &str| {
+            let sep = if s.is_empty() { '?' } else { '&' };
+            let value =
+                percent_encoding::utf8_percent_encode(value, percent_encoding::NON_ALPHANUMERIC);
+            let _ = write!(&mut s, "{sep}{key}={value}");
+        };
+
+        if let Some(class) = &self.class {
+            push("class", class);
+        }
+        if let Some(skill) = &self.skill {
+            push("skill", skill);
+        }
+        match self.game_version {
+            Some(GameVersion::One) => push("game_version", "1"),
+            Some(GameVersion::Two) => push("game_version", "2"),
+            None => {}
+        }
+        if let Some(private) = self.private {
+            push("private", if private { "true" } else { "false" });
+        }
+        if let Some(tag) = &self.tag {
+            push("tag", tag);
+        }
+        if self.sort != PasteSort::default() {
+            push("sort", self.sort.as_str());
+        }
+        if let Some(cursor) = &self.cursor {
+            push("cursor", cursor);
+        }
+
+        s
+    }
+
+    /// Returns the same query for a different page.
+    pub fn with_cursor(&self, cursor: Option<String>) -> Self {
+        Self {
+            cursor,
+            ..self.clone()
+        }
+    }
+
+    /// Whether the query is the unfiltered first page.
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// A named collection of user pastes, e.g. league starters of a league.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Collection {
+    pub id: Id,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Private collections are only visible to their owner.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub private: bool,
+    /// Pastes of the collection, in the order they are displayed.
+    #[serde(default)]
+    pub pastes: Vec<Id>,
+    #[serde(default, skip_serializing_if = "crate::utils::is_zero")]
+    pub last_modified: u64,
+}
+
+/// A collection together with the summaries of its pastes.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CollectionPage {
+    pub user: User,
+    pub collection: Collection,
+    /// Summaries of all pastes visible to the current user, in collection order.
+    pub pastes: Vec<PasteSummary>,
+}
+
+/// A personal API token of a user.
+///
+/// The token itself is only returned once, when it is created, see [`NewApiToken`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApiToken {
+    pub id: String,
+    /// User supplied name to identify the token.
+    pub name: String,
+    /// Creation time in milliseconds.
+    pub created: u64,
+}
+
+/// A newly created API token.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NewApiToken {
+    #[serde(flatten)]
+    pub info: ApiToken,
+    /// The secret token, used as a bearer token in the `Authorization` header.
+    pub token: String,
+}
+
+/// An unlisted share link of a private paste.
+///
+/// The token grants read access to the paste, when passed as `share` query parameter.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShareLink {
+    pub token: String,
+    /// Creation time in milliseconds.
+    pub created: u64,
+}
+
+/// An outgoing webhook of a user, notified when a paste of the user changes.
+///
+/// The secret is only returned once, when it is created, see [`NewWebhook`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// Creation time in milliseconds.
+    pub created: u64,
+}
+
+/// Payload format of a webhook.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// Plain JSON event.
+    #[default]
+    Json,
+    /// Discord compatible message with an embed.
+    Discord,
+}
+
+/// A newly created webhook.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NewWebhook {
+    #[serde(flatten)]
+    pub info: Webhook,
+    /// Secret used to sign the payloads with HMAC-SHA256.
+    pub secret: String,
+}
+
+/// A report of a paste, waiting for review by a moderator.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PasteReport {
+    pub id: String,
+    pub paste: PasteId,
+    pub reason: String,
+    /// The reporting user, `None` for anonymous reports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reporter: Option<User>,
+    /// Creation time in milliseconds.
+    pub created: u64,
+}
+
+/// Results of a search over all public pastes.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SearchResults {
+    /// Total amount of pastes matching the query.
+    pub total: usize,
+    /// The most recently modified pastes matching the query.
+    pub pastes: Vec<PasteSummary>,
+    pub facets: SearchFacets,
+}
+
+/// Value counts of all pastes matching a search query.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SearchFacets {
+    pub ascendancy_or_class: Vec<FacetCount>,
+    pub main_skill_name: Vec<FacetCount>,
+    pub game_version: Vec<FacetCount>,
+    pub version: Vec<FacetCount>,
+    pub tags: Vec<FacetCount>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: usize,
 }
 
 impl PasteSummary {
@@ -135,3 +446,154 @@ pub mod data {
         pub quest: String,
     }
 }
+
+/// Compact, machine readable summary of a build, served as `/:id/summary.json`.
+///
+/// The schema is versioned by [`summary::VERSION`], the version is incremented for every
+/// incompatible change. New fields may be added without incrementing the version.
+pub mod summary {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{Ascendancy, Class, GameVersion};
+
+    /// Current version of the schema.
+    pub const VERSION: u32 = 1;
+
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+    pub struct BuildSummary {
+        /// Version of the schema, see [`VERSION`].
+        pub version: u32,
+        pub title: String,
+        pub game_version: GameVersion,
+        pub class: Class,
+        pub ascendancy: Option<Ascendancy>,
+        pub level: u8,
+        /// Version of the active passive tree, e.g. `3.25`.
+        pub tree_version: Option<String>,
+        pub main_skill: Option<MainSkill>,
+        pub stats: Stats,
+        /// Names of the allocated keystones.
+        pub keystones: Vec<String>,
+        pub masteries: Vec<Mastery>,
+        /// Equipped gear of the selected item set, empty slots are omitted.
+        pub gear: Vec<GearItem>,
+    }
+
+    /// The selected skill group.
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+    pub struct MainSkill {
+        /// Name of the main skill as selected in Path of Building.
+        pub name: Option<String>,
+        pub gems: Vec<Gem>,
+        pub supports: Vec<Gem>,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+    pub struct Gem {
+        pub name: String,
+        pub level: u8,
+        pub quality: u8,
+    }
+
+    /// Key stats as calculated by Path of Building.
+    ///
+    /// Stats which are not calculated by Path of Building are `null`.
+    #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+    pub struct Stats {
+        /// Full DPS or combined DPS of the main skill, includes minions.
+        pub dps: Option<f32>,
+        pub life: Option<f32>,
+        pub energy_shield: Option<f32>,
+        pub mana: Option<f32>,
+        pub ward: Option<f32>,
+        /// Total effective hit pool.
+        pub ehp: Option<f32>,
+        pub resistances: Resistances,
+        pub max_hits: MaxHits,
+    }
+
+    #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+    pub struct Resistances {
+        pub fire: Option<f32>,
+        pub cold: Option<f32>,
+        pub lightning: Option<f32>,
+        pub chaos: Option<f32>,
+    }
+
+    /// Maximum hit taken per damage type.
+    #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+    pub struct MaxHits {
+        pub physical: Option<f32>,
+        pub fire: Option<f32>,
+        pub cold: Option<f32>,
+        pub lightning: Option<f32>,
+        pub chaos: Option<f32>,
+    }
+
+    /// An allocated mastery and its selected effects.
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+    pub struct Mastery {
+        pub name: String,
+        pub effects: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+    pub struct GearItem {
+        /// Name of the slot, e.g. `Body Armour` or `Flask 1`.
+        pub slot: String,
+        /// Name of the item, `None` for normal and magic items.
+        pub name: Option<String>,
+        pub base: String,
+        pub rarity: Rarity,
+    }
+
+    #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Rarity {
+        Normal,
+        Magic,
+        Rare,
+        Unique,
+        Relic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_pastes_query_roundtrip() {
+        let query = ListPastesQuery {
+            class: Some("Chieftain".to_owned()),
+            skill: Some("Righteous Fire & Friends".to_owned()),
+            game_version: Some(GameVersion::Two),
+            private: Some(false),
+            tag: Some("league start".to_owned()),
+            sort: PasteSort::Title,
+            cursor: Some("abc-_".to_owned()),
+        };
+
+        let s = query.to_query_string();
+        assert_eq!(
+            s,
+            "?class=Chieftain&skill=Righteous%20Fire%20%26%20Friends&game_version=2\
+             &private=false&tag=league%20start&sort=title&cursor=abc%2D%5F"
+        );
+        assert_eq!(ListPastesQuery::from_query_string(&s), query);
+    }
+
+    #[test]
+    fn test_list_pastes_query_default() {
+        assert_eq!(ListPastesQuery::default().to_query_string(), "");
+        assert!(ListPastesQuery::from_query_string("").is_default());
+        assert!(ListPastesQuery::from_query_string("?sort=pinned&foo=bar&class=").is_default());
+        assert_eq!(
+            ListPastesQuery::from_query_string("skill=Cyclone+of+Tumult&game_version=3"),
+            ListPastesQuery {
+                skill: Some("Cyclone of Tumult".to_owned()),
+                ..Default::default()
+            }
+        );
+    }
+}
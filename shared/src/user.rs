@@ -35,6 +35,21 @@ impl User {
     pub fn to_url(&self) -> UrlSafe<'static> {
         UrlSafe::SLASH.join("u").join(&*self.0)
     }
+
+    /// Returns the URL to the frontend page of a collection.
+    pub fn to_collection_url(&self, collection: &crate::Id) -> UrlSafe<'static> {
+        self.to_url().join("c").join(collection.as_str())
+    }
+
+    /// Returns the URL to the API listing all collections of the user.
+    pub fn to_collections_api_url(&self) -> UrlSafe<'static> {
+        self.to_api_url().join("collections")
+    }
+
+    /// Returns the URL to the API of a collection.
+    pub fn to_collection_api_url(&self, collection: &crate::Id) -> UrlSafe<'static> {
+        self.to_collections_api_url().join(collection.as_str())
+    }
 }
 
 impl std::ops::Deref for User {
@@ -82,6 +82,63 @@ pub mod user {
         }
     }
 
+    /// Maximum amount of tags on a single paste.
+    pub const MAX_TAGS: usize = 10;
+
+    #[must_use]
+    pub fn is_valid_tag(tag: &str) -> Validation {
+        match tag.len() {
+            0 => return Invalid("Tag too short"),
+            1..=24 => (),
+            _ => return Invalid("Tag too long"),
+        };
+
+        let valid = tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+
+        match valid {
+            true => Valid,
+            false => Invalid("Invalid Tag, allowed characters: letters, digits, space, _ and -"),
+        }
+    }
+
+    #[must_use]
+    pub fn is_valid_tags(tags: &[String]) -> Validation {
+        if tags.len() > MAX_TAGS {
+            return Invalid("Too many tags");
+        }
+
+        tags.iter()
+            .map(|tag| is_valid_tag(tag))
+            .find(|v| !v.is_valid())
+            .unwrap_or(Valid)
+    }
+
+    #[must_use]
+    pub fn is_valid_description(description: &str) -> Validation {
+        // Stored in the R2 custom metadata, which is limited in size.
+        match description.len() {
+            0..=300 => Valid,
+            _ => Invalid("Description too long"),
+        }
+    }
+
+    /// Normalizes user supplied tags, tags are trimmed, lowercased and deduplicated.
+    ///
+    /// Empty tags are removed, the order of the remaining tags is kept.
+    pub fn normalize_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut result: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = tag.split_whitespace().collect::<Vec<_>>().join(" ");
+            let tag = tag.to_lowercase();
+            if !tag.is_empty() && !result.contains(&tag) {
+                result.push(tag);
+            }
+        }
+        result
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -104,6 +161,23 @@ pub mod user {
             assert!(is_valid_custom_title("aks jda;klsdäö").is_valid());
         }
 
+        #[test]
+        fn test_tags() {
+            assert!(is_valid_tags(&["budget".to_owned(), "league start".to_owned()]).is_valid());
+            assert!(!is_valid_tags(&["".to_owned()]).is_valid());
+            assert!(!is_valid_tags(&["a;b".to_owned()]).is_valid());
+            assert!(!is_valid_tags(&["a".repeat(25)]).is_valid());
+            assert!(!is_valid_tags(&vec!["a".to_owned(); MAX_TAGS + 1]).is_valid());
+        }
+
+        #[test]
+        fn test_normalize_tags() {
+            assert_eq!(
+                normalize_tags([" Uber ", "budget", "", "uber", "League   Start"]),
+                ["uber", "budget", "league start"]
+            );
+        }
+
         #[test]
         fn test_id_length() {
             for i in 0..200 {
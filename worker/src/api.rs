@@ -1,9 +1,13 @@
 use std::{borrow::Cow, num::NonZeroU8, rc::Rc, time::Duration};
 
-use pob::{PathOfBuilding, PathOfBuildingExt, SerdePathOfBuilding};
+use app::pob::markup::Markup;
+use pob::{PathOfBuilding, SerdePathOfBuilding};
 use sentry::MetricUnit;
 use serde::{Deserialize, Serialize};
-use shared::{model::PasteMetadata, validation, PasteId, User, UserPasteId};
+use shared::{
+    model::{ListPastesQuery, PasteMetadata},
+    validation, PasteId, User, UserPasteId,
+};
 
 use crate::{
     consts, crypto, poe_api,
@@ -11,6 +15,7 @@ use crate::{
     response,
     route::{self, DeleteEndpoints, GetEndpoints, PostEndpoints},
     statsd::{Counters, Distributions},
+    storage::to_metadata,
     utils::{self, CacheControl, Etag, LenientId, RequestExt},
     Error, Response, Result,
 };
@@ -54,12 +59,22 @@ pub async fn handle(rctx: &mut RequestContext, route: route::Api) -> response::R
     // Currently this can happen on some API endpoints related to login/auth,
     // these are handled as API endpoints but are user facing, meaning
     // the user would expect a proper error page not just some JSON.
-    let is_user_api = matches!(&route, Get(Login) | Get(Oauht2Poe));
+    let is_user_api = matches!(
+        &route,
+        Get(Login) | Get(Oauht2Poe) | Get(ImportCharacter(_))
+    );
 
     let r = match route {
         // Get
         Get(Oembed) => handle_oembed(rctx).await,
         Get(User(user)) => handle_user(rctx, user).await,
+        Get(UserCollections(user)) => handle_user_collections(rctx, user).await,
+        Get(UserCollection(user, id)) => handle_user_collection(rctx, user, id).await,
+        Get(Search) => handle_search(rctx).await,
+        Get(ApiTokens) => handle_api_tokens(rctx).await,
+        Get(Webhooks) => handle_webhooks(rctx).await,
+        Get(Export) => handle_export(rctx).await,
+        Get(Reports) => handle_reports(rctx).await,
         Get(PobPaste(LenientId(id))) => handle_download_text(rctx, id).await,
         Get(PobUserPaste(user, LenientId(id))) => {
             handle_download_text(rctx, UserPasteId { user, id }.into()).await
@@ -76,13 +91,64 @@ pub async fn handle(rctx: &mut RequestContext, route: route::Api) -> response::R
         Get(UserPasteXml(user, id)) => {
             handle_download_xml(rctx, UserPasteId { user, id }.into()).await
         }
+        Get(PasteMarkdown(id)) => {
+            handle_download_markup(rctx, PasteId::Paste(id), Markup::Markdown).await
+        }
+        Get(UserPasteMarkdown(user, id)) => {
+            handle_download_markup(rctx, UserPasteId { user, id }.into(), Markup::Markdown).await
+        }
+        Get(PasteBbCode(id)) => {
+            handle_download_markup(rctx, PasteId::Paste(id), Markup::BbCode).await
+        }
+        Get(UserPasteBbCode(user, id)) => {
+            handle_download_markup(rctx, UserPasteId { user, id }.into(), Markup::BbCode).await
+        }
+        Get(PasteBuildSummary(id)) => handle_build_summary(rctx, PasteId::Paste(id)).await,
+        Get(UserPasteBuildSummary(user, id)) => {
+            handle_build_summary(rctx, UserPasteId { user, id }.into()).await
+        }
+        Get(PastePreview(id)) => handle_preview(rctx, PasteId::Paste(id)).await,
+        Get(UserPastePreview(user, id)) => {
+            handle_preview(rctx, UserPasteId { user, id }.into()).await
+        }
+        Get(PasteRevisions(id)) => handle_paste_revisions(rctx, id).await,
+        Get(PasteShare(id)) => handle_paste_share(rctx, id).await,
+        Get(UserPasteRevision(user, id, revision)) => {
+            handle_download_revision_text(rctx, UserPasteId { user, id }, revision).await
+        }
+        Get(UserPasteRevisionJson(user, id, revision)) => {
+            handle_download_revision_json(rctx, UserPasteId { user, id }, revision).await
+        }
         Get(Login) => handle_login(rctx).await,
         Get(Oauht2Poe) => handle_oauth2_poe(rctx).await,
+        Get(ImportCharacter(name)) => handle_import_character(rctx, name).await,
         // Post
         Post(Upload) => handle_upload(rctx).await,
         Post(PobUpload) => handle_pob_upload(rctx).await,
+        Post(RestorePasteRevision(id, revision)) => {
+            handle_restore_paste_revision(rctx, id, revision).await
+        }
+        Post(ForkPaste(id)) => handle_fork_paste(rctx, id).await,
+        Post(RenamePaste(id)) => handle_rename_paste(rctx, id).await,
+        Post(CreatePasteShare(id)) => handle_create_paste_share(rctx, id).await,
+        Post(ReportPaste(id)) => handle_report_paste(rctx, id).await,
+        Post(TakedownPaste(id)) => handle_takedown_paste(rctx, id).await,
+        Post(RunLifecycle) => handle_run_lifecycle(rctx).await,
+        Post(RunMaintenance(task)) => handle_run_maintenance(rctx, task).await,
+        Post(CreateApiToken) => handle_create_api_token(rctx).await,
+        Post(CreateWebhook) => handle_create_webhook(rctx).await,
+        Post(SaveCollection) => handle_save_collection(rctx).await,
+        Post(Import) => handle_import(rctx).await,
+        Post(ImportUrl) => handle_import_url(rctx).await,
+        Post(Logout) => handle_logout(rctx).await,
+        Post(LogoutEverywhere) => handle_logout_everywhere(rctx).await,
         // Delete
         Delete(DeletePaste(id)) => handle_delete_paste(rctx, id).await,
+        Delete(RevokePasteShare(id)) => handle_revoke_paste_share(rctx, id).await,
+        Delete(RevokeApiToken(id)) => handle_revoke_api_token(rctx, id).await,
+        Delete(DeleteWebhook(id)) => handle_delete_webhook(rctx, id).await,
+        Delete(DeleteCollection(id)) => handle_delete_collection(rctx, id).await,
+        Delete(DismissReport(id)) => handle_dismiss_report(rctx, id).await,
         // Not Found Routes - these should never happen,
         // but they are there because sycamore_router requires them.
         Get(GetEndpoints::NotFound)
@@ -96,87 +162,164 @@ pub async fn handle(rctx: &mut RequestContext, route: route::Api) -> response::R
     }
 }
 
+/// oEmbed response, see <https://oembed.com/>.
 #[derive(Default, Serialize)]
 struct Oembed<'a> {
+    #[serde(rename = "type")]
+    ty: &'a str,
+    version: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<Cow<'a, str>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     author_name: Option<Cow<'a, str>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     author_url: Option<Cow<'a, str>>,
     provider_name: &'a str,
     provider_url: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<u32>,
 }
 
+/// Default size of the embedded build.
+const OEMBED_SIZE: (u32, u32) = (600, 400);
+
 #[tracing::instrument(skip(rctx))]
 async fn handle_oembed(rctx: &RequestContext) -> Result<Response> {
+    let url = rctx.url()?;
+    let query = |name: &str| {
+        url.query_pairs()
+            .find_map(|(k, v)| (k == name).then_some(v))
+    };
+
+    let provider_url = format!("https://{}", url.host_str().unwrap());
     let mut oembed = Oembed {
+        ty: "link",
+        version: "1.0",
         provider_name: "Paste of Exile - POBb.in",
-        provider_url: &format!("https://{}", rctx.url()?.host_str().unwrap()),
+        provider_url: &provider_url,
         ..Default::default()
     };
 
-    let url = rctx.url()?;
-    if let Some(author) = url
-        .query_pairs()
-        .find_map(|(k, v)| (k == "user").then_some(v))
-    {
-        oembed.author_url = Some(format!("{}/u/{author}", oembed.provider_url).into());
-        oembed.author_name = Some(author);
+    let Some(paste_url) = query("url") else {
+        if let Some(author) = query("user") {
+            oembed.author_url = Some(format!("{provider_url}/u/{author}").into());
+            oembed.author_name = Some(author);
+        }
+
+        return Ok(Response::ok()
+            .json(&oembed)
+            .cache_for(Duration::from_secs(12 * 3600)));
+    };
+
+    let not_found = || Error::NotFound("paste", paste_url.to_string());
+    let id = url::Url::parse(&paste_url)
+        .ok()
+        .filter(|paste_url| paste_url.host_str() == url.host_str())
+        .and_then(|paste_url| route::paste_from_path(paste_url.path()))
+        .ok_or_else(not_found)?;
+
+    let pastes = rctx.inject::<crate::pastes::Pastes>();
+    let (_, paste) = pastes.get_paste(&id).await?.ok_or_else(not_found)?;
+    // Private pastes are never unfurled, the share token is not part of the request.
+    if paste.metadata.as_ref().is_some_and(|m| m.private) {
+        return Err(not_found());
+    }
+
+    let title = match paste.metadata {
+        Some(metadata) => metadata.title,
+        None => {
+            let pob = SerdePathOfBuilding::from_export(&paste.content)
+                .map_err(|e| Error::InvalidPoB(e, paste.content.clone()))?;
+            app::pob::title(&pob)
+        }
+    };
+
+    let size = |name: &str, default: u32| {
+        query(name)
+            .and_then(|v| v.parse::<u32>().ok())
+            .map_or(default, |max| max.min(default))
+    };
+    let width = size("maxwidth", OEMBED_SIZE.0);
+    let height = size("maxheight", OEMBED_SIZE.1);
+
+    oembed.ty = "rich";
+    oembed.title = Some(title.into());
+    if let Some(user) = id.user() {
+        oembed.author_name = Some(user.to_string().into());
+        oembed.author_url = Some(format!("{provider_url}{}", user.to_url()).into());
     }
+    oembed.thumbnail_url = Some(format!(
+        "{provider_url}{}",
+        id.to_url().join(app::pob::preview::PATH)
+    ));
+    oembed.thumbnail_width = Some(app::pob::preview::WIDTH);
+    oembed.thumbnail_height = Some(app::pob::preview::HEIGHT);
+    oembed.html = Some(format!(
+        r#"<iframe src="{provider_url}{}" width="{width}" height="{height}" frameborder="0" loading="lazy" title="Path of Building build"></iframe>"#,
+        id.to_embed_url()
+    ));
+    oembed.width = Some(width);
+    oembed.height = Some(height);
 
     Ok(Response::ok()
         .json(&oembed)
-        .cache_for(Duration::from_secs(12 * 3600)))
+        .cache_for(Duration::from_secs(3600)))
 }
 
 #[tracing::instrument(skip(rctx))]
 async fn handle_download_text(rctx: &RequestContext, id: PasteId) -> Result<Response> {
     let storage = rctx.inject::<crate::storage::Storage>();
-    let paste = storage
-        .get(&id)
-        .await?
-        .ok_or_else(|| Error::NotFound("paste", id.to_string()))?;
+    let Some(paste) = storage.get(&id).await? else {
+        return redirect_renamed(rctx, &id).await;
+    };
+    let access = crate::shares::check_access(rctx, &id, paste.metadata.as_ref()).await?;
 
+    let cache_control = crate::cache::paste_cache_control(paste.expires);
     Response::ok()
         .meta_paste(id, &paste)
         .body(paste.content)
         .content_type("text/plain")
         .etag(Etag::strong(&paste.entity_id))
-        .cache(
-            CacheControl::default()
-                .public()
-                .s_max_age(consts::CACHE_FOREVER),
-        )
+        .cache(cache_control)
+        .private(access.is_private())
         .result()
 }
 
 #[tracing::instrument(skip(rctx))]
 async fn handle_download_json(rctx: &RequestContext, id: PasteId) -> Result<Response> {
     let pastes = rctx.inject::<crate::pastes::Pastes>();
-    let (meta, paste) = pastes
-        .get_paste(&id)
-        .await?
-        .ok_or_else(|| Error::NotFound("paste", id.to_string()))?;
+    let Some((meta, paste)) = pastes.get_paste(&id).await? else {
+        return redirect_renamed(rctx, &id).await;
+    };
+    let access = crate::shares::check_access(rctx, &id, paste.metadata.as_ref()).await?;
 
     Response::ok()
         .json(&paste)
         .meta_paste(id, paste)
         .content_type("application/json")
         .etag(Etag::strong(&meta.etag))
-        .cache(
-            CacheControl::default()
-                .public()
-                .s_max_age(consts::CACHE_FOREVER),
-        )
+        .cache(crate::cache::paste_cache_control(meta.expires))
+        .private(access.is_private())
         .result()
 }
 
 #[tracing::instrument(skip(rctx))]
 async fn handle_download_xml(rctx: &RequestContext, id: PasteId) -> Result<Response> {
     let storage = rctx.inject::<crate::storage::Storage>();
-    let paste = storage
-        .get(&id)
-        .await?
-        .ok_or_else(|| Error::NotFound("paste", id.to_string()))?;
+    let Some(paste) = storage.get(&id).await? else {
+        return redirect_renamed(rctx, &id).await;
+    };
+    let access = crate::shares::check_access(rctx, &id, paste.metadata.as_ref()).await?;
 
     let content = pob::decompress(&paste.content).map_err(|e| Error::BadRequest(e.to_string()))?;
 
@@ -185,6 +328,158 @@ async fn handle_download_xml(rctx: &RequestContext, id: PasteId) -> Result<Respo
         .body(content)
         .content_type("application/xml")
         .etag(Etag::strong(&paste.entity_id))
+        .cache(crate::cache::paste_cache_control(paste.expires))
+        .private(access.is_private())
+        .result()
+}
+
+/// Renders a summary of the paste for forums and social platforms, see [`Markup`].
+#[tracing::instrument(skip(rctx))]
+async fn handle_download_markup(
+    rctx: &RequestContext,
+    id: PasteId,
+    markup: Markup,
+) -> Result<Response> {
+    let pastes = rctx.inject::<crate::pastes::Pastes>();
+    let Some((meta, paste)) = pastes.get_paste(&id).await? else {
+        return redirect_renamed(rctx, &id).await;
+    };
+    let access = crate::shares::check_access(rctx, &id, paste.metadata.as_ref()).await?;
+
+    let pob = SerdePathOfBuilding::from_export(&paste.content)
+        .map_err(|e| Error::InvalidPoB(e, paste.content.clone()))?;
+    let title = match paste.metadata {
+        Some(ref metadata) => Cow::Borrowed(metadata.title.as_str()),
+        None => Cow::Owned(app::pob::title(&pob)),
+    };
+
+    let mut url = rctx.url()?;
+    url.set_path(&id.to_url().into_cow());
+    url.set_query(None);
+    let url = crate::shares::with_share_token(rctx, url.as_str());
+
+    // Node data is in the same order as the tree specs.
+    let keystones = pob
+        .tree_specs()
+        .iter()
+        .position(|spec| spec.active)
+        .and_then(|index| paste.data.nodes.get(index))
+        .map(|nodes| nodes.keystones.as_slice())
+        .unwrap_or_default();
+
+    let content = app::pob::markup::render(markup, &title, &url, &pob, keystones);
+
+    Response::ok()
+        .body(content)
+        .meta_paste(id, &paste)
+        .content_type(markup.content_type())
+        .etag(Etag::strong(&meta.etag))
+        .cache(crate::cache::paste_cache_control(meta.expires))
+        .private(access.is_private())
+        .result()
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_build_summary(rctx: &RequestContext, id: PasteId) -> Result<Response> {
+    let pastes = rctx.inject::<crate::pastes::Pastes>();
+    let Some((meta, paste)) = pastes.get_paste(&id).await? else {
+        return redirect_renamed(rctx, &id).await;
+    };
+    let access = crate::shares::check_access(rctx, &id, paste.metadata.as_ref()).await?;
+
+    let pob = SerdePathOfBuilding::from_export(&paste.content)
+        .map_err(|e| Error::InvalidPoB(e, paste.content.clone()))?;
+    let title = match paste.metadata {
+        Some(ref metadata) => Cow::Borrowed(metadata.title.as_str()),
+        None => Cow::Owned(app::pob::title(&pob)),
+    };
+
+    // Node data is in the same order as the tree specs.
+    let nodes = pob
+        .tree_specs()
+        .iter()
+        .position(|spec| spec.active)
+        .and_then(|index| paste.data.nodes.get(index));
+
+    let summary = app::pob::build_summary::summarize(&title, &pob, nodes);
+
+    Response::ok()
+        .json(&summary)
+        .meta_paste(id, &paste)
+        .etag(Etag::strong(&meta.etag))
+        .cache(crate::cache::paste_cache_control(meta.expires))
+        .private(access.is_private())
+        .result()
+}
+
+async fn handle_preview(rctx: &RequestContext, id: PasteId) -> Result<Response> {
+    let pastes = rctx.inject::<crate::pastes::Pastes>();
+    let Some((meta, paste)) = pastes.get_paste(&id).await? else {
+        return redirect_renamed(rctx, &id).await;
+    };
+    let access = crate::shares::check_access(rctx, &id, paste.metadata.as_ref()).await?;
+
+    let pob = SerdePathOfBuilding::from_export(&paste.content)
+        .map_err(|e| Error::InvalidPoB(e, paste.content.clone()))?;
+    let title = match paste.metadata {
+        Some(ref metadata) => Cow::Borrowed(metadata.title.as_str()),
+        None => Cow::Owned(app::pob::title(&pob)),
+    };
+    let author = match &id {
+        PasteId::UserPaste(up) => Some(&*up.user),
+        PasteId::Paste(_) => None,
+    };
+
+    let content = crate::preview::render(rctx, &pob, &title, author).await?;
+
+    Response::ok()
+        .body(content)
+        .meta_paste(id, &paste)
+        .content_type(app::pob::preview::CONTENT_TYPE)
+        .etag(Etag::strong(&meta.etag))
+        .cache(crate::cache::paste_cache_control(meta.expires))
+        .private(access.is_private())
+        .result()
+}
+
+/// Permanently redirects a request for a renamed user paste to the new location of the paste.
+///
+/// Responds with not found if the paste was never renamed.
+async fn redirect_renamed(rctx: &RequestContext, id: &PasteId) -> Result<Response> {
+    let not_found = || Error::NotFound("paste", id.to_string());
+    let PasteId::UserPaste(up) = id else {
+        return Err(not_found());
+    };
+
+    let storage = rctx.inject::<crate::storage::Storage>();
+    let to = storage.get_redirect(up).await?.ok_or_else(not_found)?;
+
+    let location = match rctx.route() {
+        route::Route::Api(route::Api::Get(GetEndpoints::PobPaste(_))) => to.to_pob_load_url(),
+        route::Route::Api(route::Api::Get(GetEndpoints::PobUserPaste(..))) => {
+            to.to_pob_long_load_url()
+        }
+        route::Route::Api(route::Api::Get(GetEndpoints::UserPasteJson(..))) => to.to_json_url(),
+        route::Route::Api(route::Api::Get(GetEndpoints::UserPasteXml(..))) => {
+            to.to_paste_url().join("xml")
+        }
+        route::Route::Api(route::Api::Get(GetEndpoints::UserPasteMarkdown(..))) => {
+            to.to_paste_url().join(Markup::Markdown.extension())
+        }
+        route::Route::Api(route::Api::Get(GetEndpoints::UserPasteBbCode(..))) => {
+            to.to_paste_url().join(Markup::BbCode.extension())
+        }
+        route::Route::Api(route::Api::Get(GetEndpoints::UserPasteBuildSummary(..))) => {
+            to.to_paste_url().join("summary.json")
+        }
+        route::Route::Api(route::Api::Get(GetEndpoints::UserPastePreview(..))) => {
+            to.to_paste_url().join(app::pob::preview::PATH)
+        }
+        _ => to.to_raw_url(),
+    };
+    tracing::info!("redirecting renamed paste {up} to {to}");
+
+    Response::redirect_perm(&crate::shares::with_share_token(rctx, &location.into_cow()))
         .cache(
             CacheControl::default()
                 .public()
@@ -194,16 +489,174 @@ async fn handle_download_xml(rctx: &RequestContext, id: PasteId) -> Result<Respo
 }
 
 #[tracing::instrument(skip(rctx))]
-async fn handle_delete_paste(rctx: &RequestContext, id: PasteId) -> Result<Response> {
+async fn handle_paste_revisions(rctx: &RequestContext, id: PasteId) -> Result<Response> {
+    let PasteId::UserPaste(id) = id else {
+        return Err(Error::NotFound("paste", id.to_string()));
+    };
+
+    let access = crate::shares::check_revision_access(rctx, &id).await?;
+    let pastes = rctx.inject::<crate::pastes::Pastes>();
+    let revisions = pastes.list_revisions(&id).await?;
+
+    Ok(Response::ok()
+        .json(&revisions)
+        .meta_paste(id, ())
+        .private(access.is_private()))
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_download_revision_text(
+    rctx: &RequestContext,
+    id: UserPasteId,
+    revision: u64,
+) -> Result<Response> {
+    let access = crate::shares::check_revision_access(rctx, &id).await?;
+    let storage = rctx.inject::<crate::storage::Storage>();
+    let paste = storage
+        .get_revision(&id, revision)
+        .await?
+        .ok_or_else(|| Error::NotFound("revision", format!("{id}@{revision}")))?;
+
+    let cache_control = crate::cache::paste_cache_control(paste.expires);
+    Response::ok()
+        .meta_paste(id, &paste)
+        .body(paste.content)
+        .content_type("text/plain")
+        .etag(Etag::strong(&paste.entity_id))
+        .cache(cache_control)
+        .private(access.is_private())
+        .result()
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_download_revision_json(
+    rctx: &RequestContext,
+    id: UserPasteId,
+    revision: u64,
+) -> Result<Response> {
+    let access = crate::shares::check_revision_access(rctx, &id).await?;
+    let pastes = rctx.inject::<crate::pastes::Pastes>();
+    let (meta, paste) = pastes
+        .get_revision(&id, revision)
+        .await?
+        .ok_or_else(|| Error::NotFound("revision", format!("{id}@{revision}")))?;
+
+    Response::ok()
+        .json(&paste)
+        .meta_paste(id, paste)
+        .content_type("application/json")
+        .etag(Etag::strong(&meta.etag))
+        .cache(crate::cache::paste_cache_control(meta.expires))
+        .private(access.is_private())
+        .result()
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_restore_paste_revision(
+    rctx: &RequestContext,
+    id: PasteId,
+    revision: u64,
+) -> Result<Response> {
     let session = rctx.session().ok_or(Error::AccessDenied)?;
     validate_access!(Some(session.name.as_str()) == id.user().map(|user| user.as_str()));
+    let PasteId::UserPaste(up) = id else {
+        return Err(Error::AccessDenied);
+    };
 
+    tracing::info!("restoring revision {revision} of paste '{up}'");
     let storage = rctx.inject::<crate::storage::Storage>();
-    storage.delete(&id).await?;
+    let (paste, pruned) = storage
+        .restore_revision(&up, revision)
+        .await?
+        .ok_or_else(|| Error::NotFound("revision", format!("{up}@{revision}")))?;
+    crate::cache::on_revisions_delete(rctx, up.clone(), pruned);
+
+    let sha1 = crypto::sha1(paste.content.as_bytes()).await?;
+    let id = PasteId::UserPaste(up);
+    storage
+        .put(
+            &id,
+            &sha1,
+            paste.content.as_bytes(),
+            paste.metadata.as_ref(),
+        )
+        .await?;
+
+    if let (Some(metadata), Ok(pob)) = (
+        &paste.metadata,
+        SerdePathOfBuilding::from_export(&paste.content),
+    ) {
+        crate::search::on_paste_change(rctx, id.clone(), metadata, &pob);
+    }
+    crate::webhooks::on_paste_change(
+        rctx,
+        crate::webhooks::Event::Updated,
+        id.clone(),
+        paste.metadata.as_ref(),
+    );
+
+    let response = Response::ok().json(&id).meta_paste(&id, &paste);
+
     crate::cache::on_paste_change(rctx, id);
+
+    Ok(response)
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_delete_paste(rctx: &RequestContext, id: PasteId) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    validate_access!(Some(session.name.as_str()) == id.user().map(|user| user.as_str()));
+
+    let storage = rctx.inject::<crate::storage::Storage>();
+    let metadata = get_removed_metadata(&storage, &id).await;
+    let revisions = storage.delete(&id).await?;
+    on_paste_removed(rctx, id, metadata, revisions).await?;
+
     Ok(Response::ok())
 }
 
+/// Metadata of a paste which is about to be removed, webhooks still want to know what was removed.
+async fn get_removed_metadata(
+    storage: &crate::storage::Storage,
+    id: &PasteId,
+) -> Option<PasteMetadata> {
+    storage
+        .get(id)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|paste| paste.metadata)
+}
+
+/// Cleans up everything referencing a deleted or taken down paste and its deleted revisions.
+async fn on_paste_removed(
+    rctx: &RequestContext,
+    id: PasteId,
+    metadata: Option<PasteMetadata>,
+    revisions: Vec<u64>,
+) -> Result<()> {
+    if let PasteId::UserPaste(ref up) = id {
+        rctx.inject::<crate::shares::Shares>().revoke(up).await?;
+        crate::cache::on_revisions_delete(rctx, up.clone(), revisions);
+
+        let collections = rctx.inject::<crate::collections::Collections>();
+        for collection in collections.remove_paste(&up.user, &up.id).await? {
+            crate::cache::on_collection_change(rctx, up.user.clone(), collection);
+        }
+    }
+
+    crate::search::on_paste_delete(rctx, id.clone());
+    crate::webhooks::on_paste_change(
+        rctx,
+        crate::webhooks::Event::Deleted,
+        id.clone(),
+        metadata.as_ref(),
+    );
+    crate::cache::on_paste_change(rctx, id);
+
+    Ok(())
+}
+
 #[derive(Deserialize)]
 struct UploadRequest {
     /// Existing id to update a paste.
@@ -228,6 +681,17 @@ struct UploadRequest {
     #[serde(default)]
     private: bool,
 
+    /// User defined tags, currently only supported for user pastes.
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Short notes, currently only supported for user pastes.
+    #[serde(default)]
+    description: String,
+
+    /// Seconds after which an anonymous paste expires, user pastes never expire.
+    #[serde(default)]
+    expires_in: Option<u64>,
+
     content: String,
 }
 
@@ -236,7 +700,7 @@ async fn handle_upload(rctx: &mut RequestContext) -> Result<Response> {
     let data = rctx.req_mut().json::<UploadRequest>().await?;
     let content: Rc<[u8]> = data.content.into_bytes().into();
 
-    tracing::info!(?data.id, data.as_user, ?data.title, ?data.custom_id, size = content.len(), "upload");
+    tracing::info!(?data.id, data.as_user, ?data.title, ?data.custom_id, ?data.expires_in, size = content.len(), "upload");
     sentry::add_attachment_plain(content.clone(), "pob.txt");
 
     let pob = validate_pob(rctx.is_logged_in(), &content)?;
@@ -244,19 +708,34 @@ async fn handle_upload(rctx: &mut RequestContext) -> Result<Response> {
 
     let sha1 = crypto::sha1(&content).await?;
 
+    let expires = to_expires(data.expires_in)?;
+
+    let event = match data.id {
+        Some(_) => crate::webhooks::Event::Updated,
+        None => crate::webhooks::Event::Created,
+    };
+
     let id = if data.as_user {
         let session = rctx.session().ok_or_else(|| {
             tracing::warn!("missing user session");
             Error::AccessDenied
         })?;
 
+        validate!(expires.is_none(), "User pastes cannot expire");
         validate!(data.title.is_some(), "Title is required");
         let title = data.title.unwrap();
         validate_v!(validation::user::is_valid_custom_title(&title));
 
+        let tags = validation::user::normalize_tags(data.tags.iter().map(String::as_str));
+        validate_v!(validation::user::is_valid_tags(&tags));
+        let description = data.description.trim();
+        validate_v!(validation::user::is_valid_description(description));
+
         metadata.title = title;
         metadata.rank = if data.pinned { NonZeroU8::new(1) } else { None };
         metadata.private = data.private;
+        metadata.tags = tags;
+        metadata.description = description.to_owned();
 
         if let Some(id) = data.id {
             validate_access!(Some(session.name.as_str()) == id.user().map(|user| user.as_str()));
@@ -292,9 +771,37 @@ async fn handle_upload(rctx: &mut RequestContext) -> Result<Response> {
 
     tracing::debug!("--> uploading paste '{}'", id);
     let storage = rctx.inject::<crate::storage::Storage>();
-    storage.put(&id, &sha1, &content, Some(&metadata)).await?;
+    if let PasteId::Paste(_) = id {
+        // Anonymous ids are derived from the content, a removed paste must not be re-uploaded.
+        if storage.is_removed(&id).await? {
+            return Err(Error::Gone("paste", id.to_string()));
+        }
+    }
+    if let PasteId::UserPaste(ref up) = id {
+        if let Some(current) = storage.get(&id).await? {
+            // The origin of a forked paste is not part of the upload, keep it when editing.
+            metadata.forked_from = current
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.forked_from.clone());
+            // Keep the previous version around, when overwriting an existing paste.
+            let pruned = storage.archive(up, &current).await?;
+            crate::cache::on_revisions_delete(rctx, up.clone(), pruned);
+        }
+    }
+    match expires {
+        Some(expires) => {
+            storage
+                .put_expiring(&id, &sha1, &content, Some(&metadata), expires)
+                .await?
+        }
+        None => storage.put(&id, &sha1, &content, Some(&metadata)).await?,
+    }
     tracing::debug!("<-- paste uploaded");
 
+    crate::search::on_paste_change(rctx, id.clone(), &metadata, &pob);
+    crate::webhooks::on_paste_change(rctx, event, id.clone(), Some(&metadata));
+
     let response = Response::ok().json(&id).meta_paste(&id, metadata);
 
     crate::cache::on_paste_change(rctx, id);
@@ -302,55 +809,438 @@ async fn handle_upload(rctx: &mut RequestContext) -> Result<Response> {
     Ok(response)
 }
 
-#[tracing::instrument(skip(rctx))]
-async fn handle_pob_upload(rctx: &mut RequestContext) -> Result<Response> {
-    let data: Rc<[u8]> = rctx.req_mut().bytes().await?.into();
+#[derive(Debug, Deserialize)]
+struct ForkRequest {
+    /// Custom id for the forked paste, a random id is generated if missing.
+    #[serde(default)]
+    custom_id: Option<String>,
+    /// Custom title for the forked paste, defaults to the title of the source paste.
+    #[serde(default)]
+    title: Option<String>,
+}
 
-    tracing::info!(size = data.len(), "pob upload");
-    sentry::add_attachment_plain(data.clone(), "pob.txt");
+#[tracing::instrument(skip(rctx))]
+async fn handle_fork_paste(rctx: &mut RequestContext, source: PasteId) -> Result<Response> {
+    let data = rctx.req_mut().json::<ForkRequest>().await?;
 
-    let pob = validate_pob(rctx.is_logged_in(), &data)?;
-    let metadata = to_metadata(&pob);
+    let session = rctx.session().ok_or_else(|| {
+        tracing::warn!("missing user session");
+        Error::AccessDenied
+    })?;
 
-    let sha1 = crypto::sha1(&data).await?;
-    let id = PasteId::Paste(utils::hash_to_short_id(&sha1));
+    let is_owner = Some(session.name.as_str()) == source.user().map(|user| user.as_str());
 
-    tracing::debug!("--> uploading paste '{}'", id);
     let storage = rctx.inject::<crate::storage::Storage>();
-    storage.put(&id, &sha1, &data, Some(&metadata)).await?;
-    tracing::debug!("<-- paste uploaing ...");
+    let paste = storage
+        .get(&source)
+        .await?
+        .filter(|paste| {
+            let is_private = paste.metadata.as_ref().is_some_and(|m| m.private);
+            !is_private || is_owner
+        })
+        .ok_or_else(|| Error::NotFound("paste", source.to_string()))?;
 
-    let response = Response::ok()
-        .body(id.to_string())
-        .meta_paste(&id, metadata);
+    tracing::info!(%source, ?data.custom_id, ?data.title, "fork");
+
+    let pob = validate_pob(true, paste.content.as_bytes())?;
+    let mut metadata = to_metadata(&pob);
+
+    if let Some(m) = &paste.metadata {
+        metadata.tags = m.tags.clone();
+        metadata.description = m.description.clone();
+        // Forking your own private paste must not publish the copy.
+        metadata.private = m.private && is_owner;
+    }
+    let title = data
+        .title
+        .or_else(|| paste.metadata.map(|m| m.title))
+        .unwrap_or_else(|| metadata.title.clone());
+    validate_v!(validation::user::is_valid_custom_title(&title));
+    metadata.title = title;
+    metadata.forked_from = Some(source);
+
+    let id = match data.custom_id {
+        Some(id) => id,
+        None => utils::random_string::<9>()?,
+    };
+    validate_v!(validation::user::is_valid_custom_id(&id));
+    let id: PasteId = UserPasteId {
+        user: session.name.clone(),
+        id: id.try_into()?,
+    }
+    .into();
+
+    // Forking never overwrites an existing paste.
+    validate!(storage.get(&id).await?.is_none(), "Id already in use");
+
+    let sha1 = crypto::sha1(paste.content.as_bytes()).await?;
+
+    tracing::debug!("--> uploading forked paste '{}'", id);
+    storage
+        .put(&id, &sha1, paste.content.as_bytes(), Some(&metadata))
+        .await?;
+    tracing::debug!("<-- forked paste uploaded");
+
+    crate::search::on_paste_change(rctx, id.clone(), &metadata, &pob);
+    crate::webhooks::on_paste_change(
+        rctx,
+        crate::webhooks::Event::Created,
+        id.clone(),
+        Some(&metadata),
+    );
+
+    let response = Response::ok().json(&id).meta_paste(&id, metadata);
 
     crate::cache::on_paste_change(rctx, id);
 
     Ok(response)
 }
 
-fn validate_pob(is_logged_in: bool, data: &[u8]) -> Result<SerdePathOfBuilding> {
-    let limit = if is_logged_in {
-        consts::MAX_UPLOAD_SIZE_LOGGED_IN
-    } else {
-        consts::MAX_UPLOAD_SIZE
-    };
+#[derive(Debug, Deserialize)]
+struct RenameRequest {
+    /// New custom id of the paste.
+    id: String,
+}
 
-    sentry::distribution(Distributions::PobSize, data.len() as f64).unit(MetricUnit::Byte);
+/// Moves a user paste to a new id, requests for the old id are permanently redirected.
+#[tracing::instrument(skip(rctx))]
+async fn handle_rename_paste(rctx: &mut RequestContext, id: PasteId) -> Result<Response> {
+    let data = rctx.req_mut().json::<RenameRequest>().await?;
 
-    if data.len() > limit {
-        return Err(Error::BadRequest(
-            "Paste too large, please login and use the website".to_owned(),
-        ));
-    }
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    validate_access!(Some(session.name.as_str()) == id.user().map(|user| user.as_str()));
+    let PasteId::UserPaste(from) = id else {
+        return Err(Error::AccessDenied);
+    };
 
-    let s = std::str::from_utf8(data)
-        .map_err(|_| "invalid content".to_owned())
-        .map_err(Error::BadRequest)?;
+    validate_v!(validation::user::is_valid_custom_id(&data.id));
+    let to = UserPasteId {
+        user: from.user.clone(),
+        id: data.id.try_into()?,
+    };
+    validate!(from != to, "Paste already has this id");
 
-    // Generic 401, probably just actually bad data
-    let s = pob::decompress(s).map_err(|e| Error::BadRequest(e.to_string()))?;
-    // More specific error for a separate Sentry categoy
+    let storage = rctx.inject::<crate::storage::Storage>();
+    let paste = storage
+        .get(&from.clone().into())
+        .await?
+        .ok_or_else(|| Error::NotFound("paste", from.to_string()))?;
+    validate!(
+        is_free(&storage, &to.user, &to.id).await?,
+        "Id already in use"
+    );
+
+    tracing::info!(%from, %to, "rename");
+    let revisions = storage.rename(&from, &to).await?;
+    crate::cache::on_revisions_delete(rctx, from.clone(), revisions);
+    rctx.inject::<crate::shares::Shares>()
+        .rename(&from, &to)
+        .await?;
+
+    let collections = rctx.inject::<crate::collections::Collections>();
+    for collection in collections
+        .rename_paste(rctx.session(), &from.user, &from.id, &to.id)
+        .await?
+    {
+        crate::cache::on_collection_change(rctx, from.user.clone(), collection);
+    }
+
+    let (from, to) = (PasteId::UserPaste(from), PasteId::UserPaste(to));
+    crate::search::on_paste_delete(rctx, from.clone());
+    if let (Some(metadata), Ok(pob)) = (
+        &paste.metadata,
+        SerdePathOfBuilding::from_export(&paste.content),
+    ) {
+        crate::search::on_paste_change(rctx, to.clone(), metadata, &pob);
+    }
+    crate::webhooks::on_paste_change(
+        rctx,
+        crate::webhooks::Event::Deleted,
+        from.clone(),
+        paste.metadata.as_ref(),
+    );
+    crate::webhooks::on_paste_change(
+        rctx,
+        crate::webhooks::Event::Created,
+        to.clone(),
+        paste.metadata.as_ref(),
+    );
+
+    let response = Response::ok().json(&to).meta_paste(&to, &paste);
+
+    crate::cache::on_paste_change(rctx, from);
+    crate::cache::on_paste_change(rctx, to);
+
+    Ok(response)
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_export(rctx: &RequestContext) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    tracing::info!(user = %session.name, "export");
+    let storage = rctx.inject::<crate::storage::Storage>();
+    crate::export::export(storage, session.name.clone()).await
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ImportReport {
+    imported: Vec<ImportedPaste>,
+    failed: Vec<FailedImport>,
+}
+
+#[derive(Debug, Serialize)]
+struct ImportedPaste {
+    /// Id of the paste in the archive.
+    from: String,
+    id: PasteId,
+}
+
+#[derive(Debug, Serialize)]
+struct FailedImport {
+    id: String,
+    reason: String,
+}
+
+/// Recreates pastes from an archive created by [`handle_export`].
+///
+/// Ids are kept when they are still free, otherwise a new random id is assigned.
+/// Existing pastes are never overwritten.
+#[tracing::instrument(skip(rctx))]
+async fn handle_import(rctx: &mut RequestContext) -> Result<Response> {
+    let data = rctx.req_mut().bytes().await?;
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    validate!(data.len() <= consts::MAX_IMPORT_SIZE, "Archive too large");
+    let pastes = crate::export::read_import(&data)?;
+    validate!(
+        pastes.len() <= consts::MAX_IMPORT_PASTES,
+        format!(
+            "Too many pastes, at most {} pastes can be imported at once",
+            consts::MAX_IMPORT_PASTES
+        )
+    );
+
+    tracing::info!(user = %session.name, pastes = pastes.len(), "import");
+
+    let mut report = ImportReport::default();
+    for paste in pastes {
+        let from = paste.id.clone();
+        match import_paste(rctx, &session.name, paste).await {
+            Ok(id) => report.imported.push(ImportedPaste { from, id }),
+            Err(err) => {
+                tracing::warn!("failed to import paste '{from}': {err}");
+                report.failed.push(FailedImport {
+                    id: from,
+                    reason: err.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(Response::ok().json(&report))
+}
+
+async fn import_paste(
+    rctx: &RequestContext,
+    user: &User,
+    paste: crate::export::ImportedPaste,
+) -> Result<PasteId> {
+    let pob = validate_pob(true, paste.content.as_bytes())?;
+    let mut metadata = to_metadata(&pob);
+
+    validate_v!(validation::user::is_valid_custom_title(
+        &paste.metadata.title
+    ));
+    let tags = validation::user::normalize_tags(paste.metadata.tags.iter().map(String::as_str));
+    validate_v!(validation::user::is_valid_tags(&tags));
+    validate_v!(validation::user::is_valid_description(
+        &paste.metadata.description
+    ));
+    metadata.title = paste.metadata.title;
+    metadata.rank = paste.metadata.rank;
+    metadata.private = paste.metadata.private;
+    metadata.forked_from = paste.metadata.forked_from;
+    metadata.tags = tags;
+    metadata.description = paste.metadata.description;
+
+    let storage = rctx.inject::<crate::storage::Storage>();
+
+    let custom_id = validation::user::is_valid_custom_id(&paste.id)
+        .is_valid()
+        .then(|| paste.id.parse::<shared::Id>())
+        .transpose()?;
+    let id = match custom_id {
+        Some(id) if is_free(&storage, user, &id).await? => id,
+        _ => utils::random_string::<9>()?.try_into()?,
+    };
+    let id = PasteId::UserPaste(UserPasteId {
+        user: user.clone(),
+        id,
+    });
+
+    let sha1 = crypto::sha1(paste.content.as_bytes()).await?;
+
+    tracing::debug!("--> importing paste '{}'", id);
+    storage
+        .put(&id, &sha1, paste.content.as_bytes(), Some(&metadata))
+        .await?;
+    tracing::debug!("<-- paste imported");
+
+    crate::search::on_paste_change(rctx, id.clone(), &metadata, &pob);
+    crate::webhooks::on_paste_change(
+        rctx,
+        crate::webhooks::Event::Created,
+        id.clone(),
+        Some(&metadata),
+    );
+    crate::cache::on_paste_change(rctx, id.clone());
+
+    Ok(id)
+}
+
+/// Whether a user paste id is neither in use nor was taken down.
+async fn is_free(storage: &crate::storage::Storage, user: &User, id: &shared::Id) -> Result<bool> {
+    let id = PasteId::UserPaste(UserPasteId {
+        user: user.clone(),
+        id: id.clone(),
+    });
+
+    match storage.get(&id).await {
+        Ok(paste) => Ok(paste.is_none()),
+        Err(Error::Gone(..)) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_pob_upload(rctx: &mut RequestContext) -> Result<Response> {
+    let data: Rc<[u8]> = rctx.req_mut().bytes().await?.into();
+
+    tracing::info!(size = data.len(), "pob upload");
+    sentry::add_attachment_plain(data.clone(), "pob.txt");
+
+    let pob = validate_pob(rctx.is_logged_in(), &data)?;
+    let metadata = to_metadata(&pob);
+
+    let expires_in = rctx
+        .url()?
+        .query_pairs()
+        .find_map(|(k, v)| (k == "expires_in").then_some(v))
+        .map(|v| v.parse::<u64>())
+        .transpose()
+        .map_err(|_| Error::BadRequest("Invalid expires_in".to_owned()))?;
+    let expires = to_expires(expires_in)?;
+
+    let sha1 = crypto::sha1(&data).await?;
+    let id = PasteId::Paste(utils::hash_to_short_id(&sha1));
+
+    tracing::debug!("--> uploading paste '{}'", id);
+    let storage = rctx.inject::<crate::storage::Storage>();
+    if storage.is_removed(&id).await? {
+        return Err(Error::Gone("paste", id.to_string()));
+    }
+    match expires {
+        Some(expires) => {
+            storage
+                .put_expiring(&id, &sha1, &data, Some(&metadata), expires)
+                .await?
+        }
+        None => storage.put(&id, &sha1, &data, Some(&metadata)).await?,
+    }
+    tracing::debug!("<-- paste uploaing ...");
+
+    let response = Response::ok()
+        .body(id.to_string())
+        .meta_paste(&id, metadata);
+
+    crate::cache::on_paste_change(rctx, id);
+
+    Ok(response)
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportUrlRequest {
+    /// URL of a build shared on a supported site, see [`crate::importers`].
+    url: String,
+}
+
+/// Imports a build from another build sharing site as an anonymous paste.
+#[tracing::instrument(skip(rctx))]
+async fn handle_import_url(rctx: &mut RequestContext) -> Result<Response> {
+    let data = rctx.req_mut().json::<ImportUrlRequest>().await?;
+
+    let import = rctx
+        .inject::<crate::importers::Importer>()
+        .import(&data.url)
+        .await?;
+    let content: Rc<[u8]> = import.content.into_bytes().into();
+
+    tracing::info!(source = import.source, size = content.len(), "import url");
+    sentry::add_attachment_plain(content.clone(), "pob.txt");
+
+    let pob = validate_pob(rctx.is_logged_in(), &content)?;
+    let metadata = to_metadata(&pob);
+
+    let sha1 = crypto::sha1(&content).await?;
+    let id = PasteId::Paste(utils::hash_to_short_id(&sha1));
+
+    let storage = rctx.inject::<crate::storage::Storage>();
+    if storage.is_removed(&id).await? {
+        return Err(Error::Gone("paste", id.to_string()));
+    }
+    storage.put(&id, &sha1, &content, Some(&metadata)).await?;
+
+    let response = Response::ok().json(&id).meta_paste(&id, metadata);
+
+    crate::cache::on_paste_change(rctx, id);
+
+    Ok(response)
+}
+
+/// Validates the requested expiry of an anonymous paste
+/// and converts it to an expiry timestamp in milliseconds.
+fn to_expires(expires_in: Option<u64>) -> Result<Option<u64>> {
+    let Some(expires_in) = expires_in.map(Duration::from_secs) else {
+        return Ok(None);
+    };
+
+    validate!(
+        expires_in >= consts::MIN_PASTE_EXPIRES_IN && expires_in <= consts::MAX_PASTE_EXPIRES_IN,
+        format!(
+            "Expiry must be between {} and {} seconds",
+            consts::MIN_PASTE_EXPIRES_IN.as_secs(),
+            consts::MAX_PASTE_EXPIRES_IN.as_secs()
+        )
+    );
+
+    Ok(Some(
+        worker::Date::now().as_millis() + expires_in.as_millis() as u64,
+    ))
+}
+
+fn validate_pob(is_logged_in: bool, data: &[u8]) -> Result<SerdePathOfBuilding> {
+    let limit = if is_logged_in {
+        consts::MAX_UPLOAD_SIZE_LOGGED_IN
+    } else {
+        consts::MAX_UPLOAD_SIZE
+    };
+
+    sentry::distribution(Distributions::PobSize, data.len() as f64).unit(MetricUnit::Byte);
+
+    if data.len() > limit {
+        return Err(Error::BadRequest(
+            "Paste too large, please login and use the website".to_owned(),
+        ));
+    }
+
+    let s = std::str::from_utf8(data)
+        .map_err(|_| "invalid content".to_owned())
+        .map_err(Error::BadRequest)?;
+
+    // Generic 401, probably just actually bad data
+    let s = pob::decompress(s).map_err(|e| Error::BadRequest(e.to_string()))?;
+    // More specific error for a separate Sentry categoy
     let pob = SerdePathOfBuilding::from_xml(&s).map_err(move |e| Error::InvalidPoB(e, s))?;
 
     sentry::counter(Counters::PobUpload)
@@ -364,53 +1254,494 @@ fn validate_pob(is_logged_in: bool, data: &[u8]) -> Result<SerdePathOfBuilding>
     Ok(pob)
 }
 
-fn to_metadata(pob: &SerdePathOfBuilding) -> PasteMetadata {
-    PasteMetadata {
-        title: app::pob::title(pob),
-        game_version: pob.game_version(),
-        ascendancy_or_class: pob.ascendancy_or_class().to_owned(),
-        version: pob.max_tree_version(),
-        main_skill_name: pob.main_skill_name().map(|x| x.to_owned()),
-        rank: None,
-        private: false,
-    }
-}
-
 #[tracing::instrument(skip(rctx))]
 async fn handle_user(rctx: &RequestContext, user: User) -> Result<Response> {
+    let query = ListPastesQuery::from_query_string(rctx.url()?.query().unwrap_or_default());
+
     let pastes = rctx.inject::<crate::pastes::Pastes>();
     let session = rctx.session();
-    let (meta, pastes) = pastes.list_pastes(session, &user).await?;
+    let (meta, page) = pastes.list_pastes(session, &user, &query).await?;
 
     Response::ok()
-        .json(&pastes)
+        .json(&page)
         .meta_list(user)
         .etag(Etag::strong(&meta.etag))
+        .cache(crate::cache::list_cache_control(&query))
+        .result()
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_user_collections(rctx: &RequestContext, user: User) -> Result<Response> {
+    let collections = rctx
+        .inject::<crate::collections::Collections>()
+        .list(rctx.session(), &user)
+        .await?;
+
+    Response::ok()
+        .json(&collections)
+        .cache(crate::cache::collection_cache_control())
+        .result()
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_user_collection(
+    rctx: &RequestContext,
+    user: User,
+    id: shared::Id,
+) -> Result<Response> {
+    let page = rctx
+        .inject::<crate::collections::Collections>()
+        .page(rctx.session(), &user, &id)
+        .await?
+        .ok_or_else(|| Error::NotFound("collection", format!("{user}:{id}")))?;
+
+    Response::ok()
+        .json(&page)
+        .cache(crate::cache::collection_cache_control())
+        .result()
+}
+
+#[derive(Debug, Deserialize)]
+struct SaveCollectionRequest {
+    /// Id of the collection, a new collection with a random id is created if missing.
+    #[serde(default)]
+    id: Option<String>,
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    private: bool,
+    #[serde(default)]
+    pastes: Vec<shared::Id>,
+}
+
+/// Creates a new or replaces an existing collection of the current user.
+#[tracing::instrument(skip(rctx))]
+async fn handle_save_collection(rctx: &mut RequestContext) -> Result<Response> {
+    let mut data = rctx.req_mut().json::<SaveCollectionRequest>().await?;
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    let title = data.title.trim().to_owned();
+    validate_v!(validation::user::is_valid_custom_title(&title));
+    let description = data.description.trim().to_owned();
+    validate!(
+        description.chars().count() <= consts::MAX_COLLECTION_DESCRIPTION_LENGTH,
+        "Description is too long"
+    );
+    validate!(
+        data.pastes.len() <= consts::MAX_COLLECTION_PASTES,
+        format!(
+            "Too many builds, at most {} builds are allowed",
+            consts::MAX_COLLECTION_PASTES
+        )
+    );
+    // Keep the first occurrence of a paste, a paste can only be in a collection once.
+    let mut seen = std::collections::BTreeSet::new();
+    data.pastes.retain(|id| seen.insert(id.clone()));
+
+    let owned = rctx
+        .inject::<crate::storage::Storage>()
+        .list(&session.name)
+        .await?
+        .into_iter()
+        .map(|paste| paste.name)
+        .collect::<std::collections::HashSet<_>>();
+    if let Some(id) = data.pastes.iter().find(|id| !owned.contains(id.as_str())) {
+        return Err(Error::BadRequest(format!("Unknown build {id}")));
+    }
+
+    let collections = rctx.inject::<crate::collections::Collections>();
+    let id: shared::Id = match data.id {
+        Some(id) => {
+            validate_v!(validation::user::is_valid_custom_id(&id));
+            id.try_into()?
+        }
+        None => utils::random_string::<9>()?.try_into()?,
+    };
+
+    if collections.get(&session.name, &id).await?.is_none() {
+        let existing = collections.list(rctx.session(), &session.name).await?;
+        validate!(
+            existing.len() < consts::MAX_COLLECTIONS,
+            format!(
+                "Too many collections, at most {} collections are allowed",
+                consts::MAX_COLLECTIONS
+            )
+        );
+    }
+
+    let collection = shared::model::Collection {
+        id,
+        title,
+        description,
+        private: data.private,
+        pastes: data.pastes,
+        last_modified: worker::Date::now().as_millis(),
+    };
+
+    tracing::info!(id = %collection.id, pastes = collection.pastes.len(), "saving collection");
+    collections.put(&session.name, &collection).await?;
+
+    crate::cache::on_collection_change(rctx, session.name.clone(), collection.id.clone());
+
+    Ok(Response::ok().json(&collection))
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_delete_collection(rctx: &RequestContext, id: shared::Id) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    tracing::info!(%id, "deleting collection");
+    rctx.inject::<crate::collections::Collections>()
+        .delete(&session.name, &id)
+        .await?;
+
+    crate::cache::on_collection_change(rctx, session.name.clone(), id);
+
+    Ok(Response::ok())
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_search(rctx: &RequestContext) -> Result<Response> {
+    let query = crate::search::SearchQuery::from_url(&rctx.url()?);
+    tracing::info!(?query, "search");
+    sentry::counter(Counters::Search).inc(1);
+
+    let results = rctx
+        .inject::<crate::search::Search>()
+        .search(&query)
+        .await?;
+
+    Response::ok()
+        .json(&results)
         .cache(
             CacheControl::default()
                 .public()
-                .s_max_age(consts::CACHE_FOREVER),
+                .s_max_age(consts::CACHE_SEARCH),
         )
         .result()
 }
 
+#[tracing::instrument(skip(rctx))]
+async fn handle_api_tokens(rctx: &RequestContext) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    let tokens = rctx
+        .inject::<crate::tokens::Tokens>()
+        .list(&session.name)
+        .await?;
+
+    Ok(Response::ok().json(&tokens))
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateApiTokenRequest {
+    name: String,
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_create_api_token(rctx: &mut RequestContext) -> Result<Response> {
+    let data = rctx.req_mut().json::<CreateApiTokenRequest>().await?;
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    let name = data.name.trim().to_owned();
+    validate!(!name.is_empty(), "Name is required");
+    validate!(name.len() <= 50, "Name is too long");
+
+    tracing::info!(%name, "creating API token");
+    let (info, token) = rctx
+        .inject::<crate::tokens::Tokens>()
+        .create(&session.name, name)
+        .await?;
+
+    Ok(Response::ok().json(&shared::model::NewApiToken { info, token }))
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_revoke_api_token(rctx: &RequestContext, id: String) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    tracing::info!(%id, "revoking API token");
+    rctx.inject::<crate::tokens::Tokens>()
+        .revoke(&session.name, &id)
+        .await?;
+
+    Ok(Response::ok())
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_webhooks(rctx: &RequestContext) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    let webhooks = rctx
+        .inject::<crate::webhooks::Webhooks>()
+        .list(&session.name)
+        .await?;
+
+    Ok(Response::ok().json(&webhooks))
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateWebhookRequest {
+    url: String,
+    #[serde(default)]
+    format: shared::model::WebhookFormat,
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_create_webhook(rctx: &mut RequestContext) -> Result<Response> {
+    let data = rctx.req_mut().json::<CreateWebhookRequest>().await?;
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    let url = data.url.trim().to_owned();
+    validate!(
+        crate::webhooks::is_valid_url(&url),
+        "Invalid webhook URL, only https URLs are supported"
+    );
+
+    tracing::info!(?data.format, "creating webhook");
+    let (info, secret) = rctx
+        .inject::<crate::webhooks::Webhooks>()
+        .create(&session.name, url, data.format)
+        .await?;
+
+    Ok(Response::ok().json(&shared::model::NewWebhook { info, secret }))
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_delete_webhook(rctx: &RequestContext, id: String) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    tracing::info!(%id, "deleting webhook");
+    rctx.inject::<crate::webhooks::Webhooks>()
+        .delete(&session.name, &id)
+        .await?;
+
+    Ok(Response::ok())
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_paste_share(rctx: &RequestContext, id: PasteId) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    validate_access!(Some(session.name.as_str()) == id.user().map(|user| user.as_str()));
+    let PasteId::UserPaste(up) = id else {
+        return Err(Error::AccessDenied);
+    };
+
+    let link = rctx.inject::<crate::shares::Shares>().get(&up).await?;
+
+    Ok(Response::ok().json(&link).meta_paste(up, ()))
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_create_paste_share(rctx: &RequestContext, id: PasteId) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    validate_access!(Some(session.name.as_str()) == id.user().map(|user| user.as_str()));
+
+    let storage = rctx.inject::<crate::storage::Storage>();
+    if storage.get(&id).await?.is_none() {
+        return Err(Error::NotFound("paste", id.to_string()));
+    }
+    let PasteId::UserPaste(up) = id else {
+        return Err(Error::AccessDenied);
+    };
+
+    tracing::info!("creating share link for paste '{up}'");
+    let link = rctx.inject::<crate::shares::Shares>().create(&up).await?;
+
+    Ok(Response::ok().json(&link).meta_paste(up, ()))
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_revoke_paste_share(rctx: &RequestContext, id: PasteId) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    validate_access!(Some(session.name.as_str()) == id.user().map(|user| user.as_str()));
+    let PasteId::UserPaste(up) = id else {
+        return Err(Error::AccessDenied);
+    };
+
+    tracing::info!("revoking share link for paste '{up}'");
+    rctx.inject::<crate::shares::Shares>().revoke(&up).await?;
+
+    Ok(Response::ok().meta_paste(up, ()))
+}
+
+#[derive(Debug, Deserialize)]
+struct ModerationRequest {
+    reason: String,
+}
+
+fn validate_moderation_reason(reason: &str) -> Result<()> {
+    validate!(!reason.trim().is_empty(), "Reason is required");
+    validate!(
+        reason.chars().count() <= consts::MAX_MODERATION_REASON_LENGTH,
+        format!(
+            "Reason must not be longer than {} characters",
+            consts::MAX_MODERATION_REASON_LENGTH
+        )
+    );
+    Ok(())
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_report_paste(rctx: &mut RequestContext, id: PasteId) -> Result<Response> {
+    let data = rctx.req_mut().json::<ModerationRequest>().await?;
+    validate_moderation_reason(&data.reason)?;
+
+    let storage = rctx.inject::<crate::storage::Storage>();
+    if storage.get(&id).await?.is_none() {
+        return Err(Error::NotFound("paste", id.to_string()));
+    }
+
+    let reporter = rctx.session().map(|session| session.name.clone());
+    tracing::info!(%id, ?reporter, "reporting paste");
+    rctx.inject::<crate::moderation::Moderation>()
+        .report(id, data.reason.trim().to_owned(), reporter)
+        .await?;
+
+    Ok(Response::ok())
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_takedown_paste(rctx: &mut RequestContext, id: PasteId) -> Result<Response> {
+    let data = rctx.req_mut().json::<ModerationRequest>().await?;
+
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    let moderation = rctx.inject::<crate::moderation::Moderation>();
+    validate_access!(moderation.is_admin(&session.name));
+    validate_moderation_reason(&data.reason)?;
+
+    tracing::warn!(%id, admin = %session.name, reason = %data.reason, "taking down paste");
+    let tombstone = crate::storage::Tombstone {
+        reason: data.reason.trim().to_owned(),
+        removed_by: session.name.clone(),
+        removed_at: worker::Date::now().as_millis(),
+    };
+    let storage = rctx.inject::<crate::storage::Storage>();
+    let metadata = get_removed_metadata(&storage, &id).await;
+    let revisions = storage.takedown(&id, &tombstone).await?;
+    moderation.dismiss_paste(&id).await?;
+    on_paste_removed(rctx, id, metadata, revisions).await?;
+
+    Ok(Response::ok())
+}
+
+/// Runs a single batch of the paste lifecycle on demand, in dry-run mode
+/// unless `?dry_run=false` is given.
+///
+/// The batch starts at `?cursor=`, the cursor of the next batch is part of the report.
+#[tracing::instrument(skip(rctx))]
+async fn handle_run_lifecycle(rctx: &RequestContext) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    let moderation = rctx.inject::<crate::moderation::Moderation>();
+    validate_access!(moderation.is_admin(&session.name));
+
+    let url = rctx.url()?;
+    let dry_run = url
+        .query_pairs()
+        .find_map(|(k, v)| (k == "dry_run").then_some(v))
+        .is_none_or(|v| v != "false");
+    let cursor = url
+        .query_pairs()
+        .find_map(|(k, v)| (k == "cursor").then(|| v.into_owned()));
+
+    let mut options = crate::lifecycle::LifecycleOptions::from_env(rctx.env());
+    options.dry_run = dry_run;
+
+    tracing::info!(admin = %session.name, ?options, "running paste lifecycle");
+    let report = rctx
+        .inject::<crate::lifecycle::Lifecycle>()
+        .run(options, cursor, worker::Date::now().as_millis())
+        .await?;
+
+    Ok(Response::ok().json(&report))
+}
+
+/// Runs a single maintenance task on demand, e.g. to migrate metadata in bulk.
+///
+/// Batched tasks continue where the previous run stopped.
+#[tracing::instrument(skip(rctx))]
+async fn handle_run_maintenance(rctx: &RequestContext, task: String) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    let moderation = rctx.inject::<crate::moderation::Moderation>();
+    validate_access!(moderation.is_admin(&session.name));
+
+    let task = task
+        .parse::<crate::maintenance::Task>()
+        .map_err(Error::BadRequest)?;
+
+    tracing::info!(admin = %session.name, ?task, "running maintenance task");
+    let now = worker::Date::now().as_millis();
+    let report = crate::maintenance::run_task(rctx.env(), task, now).await?;
+
+    Ok(Response::ok().json(&report))
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_reports(rctx: &RequestContext) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    let moderation = rctx.inject::<crate::moderation::Moderation>();
+    validate_access!(moderation.is_admin(&session.name));
+
+    let reports = moderation.reports().await?;
+
+    Ok(Response::ok().json(&reports))
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_dismiss_report(rctx: &RequestContext, id: String) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+    let moderation = rctx.inject::<crate::moderation::Moderation>();
+    validate_access!(moderation.is_admin(&session.name));
+
+    tracing::info!(%id, admin = %session.name, "dismissing report");
+    moderation.dismiss(&id).await?;
+
+    Ok(Response::ok())
+}
+
 #[tracing::instrument(skip(rctx))]
 async fn handle_login(rctx: &RequestContext) -> Result<Response> {
+    redirect_to_oauth(rctx, consts::OAUTH_SCOPE, None).await
+}
+
+/// Logs in again with access to the characters of the account,
+/// the character is imported once the login completes, see [`import_character`].
+#[tracing::instrument(skip(rctx))]
+async fn handle_import_character(rctx: &RequestContext, name: String) -> Result<Response> {
+    validate_access!(rctx.is_logged_in());
+    validate!(
+        crate::characters::is_valid_character_name(&name),
+        "Invalid character name"
+    );
+
+    redirect_to_oauth(rctx, consts::OAUTH_SCOPE_CHARACTERS, Some(&name)).await
+}
+
+async fn redirect_to_oauth(
+    rctx: &RequestContext,
+    scope: &str,
+    character: Option<&str>,
+) -> Result<Response> {
     let req_url = rctx.url()?;
     let host = crate::utils::if_develop!("preview.pobb.in", req_url.host_str().unwrap());
 
-    let state = create_oauth_state(&req_url, rctx.referrer().as_ref())?;
+    let state = create_oauth_state(&req_url, rctx.referrer().as_ref(), character)?;
     let redirect_uri = format!("https://{host}/oauth2/authorization/poe");
-    let login_uri = rctx.inject::<crate::poe_api::Oauth>().get_login_url(
-        &redirect_uri,
-        &state,
-        consts::OAUTH_SCOPE,
-    );
+    let login_uri =
+        rctx.inject::<crate::poe_api::Oauth>()
+            .get_login_url(&redirect_uri, &state, scope);
 
     tracing::info!(%redirect_uri, %state, "redirecting for login");
 
+    // The state cookie is signed, it can only be created by the worker.
+    let state_cookie = rctx
+        .inject::<crate::dangerous::Dangerous>()
+        .sign(&state)
+        .await?;
+
     sentry::counter(Counters::ApiLogin).inc(1);
-    Ok(Response::redirect_temp(&login_uri).state_cookie(&state))
+    Ok(Response::redirect_temp(&login_uri).state_cookie(&utils::b64_encode(state_cookie)))
 }
 
 #[tracing::instrument(skip(rctx))]
@@ -440,6 +1771,18 @@ async fn handle_oauth2_poe(rctx: &RequestContext) -> Result<Response> {
     crate::utils::if_develop!({}, {
         use crate::utils::RequestExt;
         let cookie_state = rctx.cookie("state").unwrap_or_default();
+        let cookie_state = utils::b64_decode(cookie_state)
+            .ok()
+            .and_then(|state| String::from_utf8(state).ok())
+            .unwrap_or_default();
+        let cookie_state = rctx
+            .inject::<crate::dangerous::Dangerous>()
+            .verify::<String>(&cookie_state, consts::OAUTH_STATE_MAX_AGE)
+            .await
+            .map_err(|err| {
+                tracing::warn!("invalid state cookie: {err:?}");
+                Error::InvalidSessionState
+            })?;
         if cookie_state != grant.state {
             tracing::warn!(%cookie_state, %grant.state, "grant state does not match cookie state");
             return Err(Error::InvalidSessionState);
@@ -449,18 +1792,21 @@ async fn handle_oauth2_poe(rctx: &RequestContext) -> Result<Response> {
     let oauth = rctx.inject::<crate::poe_api::Oauth>();
     let token = oauth.fetch_token(&grant.code).await?;
 
-    let profile = poe_api::PoeApi::new(token.access_token)
-        .fetch_profile()
-        .await?;
+    let api = poe_api::PoeApi::new(token.access_token);
+    let profile = api.fetch_profile().await?;
 
     sentry::update_username(&profile.name);
 
+    if let Some(character) = character_from_oauth_state(&grant.state) {
+        return import_character(rctx, api, &profile, character).await;
+    }
+
     let user = app::User {
         name: User::new(&profile.name),
     };
     let session = rctx
-        .inject::<crate::dangerous::Dangerous>()
-        .sign(&user)
+        .inject::<crate::sessions::Sessions>()
+        .create(user)
         .await?;
 
     sentry::counter(Counters::ApiLoginSuccess).inc(1);
@@ -470,12 +1816,106 @@ async fn handle_oauth2_poe(rctx: &RequestContext) -> Result<Response> {
         .result()
 }
 
-fn create_oauth_state(req_url: &url::Url, referrer: Option<&url::Url>) -> Result<String> {
+#[tracing::instrument(skip(rctx))]
+async fn handle_logout(rctx: &RequestContext) -> Result<Response> {
+    if let Some(session) = rctx.req().session() {
+        rctx.inject::<crate::sessions::Sessions>()
+            .revoke(&session)
+            .await?;
+    }
+
+    Ok(Response::ok().delete_session())
+}
+
+#[tracing::instrument(skip(rctx))]
+async fn handle_logout_everywhere(rctx: &RequestContext) -> Result<Response> {
+    let session = rctx.session().ok_or(Error::AccessDenied)?;
+
+    rctx.inject::<crate::sessions::Sessions>()
+        .revoke_all(&session.name)
+        .await?;
+
+    Ok(Response::ok().delete_session())
+}
+
+/// Creates the OAuth state `<random>[:<character>].<path>`,
+/// the path is where the user is redirected to after the login.
+fn create_oauth_state(
+    req_url: &url::Url,
+    referrer: Option<&url::Url>,
+    character: Option<&str>,
+) -> Result<String> {
     let path = referrer
         .filter(|url| url.host_str() == req_url.host_str())
         .map(|url| &url[url::Position::BeforePath..])
         .unwrap_or("/");
-    Ok(format!("{}.{}", utils::random_string::<12>()?, path))
+    let random = utils::random_string::<12>()?;
+    match character {
+        Some(character) => Ok(format!("{random}:{character}.{path}")),
+        None => Ok(format!("{random}.{path}")),
+    }
+}
+
+/// Character to import after the login, see [`create_oauth_state`].
+fn character_from_oauth_state(state: &str) -> Option<&str> {
+    let (prefix, _) = state.split_once('.')?;
+    prefix.split_once(':').map(|(_, character)| character)
+}
+
+/// Creates a private user paste from a character of the logged in user.
+#[tracing::instrument(skip(rctx, api, profile))]
+async fn import_character(
+    rctx: &RequestContext,
+    api: poe_api::PoeApi,
+    profile: &poe_api::Profile,
+    name: &str,
+) -> Result<Response> {
+    let session = rctx.session().ok_or_else(|| {
+        tracing::warn!("missing user session");
+        Error::AccessDenied
+    })?;
+    // The authorized account must be the account of the session.
+    validate_access!(session.name == User::new(&profile.name));
+    validate!(
+        crate::characters::is_valid_character_name(name),
+        "Invalid character name"
+    );
+
+    let build = crate::characters::Characters::new(api).build(name).await?;
+
+    let pob = validate_pob(true, build.content.as_bytes())?;
+    let mut metadata = to_metadata(&pob);
+    validate_v!(validation::user::is_valid_custom_title(&build.title));
+    metadata.title = build.title;
+    // Characters are private until the user decides to share them.
+    metadata.private = true;
+
+    let id = PasteId::UserPaste(UserPasteId {
+        user: session.name.clone(),
+        id: utils::random_string::<9>()?.try_into()?,
+    });
+    let sha1 = crypto::sha1(build.content.as_bytes()).await?;
+
+    tracing::debug!("--> uploading character paste '{}'", id);
+    rctx.inject::<crate::storage::Storage>()
+        .put(&id, &sha1, build.content.as_bytes(), Some(&metadata))
+        .await?;
+    tracing::debug!("<-- character paste uploaded");
+
+    crate::search::on_paste_change(rctx, id.clone(), &metadata, &pob);
+    crate::webhooks::on_paste_change(
+        rctx,
+        crate::webhooks::Event::Created,
+        id.clone(),
+        Some(&metadata),
+    );
+
+    let location = id.to_url().into_cow().into_owned();
+    crate::cache::on_paste_change(rctx, id);
+
+    Response::redirect_temp(&location)
+        .delete_state_cookie()
+        .result()
 }
 
 fn redirect_from_oauth_state(state: &str) -> &str {
@@ -1,4 +1,4 @@
-use shared::{PasteId, UrlSafe, UserPasteId};
+use shared::{model::ListPastesQuery, PasteId, UserPasteId};
 
 use crate::{
     app_metadata, consts, response,
@@ -13,6 +13,7 @@ pub async fn handle(rctx: &RequestContext, route: app::Route) -> response::Resul
 pub async fn handle_err(err: crate::Error) -> Response {
     let err = match err {
         crate::Error::NotFound(typ, id) => app::Error::NotFound(typ, id),
+        crate::Error::Gone(typ, id) => app::Error::Gone(typ, id),
         err => app::Error::ServerError(err.to_string()),
     };
 
@@ -32,7 +33,7 @@ async fn handle_inner(rctx: &RequestContext, route: app::Route) -> Result<Respon
     });
 
     if let Some(location) = info.redirect {
-        return Ok(Response::redirect_perm(&location.into_cow()));
+        return Ok(Response::redirect_perm(&location));
     }
 
     Ok(render(info, ctx).await)
@@ -54,6 +55,11 @@ async fn render(info: ResponseInfo, ctx: app::Context) -> Response {
         .replace("%app%", &app);
 
     let etag = info.etag.as_deref().map(|etag| Etag::weak(etag).git());
+    // Only embeddable pages may be framed by other sites.
+    let frame_ancestors = match info.embeddable {
+        true => "frame-ancestors *",
+        false => "frame-ancestors 'self'",
+    };
 
     Response::status(resp_ctx.status_code)
         .html(index)
@@ -61,7 +67,9 @@ async fn render(info: ResponseInfo, ctx: app::Context) -> Response {
         .etag(etag)
         .append_header("Link", app_metadata::EARLY_HINTS)
         .append_header("Link", &link_preload)
+        .header("Content-Security-Policy", frame_ancestors)
         .cache(info.cache_control)
+        .private(info.private)
 }
 
 #[tracing::instrument(skip(rctx))]
@@ -82,24 +90,69 @@ async fn build_context(
             paste_page(rctx, id, Context::paste).await?
         }
         User(user) => {
+            let query = rctx.url()?;
+            let query = ListPastesQuery::from_query_string(query.query().unwrap_or_default());
+
             let pastes = rctx.inject::<crate::pastes::Pastes>();
             let session = rctx.session();
-            let (meta, pastes) = pastes.list_pastes(session, &user).await?;
+            let (meta, page) = pastes.list_pastes(session, &user, &query).await?;
 
             let info = ResponseInfo {
+                cache_control: crate::cache::list_cache_control(&query),
                 etag: Some(meta.etag),
                 meta: Some(response::Meta::list(&user)),
                 ..Default::default()
             };
 
-            (info, Context::user(user, pastes))
+            (info, Context::user(user, page, query))
+        }
+        UserCollection(user, id) => {
+            let collections = rctx.inject::<crate::collections::Collections>();
+            match collections.page(rctx.session(), &user, &id).await? {
+                Some(page) => {
+                    let info = ResponseInfo {
+                        cache_control: crate::cache::collection_cache_control(),
+                        meta: Some(response::Meta::list(&user)),
+                        ..Default::default()
+                    };
+                    (info, Context::user_collection(page))
+                }
+                None => (
+                    ResponseInfo::default().with_etag("not_found"),
+                    Context::not_found(),
+                ),
+            }
         }
         UserPaste(user, id) => {
             let id = UserPasteId { user, id }.into();
             paste_page(rctx, id, Context::user_paste).await?
         }
+        UserPasteHistory(user, id) => {
+            let id = UserPasteId { user, id };
+            let access = crate::shares::check_revision_access(rctx, &id).await?;
+            let pastes = rctx.inject::<crate::pastes::Pastes>();
+            let revisions = pastes.list_revisions(&id).await?;
+
+            let info = ResponseInfo {
+                meta: Some(response::Meta::paste(id.clone(), ())),
+                private: access.is_private(),
+                ..Default::default()
+            };
+
+            (info, Context::user_paste_history(id, revisions))
+        }
+        Embed(id) => {
+            let id = PasteId::Paste(id);
+            let (info, ctx) = paste_page(rctx, id, Context::embed).await?;
+            (info.embeddable(), ctx)
+        }
+        UserEmbed(user, id) => {
+            let id = UserPasteId { user, id }.into();
+            let (info, ctx) = paste_page(rctx, id, Context::embed).await?;
+            (info.embeddable(), ctx)
+        }
         UserEditPaste(user, id) => {
-            let location = UserPasteId { user, id }.to_paste_url();
+            let location = UserPasteId { user, id }.to_paste_url().into_cow();
             (ResponseInfo::redirect(location), Context::not_found())
         }
     };
@@ -114,19 +167,49 @@ async fn paste_page(
 ) -> Result<(ResponseInfo, app::Context)> {
     let pastes = rctx.inject::<crate::pastes::Pastes>();
 
-    let r = match pastes.get_paste(&id).await {
-        Ok(Some((meta, paste))) => {
+    let paste = match pastes.get_paste(&id).await {
+        Ok(Some((meta, paste))) => crate::shares::check_access(rctx, &id, paste.metadata.as_ref())
+            .await
+            .map(|access| Some((meta, paste, access))),
+        r => r.map(|_| None),
+    };
+
+    // Renamed user pastes are permanently redirected to their new id.
+    let redirect = match (&paste, &id) {
+        (Ok(None), PasteId::UserPaste(up)) => {
+            let storage = rctx.inject::<crate::storage::Storage>();
+            storage.get_redirect(up).await?
+        }
+        _ => None,
+    };
+    if let Some(to) = redirect {
+        let location = match rctx.route() {
+            crate::route::Route::App(app::Route::UserEmbed(..)) => PasteId::from(to).to_embed_url(),
+            _ => to.to_paste_url(),
+        };
+        let location = crate::shares::with_share_token(rctx, &location.into_cow());
+        return Ok((ResponseInfo::redirect(location), app::Context::not_found()));
+    }
+
+    let r = match paste {
+        Ok(Some((meta, paste, access))) => {
             let info = ResponseInfo {
+                cache_control: crate::cache::paste_cache_control(meta.expires),
                 etag: Some(meta.etag),
                 meta: Some(response::Meta::paste(&id, &paste)),
+                private: access.is_private(),
                 ..Default::default()
             };
             (info, mapper(id, paste))
         }
-        Err(Error::InvalidId(..)) | Ok(None) => (
+        Err(Error::InvalidId(..)) | Err(Error::NotFound(..)) | Ok(None) => (
             ResponseInfo::default().with_etag("not_found"),
             app::Context::not_found(),
         ),
+        Err(Error::Gone(typ, id)) => (
+            ResponseInfo::default(),
+            app::Context::error(app::Error::Gone(typ, id)),
+        ),
         Err(err) => return Err(err),
     };
 
@@ -136,18 +219,28 @@ async fn paste_page(
 struct ResponseInfo {
     cache_control: CacheControl,
     etag: Option<String>,
-    redirect: Option<UrlSafe<'static>>,
+    redirect: Option<String>,
     meta: Option<response::Meta>,
+    /// Whether the page contains private content, see [`Response::private`].
+    private: bool,
+    /// Whether the page may be embedded in other sites.
+    embeddable: bool,
 }
 
 impl ResponseInfo {
-    pub fn redirect(location: UrlSafe<'static>) -> Self {
+    pub fn redirect(location: impl Into<String>) -> Self {
         Self {
-            redirect: Some(location),
+            redirect: Some(location.into()),
             ..Default::default()
         }
     }
 
+    /// Allows other sites to embed the page in a frame.
+    pub fn embeddable(mut self) -> Self {
+        self.embeddable = true;
+        self
+    }
+
     pub fn with_etag(mut self, etag: impl Into<String>) -> Self {
         self.etag = Some(etag.into());
         self
@@ -163,6 +256,8 @@ impl Default for ResponseInfo {
             etag: None,
             redirect: None,
             meta: None,
+            private: false,
+            embeddable: false,
         }
     }
 }
@@ -0,0 +1,151 @@
+//! Minimal tar (ustar) archives, used for exporting and importing pastes.
+//!
+//! Only regular files with names of up to 100 bytes are supported,
+//! which is all the export needs.
+use crate::{Error, Result};
+
+const BLOCK_SIZE: usize = 512;
+const NAME_LEN: usize = 100;
+
+/// Encodes a single file entry, header and padded content.
+pub fn entry(name: &str, data: &[u8], mtime: u64) -> Result<Vec<u8>> {
+    if name.is_empty() || name.len() > NAME_LEN {
+        return Err(Error::Error(format!("invalid archive entry name '{name}'")));
+    }
+
+    let mut header = [0u8; BLOCK_SIZE];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], data.len() as u64);
+    write_octal(&mut header[136..148], mtime);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // The checksum is calculated with the checksum field filled with spaces.
+    header[148..156].fill(b' ');
+    let checksum = header.iter().map(|&b| b as u64).sum::<u64>();
+    write_octal(&mut header[148..155], checksum);
+
+    let mut result = Vec::with_capacity(BLOCK_SIZE + padded(data.len()));
+    result.extend_from_slice(&header);
+    result.extend_from_slice(data);
+    result.resize(BLOCK_SIZE + padded(data.len()), 0);
+
+    Ok(result)
+}
+
+/// End of archive marker, two empty blocks.
+pub fn finish() -> Vec<u8> {
+    vec![0; 2 * BLOCK_SIZE]
+}
+
+#[derive(Debug)]
+pub struct Entry<'a> {
+    pub name: &'a str,
+    pub data: &'a [u8],
+}
+
+/// Reads all regular file entries of an archive.
+pub fn entries(mut archive: &[u8]) -> Result<Vec<Entry<'_>>> {
+    let invalid = |msg: &str| Error::BadRequest(format!("Invalid archive: {msg}"));
+
+    let mut entries = Vec::new();
+    while archive.len() >= BLOCK_SIZE {
+        let (header, rest) = archive.split_at(BLOCK_SIZE);
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+
+        let checksum = read_octal(&header[148..156]).ok_or_else(|| invalid("checksum"))?;
+        let expected = header
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| if (148..156).contains(&i) { b' ' } else { b })
+            .map(|b| b as u64)
+            .sum::<u64>();
+        if checksum != expected {
+            return Err(invalid("checksum mismatch"));
+        }
+
+        let name_len = header[..NAME_LEN]
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(NAME_LEN);
+        let name = std::str::from_utf8(&header[..name_len]).map_err(|_| invalid("name"))?;
+        let size = read_octal(&header[124..136]).ok_or_else(|| invalid("size"))? as usize;
+
+        if rest.len() < size {
+            return Err(invalid("truncated"));
+        }
+
+        // Directories and other special entries are skipped.
+        if matches!(header[156], b'0' | 0) {
+            entries.push(Entry {
+                name,
+                data: &rest[..size],
+            });
+        }
+
+        archive = &rest[padded(size).min(rest.len())..];
+    }
+
+    Ok(entries)
+}
+
+fn padded(len: usize) -> usize {
+    len.div_ceil(BLOCK_SIZE) * BLOCK_SIZE
+}
+
+/// Writes a zero padded, nul terminated octal number.
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let s = format!("{value:0digits$o}");
+    field[..digits].copy_from_slice(&s.as_bytes()[s.len() - digits..]);
+    field[digits] = 0;
+}
+
+fn read_octal(field: &[u8]) -> Option<u64> {
+    let s = std::str::from_utf8(field).ok()?;
+    let s = s.trim_matches(|c: char| c == '\0' || c == ' ');
+    u64::from_str_radix(s, 8).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let mut archive = Vec::new();
+        archive.extend(entry("manifest.json", b"{}", 1).unwrap());
+        archive.extend(entry("abcde.txt", &[b'a'; 600], 2).unwrap());
+        archive.extend(entry("empty.xml", b"", 3).unwrap());
+        archive.extend(finish());
+        assert_eq!(archive.len() % BLOCK_SIZE, 0);
+
+        let entries = entries(&archive).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].name, "manifest.json");
+        assert_eq!(entries[0].data, b"{}");
+        assert_eq!(entries[1].name, "abcde.txt");
+        assert_eq!(entries[1].data, &[b'a'; 600]);
+        assert_eq!(entries[2].name, "empty.xml");
+        assert!(entries[2].data.is_empty());
+    }
+
+    #[test]
+    fn test_archive_invalid() {
+        let mut archive = entry("abcde.txt", b"foo", 0).unwrap();
+        archive[0] = b'x';
+        assert!(entries(&archive).is_err());
+
+        let archive = entry("abcde.txt", b"foo", 0).unwrap();
+        assert!(entries(&archive[..BLOCK_SIZE + 3]).is_ok());
+        assert!(entries(&archive[..BLOCK_SIZE]).is_err());
+
+        assert!(entry(&"a".repeat(101), b"", 0).is_err());
+    }
+}
@@ -35,6 +35,18 @@ async fn serve_asset(rctx: &RequestContext) -> Result<Response> {
         .result()
 }
 
+/// Loads the content of a text asset.
+pub(crate) async fn load_text(rctx: &RequestContext, path: &str) -> Result<Option<String>> {
+    let assets = rctx.inject::<Assets>();
+    Ok(assets.get(path).text().await?)
+}
+
+/// Loads the content of a binary asset.
+pub(crate) async fn load_bytes(rctx: &RequestContext, path: &str) -> Result<Option<Vec<u8>>> {
+    let assets = rctx.inject::<Assets>();
+    Ok(assets.get(path).bytes().await?)
+}
+
 pub fn is_asset_path(path: &str) -> bool {
     get_mime(path).is_some()
 }
@@ -1,8 +1,13 @@
-use shared::PasteId;
+use std::time::Duration;
+
+use app::pob::markup::Markup;
+use shared::{model::ListPastesQuery, Id, PasteId, User, UserPasteId};
 
 use crate::{
+    consts,
     request_context::RequestContext,
     route::{Api, Route},
+    utils::CacheControl,
     Response,
 };
 
@@ -38,6 +43,11 @@ impl CacheEntry<'_> {
             return response;
         };
 
+        if response.is_private() {
+            tracing::debug!("not caching private response in {cache}");
+            return response;
+        }
+
         let for_cache = response.for_cache();
 
         // This is technically not necessary we should be able to clone the inner JS request,
@@ -64,6 +74,11 @@ impl<'a> From<&'a RequestContext> for CacheEntry<'a> {
             return Self { inner: None };
         }
 
+        // Share links are secret and revocable, they must never be cached.
+        if crate::shares::share_token(value).is_some() {
+            return Self { inner: None };
+        }
+
         let cache = Cache::select(value);
 
         Self {
@@ -91,7 +106,23 @@ impl Cache {
 
         match rctx.route() {
             Route::App(app::Route::User(user))
-            | Route::Api(Api::Get(crate::route::GetEndpoints::User(user))) => {
+            | Route::App(app::Route::UserPasteHistory(user, _))
+            | Route::App(app::Route::UserCollection(user, _))
+            | Route::App(app::Route::UserPaste(user, _))
+            | Route::App(app::Route::UserEmbed(user, _))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::User(user)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserCollections(user)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserCollection(user, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserPaste(user, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserPasteJson(user, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserPasteXml(user, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserPasteMarkdown(user, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserPasteBbCode(user, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserPasteBuildSummary(user, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserPastePreview(user, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::PobUserPaste(user, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserPasteRevision(user, _, _)))
+            | Route::Api(Api::Get(crate::route::GetEndpoints::UserPasteRevisionJson(user, _, _))) => {
                 if Some(user) == session.map(|s| &s.name) {
                     Cache::Owned
                 } else {
@@ -116,6 +147,41 @@ impl std::fmt::Display for Cache {
     }
 }
 
+/// Cache control for a page of a user's pastes.
+///
+/// Only the unfiltered first page is purged from the cache when a paste changes,
+/// all other pages are never purged and can only be cached briefly.
+/// Changes take up to [`consts::CACHE_LIST_PAGE`] to show up on these pages.
+pub(crate) fn list_cache_control(query: &ListPastesQuery) -> CacheControl {
+    let ttl = match query.is_default() {
+        true => consts::CACHE_FOREVER,
+        false => consts::CACHE_LIST_PAGE,
+    };
+    CacheControl::default().public().s_max_age(ttl)
+}
+
+/// Cache control for collections.
+///
+/// Collections are purged from the cache when they change, but not when one of their
+/// pastes changes, they can only be cached briefly.
+pub(crate) fn collection_cache_control() -> CacheControl {
+    CacheControl::default()
+        .public()
+        .s_max_age(consts::CACHE_LIST_PAGE)
+}
+
+/// Cache control for a paste, expiring pastes are only cached until they expire.
+pub(crate) fn paste_cache_control(expires: Option<u64>) -> CacheControl {
+    let ttl = match expires {
+        Some(expires) => {
+            let now = worker::Date::now().as_millis();
+            Duration::from_millis(expires.saturating_sub(now)).min(consts::CACHE_FOREVER)
+        }
+        None => consts::CACHE_FOREVER,
+    };
+    CacheControl::default().public().s_max_age(ttl)
+}
+
 pub(crate) fn on_paste_change(rctx: &RequestContext, id: PasteId) {
     let url = rctx.url().unwrap();
     rctx.ctx().wait_until(on_paste_change_async(url, id));
@@ -143,12 +209,77 @@ pub(crate) async fn on_paste_change_async(mut url: url::Url, id: PasteId) {
     clear!(id.to_raw_url());
     clear!(id.to_json_url());
     clear!(id.to_pob_load_url());
+    for markup in [Markup::Markdown, Markup::BbCode] {
+        clear!(id.to_url().join(markup.extension()));
+    }
+    clear!(id.to_url().join("summary.json"));
+    clear!(id.to_url().join(app::pob::preview::PATH));
+    clear!(id.to_embed_url());
 
     if let PasteId::UserPaste(up) = id {
         clear!(up.to_pob_long_load_url());
         clear!(up.to_paste_edit_url());
+        clear!(up.to_history_url());
+        clear!(up.to_revisions_api_url());
         clear!(up.to_user_url());
         clear!(up.to_user_api_url());
     }
     tracing::info!("done resetting caches");
 }
+
+/// Resets the cached URLs of deleted revisions, revisions never change and are cached forever.
+pub(crate) fn on_revisions_delete(rctx: &RequestContext, id: UserPasteId, revisions: Vec<u64>) {
+    if revisions.is_empty() {
+        return;
+    }
+
+    let mut url = rctx.url().unwrap();
+    url.set_path("");
+    url.set_query(None);
+    url.set_fragment(None);
+    let prefix = url.to_string();
+
+    rctx.ctx().wait_until(async move {
+        let cache_default = Cache::Default.open().await;
+        let cache_owned = Cache::Owned.open().await;
+
+        tracing::info!(
+            "resetting cached URLs for {} revisions of {id}",
+            revisions.len()
+        );
+        for revision in revisions {
+            for url in [
+                id.to_revision_raw_url(revision),
+                id.to_revision_json_url(revision),
+            ] {
+                let r = format!("{prefix}{}", url.into_cow().trim_start_matches('/'));
+                let _ = cache_default.delete(&r, true).await;
+                let _ = cache_owned.delete(&r, true).await;
+            }
+        }
+    });
+}
+
+pub(crate) fn on_collection_change(rctx: &RequestContext, user: User, id: Id) {
+    let mut url = rctx.url().unwrap();
+    url.set_path("");
+    url.set_query(None);
+    url.set_fragment(None);
+    let prefix = url.to_string();
+
+    rctx.ctx().wait_until(async move {
+        let cache_default = Cache::Default.open().await;
+        let cache_owned = Cache::Owned.open().await;
+
+        tracing::info!("resetting cached URLs for collection {user}:{id}");
+        for url in [
+            user.to_collection_url(&id),
+            user.to_collection_api_url(&id),
+            user.to_collections_api_url(),
+        ] {
+            let r = format!("{prefix}{}", url.into_cow().trim_start_matches('/'));
+            let _ = cache_default.delete(&r, true).await;
+            let _ = cache_owned.delete(&r, true).await;
+        }
+    });
+}
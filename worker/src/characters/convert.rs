@@ -0,0 +1,383 @@
+//! Conversion of a character into a Path of Building build.
+use std::{borrow::Cow, fmt::Write};
+
+use shared::{Ascendancy, Bandit, Class};
+
+use super::model::{Character, Item};
+use crate::{Error, Result};
+
+/// Converts a character into the XML of a Path of Building build.
+///
+/// Passive tree jewels are added to the items, but not socketed,
+/// the API does not expose the node of a jewel socket.
+pub fn to_xml(character: &Character, tree_version: &str) -> Result<String> {
+    let (class, ascendancy) = class(&character.class)
+        .ok_or_else(|| Error::BadRequest(format!("Unsupported class {}", character.class)))?;
+
+    let mut items = Items::default();
+    for item in &character.equipment {
+        let Some(slot) = slot(item) else {
+            continue;
+        };
+        items.add(item, Some(&slot));
+
+        let mut abyssal_sockets = 0;
+        for jewel in item.socketed_items.iter().filter(|item| item.abyss_jewel) {
+            abyssal_sockets += 1;
+            items.add(
+                jewel,
+                Some(&format!("{slot} Abyssal Socket {abyssal_sockets}")),
+            );
+        }
+
+        items.skills(item, &slot);
+    }
+    for jewel in &character.jewels {
+        items.add(jewel, None);
+    }
+
+    let main_socket_group = items
+        .skills
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, skill)| (skill.gems.len(), std::cmp::Reverse(*index)))
+        .map(|(index, _)| index + 1)
+        .unwrap_or(1);
+
+    let bandit = character
+        .passives
+        .bandit_choice
+        .as_deref()
+        .and_then(|bandit| bandit.parse::<Bandit>().ok())
+        .map(|bandit| bandit.as_str())
+        .unwrap_or("None");
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<PathOfBuilding>\n");
+
+    let _ = writeln!(
+        xml,
+        r#"<Build level="{}" targetVersion="3_0" className="{}" ascendClassName="{}" bandit="{bandit}" mainSocketGroup="{main_socket_group}" viewMode="ITEMS">"#,
+        character.level,
+        class.as_str(),
+        ascendancy.map(|a| a.as_str()).unwrap_or("None"),
+    );
+    xml.push_str("</Build>\n");
+
+    xml.push_str("<Skills activeSkillSet=\"1\">\n<SkillSet id=\"1\">\n");
+    for skill in &items.skills {
+        let _ = writeln!(
+            xml,
+            r#"<Skill enabled="true" slot="{}" mainActiveSkill="1">"#,
+            escape(&skill.slot)
+        );
+        for gem in &skill.gems {
+            let _ = writeln!(
+                xml,
+                r#"<Gem nameSpec="{}" level="{}" quality="{}" enabled="true"/>"#,
+                escape(&gem.name),
+                gem.level,
+                gem.quality
+            );
+        }
+        xml.push_str("</Skill>\n");
+    }
+    xml.push_str("</SkillSet>\n</Skills>\n");
+
+    let mut mastery_effects = character
+        .passives
+        .mastery_effects
+        .iter()
+        .filter_map(|(node, effect)| Some((node.parse::<u32>().ok()?, *effect)))
+        .collect::<Vec<_>>();
+    mastery_effects.sort_unstable();
+    let mastery_effects = mastery_effects
+        .into_iter()
+        .map(|(node, effect)| format!("{{{node},{effect}}}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    let nodes = character
+        .passives
+        .hashes
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    xml.push_str("<Tree activeSpec=\"1\">\n");
+    let _ = writeln!(
+        xml,
+        r#"<Spec treeVersion="{}" classId="{}" ascendClassId="{}" nodes="{nodes}" masteryEffects="{mastery_effects}"/>"#,
+        escape(tree_version),
+        class_id(class),
+        ascendancy.and_then(ascendancy_id).unwrap_or(0),
+    );
+    xml.push_str("</Tree>\n");
+
+    let mut notes = format!("Imported from the character {}", character.name);
+    if let Some(league) = &character.league {
+        let _ = write!(notes, " ({league})");
+    }
+    if !character.jewels.is_empty() {
+        notes.push_str(".\nJewels of the passive tree still need to be socketed.");
+    }
+    let _ = writeln!(xml, "<Notes>{}</Notes>", escape(&notes));
+
+    xml.push_str("<Items activeItemSet=\"1\">\n");
+    for (index, item) in items.items.iter().enumerate() {
+        let _ = writeln!(xml, r#"<Item id="{}">{}</Item>"#, index + 1, escape(item));
+    }
+    xml.push_str("<ItemSet id=\"1\">\n");
+    for (slot, id) in &items.slots {
+        let _ = writeln!(xml, r#"<Slot name="{}" itemId="{id}"/>"#, escape(slot));
+    }
+    xml.push_str("</ItemSet>\n</Items>\n");
+
+    xml.push_str("<Config/>\n</PathOfBuilding>\n");
+
+    Ok(xml)
+}
+
+#[derive(Default)]
+struct Items {
+    /// Item texts, ids are the index plus one.
+    items: Vec<String>,
+    slots: Vec<(String, usize)>,
+    skills: Vec<Skill>,
+}
+
+struct Skill {
+    slot: String,
+    gems: Vec<Gem>,
+}
+
+struct Gem {
+    name: String,
+    level: u8,
+    quality: u8,
+}
+
+impl Items {
+    fn add(&mut self, item: &Item, slot: Option<&str>) {
+        self.items.push(item_text(item));
+        if let Some(slot) = slot {
+            self.slots.push((slot.to_owned(), self.items.len()));
+        }
+    }
+
+    /// Adds a skill for every linked group of gems socketed into the item.
+    fn skills(&mut self, item: &Item, slot: &str) {
+        let mut gems = item
+            .socketed_items
+            .iter()
+            .filter(|gem| !gem.abyss_jewel && gem.frame_type == FRAME_GEM)
+            .filter_map(|gem| {
+                let socket = gem.socket?;
+                Some((item.sockets.get(socket)?.group, socket, gem))
+            })
+            .collect::<Vec<_>>();
+        gems.sort_by_key(|(group, socket, _)| (*group, *socket));
+
+        let mut current = None;
+        for (group, _, gem) in gems {
+            let gem = Gem {
+                // Transfigured gems are only identified by their type line.
+                name: gem.type_line.clone(),
+                level: gem.property("Level").unwrap_or(1),
+                quality: gem.property("Quality").unwrap_or(0),
+            };
+
+            match self.skills.last_mut() {
+                Some(skill) if current == Some(group) => skill.gems.push(gem),
+                _ => self.skills.push(Skill {
+                    slot: slot.to_owned(),
+                    gems: vec![gem],
+                }),
+            }
+            current = Some(group);
+        }
+    }
+}
+
+const FRAME_GEM: u8 = 4;
+
+fn item_text(item: &Item) -> String {
+    let mut lines = Vec::new();
+
+    let rarity = match item.frame_type {
+        1 => "MAGIC",
+        2 => "RARE",
+        3 => "UNIQUE",
+        9 => "RELIC",
+        _ => "NORMAL",
+    };
+    lines.push(format!("Rarity: {rarity}"));
+
+    let base = item.base_type.as_deref().unwrap_or(&item.type_line);
+    match item.frame_type {
+        2 | 3 | 9 => {
+            let name = strip_markup(&item.name);
+            lines.push(if name.is_empty() { base } else { name }.to_owned());
+            lines.push(base.to_owned());
+        }
+        _ => lines.push(strip_markup(&item.type_line).to_owned()),
+    }
+
+    for (influence, line) in INFLUENCES {
+        if item.influences.get(influence) == Some(&true) {
+            lines.push(line.to_owned());
+        }
+    }
+
+    if item.ilvl > 0 {
+        lines.push(format!("Item Level: {}", item.ilvl));
+    }
+    if let Some(quality) = item.property("Quality") {
+        lines.push(format!("Quality: {quality}"));
+    }
+    if !item.sockets.is_empty() {
+        lines.push(format!("Sockets: {}", sockets(item)));
+    }
+    if let Some(level) = item.requirement("Level") {
+        lines.push(format!("LevelReq: {level}"));
+    }
+
+    let implicits = mods(&item.enchant_mods, "{enchant}")
+        .chain(mods(&item.implicit_mods, ""))
+        .collect::<Vec<_>>();
+    lines.push(format!("Implicits: {}", implicits.len()));
+    lines.extend(implicits);
+    lines.extend(
+        mods(&item.fractured_mods, "{fractured}")
+            .chain(mods(&item.explicit_mods, ""))
+            .chain(mods(&item.crafted_mods, "{crafted}")),
+    );
+
+    if item.split {
+        lines.push("Split".to_owned());
+    }
+    if item.duplicated {
+        lines.push("Mirrored".to_owned());
+    }
+    if item.corrupted {
+        lines.push("Corrupted".to_owned());
+    }
+
+    lines.join("\n")
+}
+
+const INFLUENCES: [(&str, &str); 6] = [
+    ("shaper", "Shaper Item"),
+    ("elder", "Elder Item"),
+    ("crusader", "Crusader Item"),
+    ("redeemer", "Redeemer Item"),
+    ("hunter", "Hunter Item"),
+    ("warlord", "Warlord Item"),
+];
+
+/// Mods with a prefix, multi line mods are split into one line per stat.
+fn mods<'a>(mods: &'a [String], prefix: &'a str) -> impl Iterator<Item = String> + 'a {
+    mods.iter()
+        .flat_map(|m| m.lines())
+        .map(move |line| format!("{prefix}{line}"))
+}
+
+/// Sockets in the PoB notation, linked sockets are joined with `-`.
+fn sockets(item: &Item) -> String {
+    let mut result = String::new();
+    let mut previous = None;
+
+    for socket in &item.sockets {
+        match previous {
+            Some(group) if group == socket.group => result.push('-'),
+            Some(_) => result.push(' '),
+            None => {}
+        }
+        result.push_str(socket.s_colour.as_deref().unwrap_or("W"));
+        previous = Some(socket.group);
+    }
+
+    result
+}
+
+/// PoB item slot of an equipped item.
+fn slot(item: &Item) -> Option<String> {
+    let slot = match item.inventory_id.as_deref()? {
+        "Weapon" => "Weapon 1",
+        "Offhand" => "Weapon 2",
+        "Weapon2" => "Weapon 1 Swap",
+        "Offhand2" => "Weapon 2 Swap",
+        "Helm" => "Helmet",
+        "BodyArmour" => "Body Armour",
+        "Gloves" => "Gloves",
+        "Boots" => "Boots",
+        "Amulet" => "Amulet",
+        "Ring" => "Ring 1",
+        "Ring2" => "Ring 2",
+        "Ring3" => "Ring 3",
+        "Belt" => "Belt",
+        "Flask" => return Some(format!("Flask {}", item.x? + 1)),
+        _ => return None,
+    };
+
+    Some(slot.to_owned())
+}
+
+/// Class of a character, the API reports the ascendancy for ascended characters.
+fn class(class: &str) -> Option<(Class, Option<Ascendancy>)> {
+    if let Ok(ascendancy) = class.parse::<Ascendancy>() {
+        return Some((ascendancy.class(), Some(ascendancy)));
+    }
+    let class = class.parse::<Class>().ok()?;
+    Some((class, None))
+}
+
+/// Class id of the passive tree.
+fn class_id(class: Class) -> u8 {
+    match class {
+        Class::Scion => 0,
+        Class::Marauder => 1,
+        Class::Ranger => 2,
+        Class::Witch => 3,
+        Class::Duelist => 4,
+        Class::Templar => 5,
+        Class::Shadow => 6,
+        _ => 0,
+    }
+}
+
+/// Ascendancy id of the passive tree, relative to the class.
+fn ascendancy_id(ascendancy: Ascendancy) -> Option<u8> {
+    use Ascendancy::*;
+
+    let id = match ascendancy {
+        Ascendant | Juggernaut | Raider | Warden | Occultist | Slayer | Inquisitor | Assassin => 1,
+        Berserker | Deadeye | Elementalist | Gladiator | Hierophant | Trickster => 2,
+        Chieftain | Pathfinder | Necromancer | Champion | Guardian | Saboteur => 3,
+        _ => return None,
+    };
+
+    Some(id)
+}
+
+/// Strips formatting markup like `<<set:MS>>` from item names.
+fn strip_markup(name: &str) -> &str {
+    name.rsplit(">>").next().unwrap_or(name)
+}
+
+fn escape(s: &str) -> Cow<'_, str> {
+    if !s.contains(['&', '<', '>', '"']) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut result = String::with_capacity(s.len() + 8);
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            c => result.push(c),
+        }
+    }
+    Cow::Owned(result)
+}
@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+
+use super::{Character, CharacterApi, CharacterResponse};
+use crate::{Error, Result};
+
+/// Character API serving fixture responses, used for tests.
+#[derive(Default)]
+pub struct MemoryApi {
+    /// Responses of the character endpoint by character name.
+    characters: HashMap<String, String>,
+}
+
+impl MemoryApi {
+    /// Adds a response of the character endpoint.
+    pub fn with(mut self, response: &str) -> Self {
+        let character = serde_json::from_str::<CharacterResponse>(response)
+            .expect("invalid character fixture")
+            .character;
+        self.characters.insert(character.name, response.to_owned());
+        self
+    }
+}
+
+impl CharacterApi for MemoryApi {
+    async fn character(&self, name: &str) -> Result<Character> {
+        let response = self
+            .characters
+            .get(name)
+            .ok_or_else(|| Error::NotFound("character", name.to_owned()))?;
+
+        Ok(serde_json::from_str::<CharacterResponse>(response)?.character)
+    }
+}
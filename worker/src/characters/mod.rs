@@ -0,0 +1,146 @@
+//! Builds created from characters of the Path of Exile API.
+//!
+//! Reading characters requires an OAuth token with the `account:characters` scope,
+//! see [`crate::consts::OAUTH_SCOPE_CHARACTERS`].
+use crate::{poe_api::PoeApi, Result};
+
+mod convert;
+#[cfg(test)]
+mod memory;
+mod model;
+
+pub use self::model::{Character, CharacterResponse};
+
+/// Source of characters, the Path of Exile API.
+pub trait CharacterApi {
+    /// Fetches a character of the authorized account.
+    async fn character(&self, name: &str) -> Result<Character>;
+}
+
+impl CharacterApi for PoeApi {
+    async fn character(&self, name: &str) -> Result<Character> {
+        self.fetch_character(name).await
+    }
+}
+
+/// A build created from a character.
+#[derive(Debug)]
+pub struct CharacterBuild {
+    pub title: String,
+    /// The build as Path of Building export code.
+    pub content: String,
+}
+
+pub struct Characters<A> {
+    api: A,
+}
+
+impl<A: CharacterApi> Characters<A> {
+    pub fn new(api: A) -> Self {
+        Self { api }
+    }
+
+    /// Creates a build from the character with the name `name`.
+    #[tracing::instrument(skip(self))]
+    pub async fn build(&self, name: &str) -> Result<CharacterBuild> {
+        let character = self.api.character(name).await?;
+
+        let tree_version = poe_tree::Version::latest().as_str();
+        let xml = convert::to_xml(&character, tree_version)?;
+
+        Ok(CharacterBuild {
+            title: format!(
+                "{} - Level {} {}",
+                character.name, character.level, character.class
+            ),
+            content: pob::compress(&xml),
+        })
+    }
+}
+
+/// Whether `name` is a valid character name, only letters and underscores are allowed.
+pub fn is_valid_character_name(name: &str) -> bool {
+    (3..=23).contains(&name.len()) && name.bytes().all(|c| c.is_ascii_alphabetic() || c == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use pob::{PathOfBuilding, SerdePathOfBuilding};
+    use shared::{Ascendancy, Bandit};
+
+    use super::*;
+    use crate::Error;
+
+    static CHARACTER: &str = include_str!("../../test/character.json");
+
+    fn characters() -> Characters<memory::MemoryApi> {
+        Characters::new(memory::MemoryApi::default().with(CHARACTER))
+    }
+
+    #[test]
+    fn test_build_from_character() {
+        let build = block_on(characters().build("Poison_Occ")).unwrap();
+        assert_eq!(build.title, "Poison_Occ - Level 96 Occultist");
+
+        let pob = SerdePathOfBuilding::from_export(&build.content).unwrap();
+        assert_eq!(pob.level(), 96);
+        assert_eq!(pob.ascendancy(), Some(Ascendancy::Occultist));
+        assert_eq!(pob.bandit(), Some(Bandit::Alira));
+        assert_eq!(pob.main_skill_name(), Some("Poisonous Concoction"));
+
+        let skill_sets = pob.skill_sets();
+        let skills = &skill_sets[0].skills;
+        assert_eq!(skills.len(), 2);
+        let gems = skills[0]
+            .gems
+            .iter()
+            .map(|gem| (gem.name, gem.level, gem.quality))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            gems,
+            vec![
+                ("Poisonous Concoction", 20, 20),
+                ("Unbound Ailments Support", 20, 20),
+                ("Void Manipulation Support", 21, 0),
+            ]
+        );
+
+        let specs = pob.tree_specs();
+        assert_eq!(specs[0].class_id, Some(3));
+        assert_eq!(specs[0].ascendancy_id, Some(1));
+        assert_eq!(specs[0].nodes, [31344, 1648, 292]);
+        assert_eq!(specs[0].mastery_effects, [(292, 38454)]);
+
+        let item_sets = pob.item_sets();
+        let gear = &item_sets[0].gear;
+        let helmet = pob::Item::parse(gear.helmet.unwrap()).unwrap();
+        assert_eq!(helmet.name, Some("Doom Veil"));
+        assert_eq!(helmet.base, "Lion Pelt");
+        assert_eq!(helmet.item_level, 84);
+        assert_eq!(helmet.quality, 20);
+        assert!(helmet.corrupted);
+        assert_eq!(
+            helmet.explicits().map(|m| m.line).collect::<Vec<_>>(),
+            vec!["+109 to maximum Life", "+29% to Lightning Resistance"]
+        );
+        assert!(gear.flask1.is_some());
+        assert!(gear.weapon1.is_none());
+    }
+
+    #[test]
+    fn test_missing_character() {
+        assert!(matches!(
+            block_on(characters().build("Missing")),
+            Err(Error::NotFound("character", _))
+        ));
+    }
+
+    #[test]
+    fn test_character_names() {
+        assert!(is_valid_character_name("Poison_Occ"));
+        assert!(!is_valid_character_name("ab"));
+        assert!(!is_valid_character_name("Poison Occ"));
+        assert!(!is_valid_character_name("Poison.Occ"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Subset of the character model of the Path of Exile API.
+//!
+//! See <https://www.pathofexile.com/developer/docs/reference#type-Character>.
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct CharacterResponse {
+    pub character: Character,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Character {
+    pub name: String,
+    /// Base class or ascendancy of the character.
+    pub class: String,
+    pub level: u8,
+    #[serde(default)]
+    pub league: Option<String>,
+    #[serde(default)]
+    pub equipment: Vec<Item>,
+    /// Jewels socketed into the passive tree.
+    #[serde(default)]
+    pub jewels: Vec<Item>,
+    #[serde(default)]
+    pub passives: Passives,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Passives {
+    #[serde(default)]
+    pub hashes: Vec<u32>,
+    /// Selected mastery effect by mastery node, keys are stringified node hashes.
+    #[serde(default)]
+    pub mastery_effects: HashMap<String, u32>,
+    #[serde(default)]
+    pub bandit_choice: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    #[serde(default)]
+    pub name: String,
+    pub type_line: String,
+    #[serde(default)]
+    pub base_type: Option<String>,
+    pub frame_type: u8,
+    #[serde(default)]
+    pub ilvl: u8,
+    #[serde(default)]
+    pub corrupted: bool,
+    #[serde(default)]
+    pub duplicated: bool,
+    #[serde(default)]
+    pub split: bool,
+    #[serde(default)]
+    pub influences: HashMap<String, bool>,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+    #[serde(default)]
+    pub requirements: Vec<Property>,
+    #[serde(default)]
+    pub enchant_mods: Vec<String>,
+    #[serde(default)]
+    pub implicit_mods: Vec<String>,
+    #[serde(default)]
+    pub fractured_mods: Vec<String>,
+    #[serde(default)]
+    pub explicit_mods: Vec<String>,
+    #[serde(default)]
+    pub crafted_mods: Vec<String>,
+    #[serde(default)]
+    pub sockets: Vec<Socket>,
+    #[serde(default)]
+    pub socketed_items: Vec<Item>,
+    /// Index into the sockets of the parent item, only set for socketed items.
+    #[serde(default)]
+    pub socket: Option<usize>,
+    #[serde(default)]
+    pub support: Option<bool>,
+    #[serde(default)]
+    pub abyss_jewel: bool,
+    #[serde(default)]
+    pub inventory_id: Option<String>,
+    #[serde(default)]
+    pub x: Option<u32>,
+}
+
+impl Item {
+    /// Numeric value of a property, e.g. the level of a gem.
+    ///
+    /// Values are displayed values like `20 (Max)` or `+20%`.
+    pub fn property(&self, name: &str) -> Option<u8> {
+        find_property(&self.properties, name)
+    }
+
+    pub fn requirement(&self, name: &str) -> Option<u8> {
+        find_property(&self.requirements, name)
+    }
+}
+
+fn find_property(properties: &[Property], name: &str) -> Option<u8> {
+    let property = properties.iter().find(|p| p.name == name)?;
+    let (value, _) = property.values.first()?;
+
+    let digits = value
+        .trim_start_matches('+')
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?;
+    digits.parse().ok()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Property {
+    pub name: String,
+    #[serde(default)]
+    pub values: Vec<(String, u8)>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Socket {
+    pub group: u8,
+    /// Colour of the socket, `R`, `G`, `B`, `W` or `A` for abyssal sockets.
+    #[serde(default)]
+    pub s_colour: Option<String>,
+}
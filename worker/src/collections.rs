@@ -0,0 +1,312 @@
+//! Named collections of user pastes.
+//!
+//! A collection is stored as a JSON object in R2, pastes are only referenced by
+//! their id and resolved to summaries whenever the collection is viewed.
+use std::collections::HashMap;
+
+use shared::{
+    model::{Collection, CollectionPage, PasteSummary},
+    Id, User,
+};
+use worker::Bucket;
+
+use crate::{
+    consts,
+    pastes::Pastes,
+    request_context::{Env, FromEnv, Session},
+    retry, Error, Result,
+};
+
+pub struct Collections {
+    bucket: Bucket,
+    pastes: Pastes,
+}
+
+impl FromEnv for Collections {
+    fn from_env(env: &Env) -> Option<Self> {
+        Some(Self {
+            bucket: env.bucket(consts::R2_STORAGE_BUCKET)?,
+            pastes: Pastes::from_env(env)?,
+        })
+    }
+}
+
+impl Collections {
+    #[tracing::instrument(skip(self))]
+    pub async fn get(&self, user: &User, id: &Id) -> Result<Option<Collection>> {
+        let path = to_path(user, id);
+        let Some(object) = retry::retry_all(3, |_| self.bucket.get(&path).execute()).await? else {
+            return Ok(None);
+        };
+
+        let Some(body) = object.body() else {
+            return Err(Error::StorageError(format!(
+                "collection {user}:{id} without body"
+            )));
+        };
+
+        Ok(Some(serde_json::from_slice(&body.bytes().await?)?))
+    }
+
+    /// Lists all collections of a user visible to the session, ordered by title.
+    #[tracing::instrument(skip(self))]
+    pub async fn list(&self, session: Session<'_>, user: &User) -> Result<Vec<Collection>> {
+        let is_owner = is_owner(session, user);
+        let mut collections = self
+            .all(user)
+            .await?
+            .into_iter()
+            .filter(|collection| is_owner || !collection.private)
+            .collect::<Vec<_>>();
+        collections.sort_unstable_by_key(|collection| collection.title.to_lowercase());
+
+        Ok(collections)
+    }
+
+    /// Loads all collections of a user, including private collections.
+    ///
+    /// The amount of collections is limited by [`consts::MAX_COLLECTIONS`] on save.
+    async fn all(&self, user: &User) -> Result<Vec<Collection>> {
+        let prefix = to_prefix(user);
+
+        let mut ids = Vec::new();
+        let mut cursor = None;
+        loop {
+            let objects = retry::retry_all(3, |_| {
+                let mut list = self.bucket.list().prefix(&prefix);
+                if let Some(cursor) = &cursor {
+                    list = list.cursor(String::clone(cursor));
+                }
+                list.execute()
+            })
+            .await?;
+
+            ids.extend(
+                objects
+                    .objects()
+                    .into_iter()
+                    .filter_map(|obj| obj.key().strip_prefix(&prefix)?.parse::<Id>().ok()),
+            );
+
+            cursor = objects.cursor();
+            if !objects.truncated() || cursor.is_none() {
+                break;
+            }
+        }
+
+        let collections =
+            futures::future::try_join_all(ids.map(|id| async move { self.get(user, &id).await }))
+                .await?;
+
+        Ok(collections.into_iter().flatten().collect())
+    }
+
+    /// Creates or replaces a collection.
+    #[tracing::instrument(skip(self))]
+    pub async fn put(&self, user: &User, collection: &Collection) -> Result<()> {
+        let path = to_path(user, &collection.id);
+        let data = serde_json::to_vec(collection)?;
+
+        retry::retry_all(3, |_| self.bucket.put(&path, data.clone()).execute()).await?;
+
+        Ok(())
+    }
+
+    /// Deletes a collection, deleting a collection which does not exist is not an error.
+    #[tracing::instrument(skip(self))]
+    pub async fn delete(&self, user: &User, id: &Id) -> Result<()> {
+        let path = to_path(user, id);
+        retry::retry_all(3, |_| self.bucket.delete(&path)).await?;
+        Ok(())
+    }
+
+    /// Replaces a renamed paste in all collections of the user.
+    ///
+    /// Returns the ids of all updated collections.
+    #[tracing::instrument(skip(self))]
+    pub async fn rename_paste(
+        &self,
+        session: Session<'_>,
+        user: &User,
+        from: &Id,
+        to: &Id,
+    ) -> Result<Vec<Id>> {
+        let mut updated = Vec::new();
+        for mut collection in self.list(session, user).await? {
+            if rename(&mut collection, from, to) {
+                self.put(user, &collection).await?;
+                updated.push(collection.id);
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Removes a paste from all collections of the user.
+    ///
+    /// Returns the ids of all updated collections.
+    #[tracing::instrument(skip(self))]
+    pub async fn remove_paste(&self, user: &User, id: &Id) -> Result<Vec<Id>> {
+        let mut updated = Vec::new();
+        for mut collection in self.all(user).await? {
+            if remove(&mut collection, id) {
+                self.put(user, &collection).await?;
+                updated.push(collection.id);
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Returns the collection with the summaries of all its pastes visible to the session.
+    ///
+    /// Private collections are only visible to their owner.
+    pub async fn page(
+        &self,
+        session: Session<'_>,
+        user: &User,
+        id: &Id,
+    ) -> Result<Option<CollectionPage>> {
+        let Some(collection) = self
+            .get(user, id)
+            .await?
+            .filter(|collection| !collection.private || is_owner(session, user))
+        else {
+            return Ok(None);
+        };
+
+        let pastes = self.pastes.list_summaries(session, user).await?;
+        let pastes = resolve(&collection, pastes);
+
+        Ok(Some(CollectionPage {
+            user: user.clone(),
+            collection,
+            pastes,
+        }))
+    }
+}
+
+/// Orders the summaries of pastes by their position in the collection.
+///
+/// Pastes which are not part of the collection or no longer exist are skipped.
+fn resolve(collection: &Collection, pastes: Vec<PasteSummary>) -> Vec<PasteSummary> {
+    let mut pastes = pastes
+        .into_iter()
+        .map(|paste| (paste.id.id().to_owned(), paste))
+        .collect::<HashMap<_, _>>();
+
+    collection
+        .pastes
+        .iter()
+        .filter_map(|id| pastes.remove(id.as_str()))
+        .collect()
+}
+
+/// Replaces all references to a paste, returns `true` if the collection changed.
+fn rename(collection: &mut Collection, from: &Id, to: &Id) -> bool {
+    let mut changed = false;
+    for id in collection.pastes.iter_mut().filter(|id| **id == *from) {
+        *id = to.clone();
+        changed = true;
+    }
+    changed
+}
+
+/// Removes all references to a paste, returns `true` if the collection changed.
+fn remove(collection: &mut Collection, id: &Id) -> bool {
+    let len = collection.pastes.len();
+    collection.pastes.retain(|paste| paste != id);
+    collection.pastes.len() != len
+}
+
+fn is_owner(session: Session<'_>, user: &User) -> bool {
+    session.map(|u| &u.name) == Some(user)
+}
+
+fn to_prefix(user: &User) -> String {
+    format!("users/{user}/collections/")
+}
+
+fn to_path(user: &User, id: &Id) -> String {
+    format!("{}{id}", to_prefix(user))
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{AscendancyOrClass, Class, PasteId, UserPasteId};
+
+    use super::*;
+
+    fn summary(id: &str) -> PasteSummary {
+        PasteSummary {
+            id: PasteId::UserPaste(UserPasteId {
+                user: User::new("foo"),
+                id: id.parse().unwrap(),
+            }),
+            title: id.to_owned(),
+            game_version: Default::default(),
+            ascendancy_or_class: AscendancyOrClass::Class(Class::Witch),
+            version: None,
+            main_skill_name: None,
+            last_modified: 0,
+            rank: None,
+            private: false,
+            tags: Vec::new(),
+            description: String::new(),
+        }
+    }
+
+    fn collection(pastes: &[&str]) -> Collection {
+        Collection {
+            id: "league".parse().unwrap(),
+            title: "League Starters".to_owned(),
+            description: String::new(),
+            private: false,
+            pastes: pastes.iter().map(|id| id.parse().unwrap()).collect(),
+            last_modified: 0,
+        }
+    }
+
+    #[test]
+    fn test_resolve_collection_order() {
+        let collection = collection(&["third", "first", "gone1"]);
+
+        let pastes = vec![summary("first"), summary("second"), summary("third")];
+        let resolved = resolve(&collection, pastes);
+
+        let ids = resolved.iter().map(|p| p.id.id()).collect::<Vec<_>>();
+        assert_eq!(ids, ["third", "first"]);
+    }
+
+    #[test]
+    fn test_rename_paste_in_collection() {
+        let mut collection = collection(&["first", "second"]);
+
+        let from = "second".parse().unwrap();
+        assert!(rename(&mut collection, &from, &"renamed".parse().unwrap()));
+        assert!(!rename(&mut collection, &from, &"other".parse().unwrap()));
+
+        let ids = collection
+            .pastes
+            .iter()
+            .map(|id| id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["first", "renamed"]);
+    }
+
+    #[test]
+    fn test_remove_paste_from_collection() {
+        let mut collection = collection(&["first", "second", "first"]);
+
+        let id = "first".parse().unwrap();
+        assert!(remove(&mut collection, &id));
+        assert!(!remove(&mut collection, &id));
+
+        let ids = collection
+            .pastes
+            .iter()
+            .map(|id| id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["second"]);
+    }
+}
@@ -7,6 +7,55 @@ const ONE_KB: usize = 1024;
 pub const MAX_UPLOAD_SIZE: usize = 75 * ONE_KB;
 pub const MAX_UPLOAD_SIZE_LOGGED_IN: usize = 150 * ONE_KB;
 
+/// Maximum amount of previous revisions stored per user paste.
+pub const MAX_PASTE_REVISIONS: usize = 50;
+
+/// Maximum amount of API tokens per user.
+pub const MAX_API_TOKENS: usize = 10;
+/// API tokens expire after one year, even if they were not revoked.
+pub const API_TOKEN_MAX_AGE: Duration = Duration::from_secs(365 * 24 * 3600);
+
+/// Maximum amount of collections per user.
+pub const MAX_COLLECTIONS: usize = 25;
+/// Maximum amount of pastes in a single collection.
+pub const MAX_COLLECTION_PASTES: usize = 100;
+/// Maximum length of a collection description.
+pub const MAX_COLLECTION_DESCRIPTION_LENGTH: usize = 1000;
+
+/// Maximum amount of webhooks per user.
+pub const MAX_WEBHOOKS: usize = 5;
+
+/// Maximum size of an uploaded export archive.
+pub const MAX_IMPORT_SIZE: usize = 10 * 1024 * ONE_KB;
+/// Maximum amount of pastes imported at once.
+pub const MAX_IMPORT_PASTES: usize = 500;
+
+/// Amount of pastes per page on a user's paste listing.
+pub const USER_PASTES_PAGE_SIZE: usize = 50;
+
+/// Maximum length of the reason given for reporting or taking down a paste.
+pub const MAX_MODERATION_REASON_LENGTH: usize = 500;
+
+/// An access of an anonymous paste is recorded at most once within this duration.
+pub const PASTE_ACCESS_INTERVAL: Duration = Duration::from_secs(24 * 3600); // 1 Day
+
+/// Bounds of the optional expiry of anonymous pastes, given in seconds on upload.
+pub const MIN_PASTE_EXPIRES_IN: Duration = Duration::from_secs(60);
+pub const MAX_PASTE_EXPIRES_IN: Duration = Duration::from_secs(365 * 24 * 3600);
+
+/// Amount of pastes processed by a metadata maintenance task per run.
+pub const MAINTENANCE_BATCH_SIZE: usize = 200;
+/// Amount of stored objects looked at per run when backfilling the search index,
+/// every indexed paste needs multiple writes.
+pub const MAINTENANCE_SEARCH_BATCH_SIZE: usize = 50;
+/// Amount of pastes which are requested when warming the cache.
+pub const MAINTENANCE_WARM_CACHE_LIMIT: usize = 25;
+
+/// Maximum amount of pastes returned by a single search.
+pub const MAX_SEARCH_RESULTS: usize = 50;
+/// Maximum amount of index entries looked at by a single search.
+pub const MAX_SEARCH_SCAN: usize = 2000;
+
 pub const KV_STATIC_CONTENT: &str = "__STATIC_CONTENT";
 
 pub const R2_STORAGE_BUCKET: &str = "STORAGE_BUCKET";
@@ -14,6 +63,24 @@ pub const R2_STORAGE_BUCKET: &str = "STORAGE_BUCKET";
 pub const ENV_SENTRY_PROJECT: &str = "SENTRY_PROJECT";
 pub const ENV_SENTRY_TOKEN: &str = "SENTRY_TOKEN";
 pub const ENV_SECRET_KEY: &str = "SECRET_KEY";
+/// Secret keys used for signing with rotation support, format: `id:secret,id:secret`, newest first.
+/// Takes precedence over [`ENV_SECRET_KEY`].
+pub const ENV_SECRET_KEYS: &str = "SECRET_KEYS";
+
+/// Comma separated list of PoE account names with admin privileges.
+pub const ENV_ADMINS: &str = "ADMINS";
+
+/// Anonymous pastes which were not accessed for this amount of days are expired.
+/// Idle pastes are never expired if not set.
+pub const ENV_PASTE_MAX_IDLE_DAYS: &str = "PASTE_MAX_IDLE_DAYS";
+/// The paste lifecycle job only reports expired pastes, unless this is set to `false`.
+pub const ENV_PASTE_EXPIRY_DRY_RUN: &str = "PASTE_EXPIRY_DRY_RUN";
+
+/// Comma separated list of maintenance tasks run by the scheduled event,
+/// see [`crate::maintenance::Task`].
+pub const ENV_MAINTENANCE_TASKS: &str = "MAINTENANCE_TASKS";
+/// Public URL of the site, e.g. `https://pobb.in`, required for warming caches.
+pub const ENV_PUBLIC_URL: &str = "PUBLIC_URL";
 
 pub const ENV_OAUTH_CLIENT_ID: &str = "OAUTH_CLIENT_ID";
 pub const ENV_OAUTH_CLIENT_SECRET: &str = "OAUTH_CLIENT_SECRET";
@@ -22,9 +89,17 @@ pub const ENV_STATS_URL: &str = "STATS_URL";
 pub const ENV_STATS_TOKEN: &str = "STATS_TOKEN";
 
 pub const OAUTH_SCOPE: &str = "account:profile";
+/// Scope of the login for a character import, see [`crate::characters`].
+pub const OAUTH_SCOPE_CHARACTERS: &str = "account:profile account:characters";
+/// Maximum age of the signed OAuth state cookie, matches the lifetime of the cookie.
+pub const OAUTH_STATE_MAX_AGE: Duration = Duration::from_secs(600);
 
 pub const CACHE_A_BIT: Duration = Duration::from_secs(21600); // 6 Hours
 pub const CACHE_FOREVER: Duration = Duration::from_secs(31536000);
+pub const CACHE_SEARCH: Duration = Duration::from_secs(300); // 5 Minutes
+pub const CACHE_LIST_PAGE: Duration = Duration::from_secs(60); // 1 Minute
+/// Session states are cached per data center, see [`crate::sessions`].
+pub const CACHE_SESSION_STATE: Duration = Duration::from_secs(60); // 1 Minute
 
 pub const CORS_POB_API: &[&str] = &["https://pob.cool"];
 pub const CORS_READ_API: &[&str] = &["https://pobarchives.com"];
@@ -15,6 +15,7 @@ pub enum DangerousError {
     Deserialize,
     Crypto,
     Expired,
+    UnknownKey,
 }
 
 impl fmt::Display for DangerousError {
@@ -26,6 +27,7 @@ impl fmt::Display for DangerousError {
             Self::Deserialize => write!(f, "Deserialization Error"),
             Self::Crypto => write!(f, "Crypto Error"),
             Self::Expired => write!(f, "Expired"),
+            Self::UnknownKey => write!(f, "Unknown Key"),
         }
     }
 }
@@ -34,12 +36,37 @@ impl std::error::Error for DangerousError {}
 
 type Result<T, E = DangerousError> = std::result::Result<T, E>;
 
-pub struct Dangerous {
+/// A signing key.
+#[derive(Debug)]
+struct Key {
+    id: String,
     secret: Vec<u8>,
 }
 
+/// Signs and verifies data.
+///
+/// Signed data has the format `{payload}.{timestamp}.{key id}.{signature}`,
+/// data signed before key rotation was supported has no key id.
+///
+/// Data is always signed with the newest key, verification uses the key identified
+/// by the key id or, if there is no key id, tries all keys.
+pub struct Dangerous {
+    /// Available keys, the newest key first.
+    keys: Vec<Key>,
+}
+
 impl FromEnv for Dangerous {
     fn from_env(env: &Env) -> Option<Self> {
+        if let Some(keys) = env.var(crate::consts::ENV_SECRET_KEYS) {
+            return match Self::parse_keys(&keys) {
+                Ok(dangerous) => Some(dangerous),
+                Err(err) => {
+                    tracing::error!("invalid secret keys: {err}");
+                    None
+                }
+            };
+        }
+
         let secret = env.var(crate::consts::ENV_SECRET_KEY)?;
         Some(Self::new(secret.into_bytes()))
     }
@@ -47,9 +74,55 @@ impl FromEnv for Dangerous {
 
 impl Dangerous {
     const SEP: char = '.';
+    /// Maximum length of a key id, timestamps are always longer,
+    /// which makes it possible to distinguish signatures with and without key id.
+    pub const MAX_KEY_ID_LEN: usize = 8;
+    const DEFAULT_KEY_ID: &'static str = "0";
 
     pub fn new(secret: Vec<u8>) -> Self {
-        Self { secret }
+        Self {
+            keys: vec![Key {
+                id: Self::DEFAULT_KEY_ID.to_owned(),
+                secret,
+            }],
+        }
+    }
+
+    /// Parses a list of keys in the format `id:secret,id:secret`, the newest key first.
+    fn parse_keys(keys: &str) -> Result<Self, &'static str> {
+        let keys = keys
+            .split(',')
+            .map(|key| {
+                let (id, secret) = key.trim().split_once(':').ok_or("missing key id")?;
+                let valid_id = !id.is_empty()
+                    && id.len() <= Self::MAX_KEY_ID_LEN
+                    && id.bytes().all(|b| b.is_ascii_alphanumeric());
+                if !valid_id {
+                    return Err("invalid key id");
+                }
+                if secret.is_empty() {
+                    return Err("empty secret");
+                }
+                Ok(Key {
+                    id: id.to_owned(),
+                    secret: secret.as_bytes().to_vec(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if keys.is_empty() {
+            return Err("no keys");
+        }
+
+        Ok(Self { keys })
+    }
+
+    fn newest(&self) -> &Key {
+        &self.keys[0]
+    }
+
+    fn key(&self, id: &str) -> Option<&Key> {
+        self.keys.iter().find(|key| key.id == id)
     }
 
     #[tracing::instrument(skip(self))]
@@ -59,11 +132,20 @@ impl Dangerous {
     {
         let now = worker::Date::now().as_millis();
 
-        let mut payload = serde_json::to_string(data).map_err(|_| DangerousError::Serialize)?;
+        let payload = serde_json::to_string(data).map_err(|_| DangerousError::Serialize)?;
+        self.sign_raw(&payload, &encode(&now.to_le_bytes())).await
+    }
+
+    async fn sign_raw(&self, payload: &str, timestamp: &str) -> Result<String> {
+        let key = self.newest();
+
+        let mut payload = payload.to_owned();
+        payload.push(Self::SEP);
+        payload.push_str(timestamp);
         payload.push(Self::SEP);
-        payload.push_str(&encode(&now.to_le_bytes()));
+        payload.push_str(&key.id);
 
-        let signature = crypto::sign_hmac_256(&self.secret, payload.as_bytes())
+        let signature = crypto::sign_hmac_256(&key.secret, payload.as_bytes())
             .await
             .map_err(|_| DangerousError::Crypto)?;
 
@@ -76,25 +158,51 @@ impl Dangerous {
 
     #[tracing::instrument(skip(self))]
     pub async fn verify<T: DeserializeOwned>(&self, data: &str, max_age: Duration) -> Result<T> {
-        let (payload, signature) = data
-            .rsplit_once(Self::SEP)
-            .ok_or(DangerousError::BadEncoding)?;
+        let signed = self.verify_raw(data, max_age).await?;
+        serde_json::from_str(signed.payload).map_err(|_| DangerousError::Deserialize)
+    }
 
-        let signature = decode(signature).map_err(|_| DangerousError::BadEncoding)?;
-        let verified = crypto::verify_hmac_256(&self.secret, &signature, payload.as_bytes())
-            .await
-            .map_err(|_| DangerousError::Crypto)?;
+    /// Whether the data was signed with an outdated key and should be re-signed.
+    ///
+    /// This does not verify the data.
+    pub fn is_outdated(&self, data: &str) -> bool {
+        Signed::parse(data).is_some_and(|signed| signed.key_id != Some(self.newest().id.as_str()))
+    }
+
+    /// Verifies the data and signs it again with the newest key.
+    ///
+    /// The original timestamp is kept, re-signing does not extend the lifetime.
+    #[tracing::instrument(skip(self))]
+    pub async fn resign(&self, data: &str, max_age: Duration) -> Result<String> {
+        let signed = self.verify_raw(data, max_age).await?;
+        self.sign_raw(signed.payload, signed.timestamp).await
+    }
+
+    async fn verify_raw<'a>(&self, data: &'a str, max_age: Duration) -> Result<Signed<'a>> {
+        let signed = Signed::parse(data).ok_or(DangerousError::BadEncoding)?;
+        let signature = decode(signed.signature).map_err(|_| DangerousError::BadEncoding)?;
+
+        let keys = match signed.key_id {
+            Some(id) => std::slice::from_ref(self.key(id).ok_or(DangerousError::UnknownKey)?),
+            None => &self.keys[..],
+        };
+
+        let mut verified = false;
+        for key in keys {
+            verified = crypto::verify_hmac_256(&key.secret, &signature, signed.signed.as_bytes())
+                .await
+                .map_err(|_| DangerousError::Crypto)?;
+            if verified {
+                break;
+            }
+        }
 
         if !verified {
             return Err(DangerousError::BadSignature);
         }
 
-        let (payload, timestamp) = payload
-            .rsplit_once(Self::SEP)
-            .ok_or(DangerousError::BadEncoding)?;
-
         let time = u64::from_le_bytes(
-            decode(timestamp)
+            decode(signed.timestamp)
                 .map_err(|_| DangerousError::BadEncoding)?
                 .try_into()
                 .map_err(|_| DangerousError::BadEncoding)?,
@@ -105,7 +213,40 @@ impl Dangerous {
             return Err(DangerousError::Expired);
         }
 
-        serde_json::from_str(payload).map_err(|_| DangerousError::Deserialize)
+        Ok(signed)
+    }
+}
+
+/// The parts of signed data.
+#[derive(Debug, PartialEq, Eq)]
+struct Signed<'a> {
+    /// The signed part of the data, everything but the signature.
+    signed: &'a str,
+    payload: &'a str,
+    timestamp: &'a str,
+    key_id: Option<&'a str>,
+    signature: &'a str,
+}
+
+impl<'a> Signed<'a> {
+    fn parse(data: &'a str) -> Option<Self> {
+        let (signed, signature) = data.rsplit_once(Dangerous::SEP)?;
+
+        let (rest, last) = signed.rsplit_once(Dangerous::SEP)?;
+        let (payload, timestamp, key_id) = if last.len() <= Dangerous::MAX_KEY_ID_LEN {
+            let (payload, timestamp) = rest.rsplit_once(Dangerous::SEP)?;
+            (payload, timestamp, Some(last))
+        } else {
+            (rest, last, None)
+        };
+
+        Some(Self {
+            signed,
+            payload,
+            timestamp,
+            key_id,
+            signature,
+        })
     }
 }
 
@@ -116,3 +257,61 @@ fn encode(b: &[u8]) -> String {
 fn decode(s: &str) -> Result<Vec<u8>, base64::DecodeError> {
     base64::decode_config(s, base64::URL_SAFE_NO_PAD)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_parse() {
+        let signed = Signed::parse(r#"{"a":"b.c"}.AAAAAAAAAAA.k1.sig"#).unwrap();
+        assert_eq!(
+            signed,
+            Signed {
+                signed: r#"{"a":"b.c"}.AAAAAAAAAAA.k1"#,
+                payload: r#"{"a":"b.c"}"#,
+                timestamp: "AAAAAAAAAAA",
+                key_id: Some("k1"),
+                signature: "sig",
+            }
+        );
+    }
+
+    #[test]
+    fn test_signed_parse_without_key_id() {
+        let signed = Signed::parse(r#"{"a":"b.c"}.AAAAAAAAAAA.sig"#).unwrap();
+        assert_eq!(
+            signed,
+            Signed {
+                signed: r#"{"a":"b.c"}.AAAAAAAAAAA"#,
+                payload: r#"{"a":"b.c"}"#,
+                timestamp: "AAAAAAAAAAA",
+                key_id: None,
+                signature: "sig",
+            }
+        );
+    }
+
+    #[test]
+    fn test_signed_parse_invalid() {
+        assert_eq!(Signed::parse("payload"), None);
+        assert_eq!(Signed::parse("payload.sig"), None);
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        let dangerous = Dangerous::parse_keys("new:secret2, old:secret1").unwrap();
+        assert_eq!(dangerous.newest().id, "new");
+        assert_eq!(dangerous.key("old").unwrap().secret, b"secret1");
+        assert!(dangerous.key("0").is_none());
+
+        assert!(dangerous.is_outdated("{}.AAAAAAAAAAA.old.sig"));
+        assert!(dangerous.is_outdated("{}.AAAAAAAAAAA.sig"));
+        assert!(!dangerous.is_outdated("{}.AAAAAAAAAAA.new.sig"));
+
+        assert!(Dangerous::parse_keys("").is_err());
+        assert!(Dangerous::parse_keys("secret").is_err());
+        assert!(Dangerous::parse_keys("toolongkeyid:secret").is_err());
+        assert!(Dangerous::parse_keys("k1:").is_err());
+    }
+}
@@ -11,6 +11,9 @@ pub enum Error {
     #[error("The requested '{0}' does not exist")]
     NotFound(&'static str, String),
 
+    #[error("The requested '{0}' has been removed")]
+    Gone(&'static str, String),
+
     #[error("Request failed {0}: {1}")]
     RemoteFailed(u16, String),
 
@@ -38,6 +41,9 @@ pub enum Error {
     #[error("Invalid Session State")]
     InvalidSessionState,
 
+    #[error("Too Many Requests, retry in {} seconds", retry_after_secs(.0))]
+    TooManyRequests(std::time::Duration),
+
     #[error(transparent)]
     PoEApiError(#[from] crate::poe_api::PoEApiError),
 
@@ -67,6 +73,7 @@ impl Error {
     pub fn name(&self) -> &'static str {
         match self {
             Self::NotFound(..) => "NotFound",
+            Self::Gone(..) => "Gone",
             Self::RemoteFailed(..) => "Remote Failed",
             Self::Serde(..) => "Serde",
             Self::Kv(..) => "Kv",
@@ -76,6 +83,7 @@ impl Error {
             Self::MissingAuthorizationGrant => "MissingAuthorizationGrant",
             Self::AuthorizationGrantError(..) => "AuthorizationGrantError",
             Self::InvalidSessionState => "InvalidSessionState",
+            Self::TooManyRequests(..) => "TooManyRequests",
             Self::InvalidPoB(..) => "InvalidPoB",
             Self::InvalidId(..) => "InvalidId",
             Self::Dangerous(..) => "DangerousError",
@@ -90,15 +98,18 @@ impl Error {
     pub fn status_code(&self) -> u16 {
         match self {
             Self::NotFound(..) | Self::InvalidId(..) => 404,
+            Self::Gone(..) => 410,
             Self::BadRequest(..) | Self::InvalidPoB(..) => 400,
             Self::AccessDenied
             | Self::MissingAuthorizationGrant
             | Self::AuthorizationGrantError(..)
             | Self::InvalidSessionState => 403,
+            Self::TooManyRequests(..) => 429,
             Self::Dangerous(err) => match err {
                 DangerousError::BadEncoding => 400,
                 DangerousError::BadSignature => 400,
                 DangerousError::Deserialize => 400,
+                DangerousError::UnknownKey => 400,
                 _ => 500,
             },
             _ => 500,
@@ -108,6 +119,7 @@ impl Error {
     pub fn level(&self) -> Level {
         match self {
             Self::NotFound(..) => Level::Info,
+            Self::Gone(..) => Level::Info,
             Self::RemoteFailed(..) => Level::Warning,
             Self::Serde(..) => Level::Error,
             Self::Kv(..) => Level::Error,
@@ -117,12 +129,14 @@ impl Error {
             Self::MissingAuthorizationGrant => Level::Warning,
             Self::AuthorizationGrantError(..) => Level::Warning,
             Self::InvalidSessionState => Level::Info,
+            Self::TooManyRequests(..) => Level::Info,
             Self::InvalidId(..) => Level::Info,
             Self::InvalidPoB(..) => Level::Error,
             Self::Dangerous(err) => match err {
                 DangerousError::BadEncoding => Level::Warning,
                 DangerousError::BadSignature => Level::Warning,
                 DangerousError::Deserialize => Level::Warning,
+                DangerousError::UnknownKey => Level::Warning,
                 _ => Level::Error,
             },
             Self::PoEApiError(..) => Level::Warning,
@@ -161,13 +175,25 @@ impl From<wasm_bindgen::JsValue> for Error {
 
 impl From<Error> for crate::Response {
     fn from(err: Error) -> Self {
-        crate::Response::status(err.status_code()).json(&ErrorResponse {
+        let response = crate::Response::status(err.status_code()).json(&ErrorResponse {
             code: err.status_code(),
             message: err.to_string(),
-        })
+        });
+
+        match err {
+            Error::TooManyRequests(retry_after) => {
+                response.header("Retry-After", &retry_after_secs(&retry_after).to_string())
+            }
+            _ => response,
+        }
     }
 }
 
+/// Seconds until a retry, rounded up, a `Retry-After` of 0 would mean retry immediately.
+fn retry_after_secs(retry_after: &std::time::Duration) -> u64 {
+    retry_after.as_millis().div_ceil(1000).max(1) as u64
+}
+
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
     pub code: u16,
@@ -0,0 +1,206 @@
+//! Export and import of all pastes of a user.
+//!
+//! The export is a tar archive containing a `manifest.json` and for every paste
+//! the raw export code as `<id>.txt` and the decoded build as `<id>.xml`.
+//! The import only reads the manifest and the export codes.
+use std::rc::Rc;
+
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use shared::{model::PasteMetadata, PasteId, User, UserPasteId};
+
+use crate::{archive, storage::Storage, Error, Response, Result};
+
+const MANIFEST: &str = "manifest.json";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExportManifest {
+    pub user: User,
+    /// Time of the export in milliseconds.
+    pub exported: u64,
+    pub pastes: Vec<ExportedPaste>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExportedPaste {
+    pub id: String,
+    pub metadata: PasteMetadata,
+    pub last_modified: u64,
+}
+
+/// A paste read from an export archive.
+#[derive(Debug)]
+pub struct ImportedPaste {
+    pub id: String,
+    pub metadata: PasteMetadata,
+    pub content: String,
+}
+
+/// Streams all pastes of a user as a tar archive.
+///
+/// Pastes are fetched one by one while the archive is streamed to the client.
+pub async fn export(storage: Storage, user: User) -> Result<Response> {
+    let pastes = storage.list(&user).await?;
+    let now = worker::Date::now().as_millis();
+
+    let manifest = ExportManifest {
+        user: user.clone(),
+        exported: now,
+        pastes: pastes
+            .into_iter()
+            .map(|paste| ExportedPaste {
+                id: paste.name,
+                metadata: paste.metadata,
+                last_modified: paste.last_modified,
+            })
+            .collect(),
+    };
+
+    let ids = manifest
+        .pastes
+        .iter()
+        .map(|paste| (paste.id.clone(), paste.last_modified))
+        .collect::<Vec<_>>();
+    let manifest = archive::entry(MANIFEST, &serde_json::to_vec(&manifest)?, now / 1000)?;
+
+    let filename = format!("pobbin-{user}.tar");
+
+    let storage = Rc::new(storage);
+    let entries = stream::iter(ids).then(move |(id, last_modified)| {
+        let storage = Rc::clone(&storage);
+        let user = user.clone();
+        async move {
+            // Once streaming started there is no way to return an error response,
+            // the error aborts the stream and the client ends up with a truncated archive.
+            export_paste(&storage, user, id, last_modified)
+                .await
+                .inspect_err(|err| tracing::error!("failed to export paste: {err:?}"))
+                .map_err(|err| worker::Error::RustError(err.to_string()))
+        }
+    });
+
+    let body = stream::once(async move { Ok::<_, worker::Error>(manifest) })
+        .chain(entries)
+        .chain(stream::once(async { Ok(archive::finish()) }));
+
+    let response = worker::Response::from_stream(body)?;
+    Ok(Response::from(response)
+        .content_type("application/x-tar")
+        .header(
+            "Content-Disposition",
+            &format!("attachment; filename=\"{filename}\""),
+        ))
+}
+
+async fn export_paste(
+    storage: &Storage,
+    user: User,
+    id: String,
+    last_modified: u64,
+) -> Result<Vec<u8>> {
+    let paste_id = PasteId::UserPaste(UserPasteId {
+        user,
+        id: id.parse()?,
+    });
+
+    // The paste may have been deleted since listing, it is simply skipped.
+    let Some(paste) = storage.get(&paste_id).await? else {
+        return Ok(Vec::new());
+    };
+
+    let mtime = last_modified / 1000;
+    let mut entries = archive::entry(&format!("{id}.txt"), paste.content.as_bytes(), mtime)?;
+    match pob::decompress(&paste.content) {
+        Ok(xml) => entries.extend(archive::entry(&format!("{id}.xml"), xml.as_bytes(), mtime)?),
+        Err(err) => tracing::warn!("failed to decompress paste {paste_id}: {err:?}"),
+    }
+
+    Ok(entries)
+}
+
+/// Reads all pastes from an export archive.
+///
+/// Pastes listed in the manifest without a matching export code are ignored.
+pub fn read_import(data: &[u8]) -> Result<Vec<ImportedPaste>> {
+    let entries = archive::entries(data)?;
+
+    let manifest = entries
+        .iter()
+        .find(|entry| entry.name == MANIFEST)
+        .ok_or_else(|| Error::BadRequest("Invalid archive: missing manifest".to_owned()))?;
+    let manifest: ExportManifest = serde_json::from_slice(manifest.data)
+        .map_err(|err| Error::BadRequest(format!("Invalid archive: invalid manifest: {err}")))?;
+
+    let mut pastes = Vec::with_capacity(manifest.pastes.len());
+    for paste in manifest.pastes {
+        let name = format!("{}.txt", paste.id);
+        let Some(entry) = entries.iter().find(|entry| entry.name == name) else {
+            tracing::warn!("missing export code for paste {}", paste.id);
+            continue;
+        };
+
+        let content = std::str::from_utf8(entry.data)
+            .map_err(|_| Error::BadRequest(format!("Invalid archive: invalid paste {name}")))?;
+
+        pastes.push(ImportedPaste {
+            id: paste.id,
+            metadata: paste.metadata,
+            content: content.trim().to_owned(),
+        });
+    }
+
+    Ok(pastes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(title: &str) -> PasteMetadata {
+        serde_json::from_value(serde_json::json!({
+            "title": title,
+            "ascendancy_or_class": "Scion",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_read_import_archive() {
+        let manifest = ExportManifest {
+            user: User::new("foo"),
+            exported: 0,
+            pastes: vec![
+                ExportedPaste {
+                    id: "first".to_owned(),
+                    metadata: metadata("First Build"),
+                    last_modified: 1,
+                },
+                ExportedPaste {
+                    id: "missing".to_owned(),
+                    metadata: metadata("Missing Build"),
+                    last_modified: 2,
+                },
+            ],
+        };
+
+        let mut data = Vec::new();
+        data.extend(archive::entry(MANIFEST, &serde_json::to_vec(&manifest).unwrap(), 0).unwrap());
+        data.extend(archive::entry("first.txt", b"code\n", 0).unwrap());
+        data.extend(archive::entry("first.xml", b"<xml/>", 0).unwrap());
+        data.extend(archive::finish());
+
+        let pastes = read_import(&data).unwrap();
+        assert_eq!(pastes.len(), 1);
+        assert_eq!(pastes[0].id, "first");
+        assert_eq!(pastes[0].metadata.title, "First Build");
+        assert_eq!(pastes[0].content, "code");
+    }
+
+    #[test]
+    fn test_read_import_without_manifest() {
+        let mut data = archive::entry("first.txt", b"code", 0).unwrap();
+        data.extend(archive::finish());
+
+        assert!(read_import(&data).is_err());
+    }
+}
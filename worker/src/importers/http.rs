@@ -0,0 +1,17 @@
+use super::Fetcher;
+use crate::{net, Error, Result};
+
+/// Fetches builds over HTTP.
+pub struct HttpFetcher;
+
+impl Fetcher for HttpFetcher {
+    async fn get(&self, url: &str, tag: &'static str) -> Result<Option<String>> {
+        let mut response = net::Request::get(url).tag(tag).send().await?;
+
+        match response.status_code() {
+            200 => Ok(Some(response.text().await?)),
+            404 => Ok(None),
+            code => Err(Error::RemoteFailed(code, format!("{tag} get failed"))),
+        }
+    }
+}
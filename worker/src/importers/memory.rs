@@ -0,0 +1,32 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use super::Fetcher;
+use crate::Result;
+
+/// Fetcher with canned responses, used for tests.
+///
+/// Unknown URLs do not exist.
+#[derive(Default)]
+pub struct MemoryFetcher {
+    responses: HashMap<String, String>,
+    requests: RefCell<Vec<(String, &'static str)>>,
+}
+
+impl MemoryFetcher {
+    pub fn with(mut self, url: &str, content: &str) -> Self {
+        self.responses.insert(url.to_owned(), content.to_owned());
+        self
+    }
+
+    /// All requested URLs with their tags.
+    pub fn requests(&self) -> Vec<(String, &'static str)> {
+        self.requests.borrow().clone()
+    }
+}
+
+impl Fetcher for MemoryFetcher {
+    async fn get(&self, url: &str, tag: &'static str) -> Result<Option<String>> {
+        self.requests.borrow_mut().push((url.to_owned(), tag));
+        Ok(self.responses.get(url).cloned())
+    }
+}
@@ -0,0 +1,316 @@
+//! Imports builds from other build sharing sites.
+//!
+//! Every supported site is a [`Source`], which maps the URL of a shared build
+//! to the URL of its raw Path of Building code. The code is fetched through a [`Fetcher`]
+//! and stored as a regular paste.
+use shared::PasteId;
+use url::Url;
+
+use crate::{
+    request_context::{Env, FromEnv},
+    Error, Result,
+};
+
+mod http;
+#[cfg(test)]
+mod memory;
+
+pub use self::http::HttpFetcher;
+
+/// Fetches the raw code of a build.
+pub trait Fetcher {
+    /// Fetches the document at `url`, returns `None` if it does not exist.
+    async fn get(&self, url: &str, tag: &'static str) -> Result<Option<String>>;
+}
+
+/// A site builds can be imported from.
+pub trait Source {
+    /// Short name of the site, used for logging and metrics.
+    fn name(&self) -> &'static str;
+
+    /// Returns the URL of the raw build code, `None` if the URL does not belong to the site.
+    fn raw_url(&self, url: &Url) -> Option<String>;
+}
+
+/// Paste sites which serve the code of `pattern` at `raw`.
+///
+/// `{id}` in the patterns is replaced with the id of the paste.
+/// Raw URLs are accepted as well.
+struct PasteSite {
+    name: &'static str,
+    host: &'static str,
+    pattern: &'static str,
+    raw: &'static str,
+}
+
+impl Source for PasteSite {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn raw_url(&self, url: &Url) -> Option<String> {
+        let host = url.host_str()?;
+        if host.strip_prefix("www.").unwrap_or(host) != self.host {
+            return None;
+        }
+
+        let raw_path = self.raw.split_once(self.host)?.1;
+        let id = capture(self.pattern, url.path()).or_else(|| capture(raw_path, url.path()))?;
+
+        Some(self.raw.replace("{id}", id))
+    }
+}
+
+/// GitHub gists, the raw code of the latest revision is imported.
+struct Gist;
+
+impl Source for Gist {
+    fn name(&self) -> &'static str {
+        "gist"
+    }
+
+    fn raw_url(&self, url: &Url) -> Option<String> {
+        let segments = url.path_segments()?.collect::<Vec<_>>();
+
+        match (url.host_str()?, &segments[..]) {
+            ("gist.github.com", [user, id]) if is_id(user) && is_id(id) => Some(format!(
+                "https://gist.githubusercontent.com/{user}/{id}/raw"
+            )),
+            ("gist.githubusercontent.com", [user, id, "raw", ..]) if is_id(user) && is_id(id) => {
+                Some(url.to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+const PASTEBIN: PasteSite = PasteSite {
+    name: "pastebin",
+    host: "pastebin.com",
+    pattern: "/{id}",
+    raw: "https://pastebin.com/raw/{id}",
+};
+
+/// All supported sites, their hosts are listed in [`app::consts::IMPORT_HOSTS`].
+const SOURCES: &[&dyn Source] = &[
+    &PASTEBIN,
+    &PasteSite {
+        name: "poe.ninja",
+        host: "poe.ninja",
+        pattern: "/pob/{id}",
+        raw: "https://poe.ninja/pob/raw/{id}",
+    },
+    &PasteSite {
+        name: "pastebinp",
+        host: "pastebinp.com",
+        pattern: "/{id}",
+        raw: "https://pastebinp.com/raw/{id}",
+    },
+    &PasteSite {
+        name: "rentry",
+        host: "rentry.co",
+        pattern: "/{id}",
+        raw: "https://rentry.co/paste/{id}/raw",
+    },
+    &PasteSite {
+        name: "poedb",
+        host: "poedb.tw",
+        pattern: "/pob/{id}",
+        raw: "https://poedb.tw/pob/{id}/raw",
+    },
+    &Gist,
+];
+
+/// Finds the source of a build URL, returns the source and the URL of the raw code.
+pub fn find(url: &str) -> Option<(&'static dyn Source, String)> {
+    let url = Url::parse(url.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    SOURCES
+        .iter()
+        .find_map(|source| Some((*source, source.raw_url(&url)?)))
+}
+
+/// Whether a paste id could be a pastebin.com paste.
+///
+/// These ids are transparently fetched from pastebin.com, see [`Importer::pastebin`].
+pub fn could_be_pastebin_id(paste: &PasteId) -> bool {
+    paste.user().is_none() && paste.id().len() == 8
+}
+
+/// A build code fetched from a [`Source`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Import {
+    pub source: &'static str,
+    pub content: String,
+}
+
+pub struct Importer<F = HttpFetcher> {
+    fetcher: F,
+}
+
+impl FromEnv for Importer {
+    fn from_env(_env: &Env) -> Option<Self> {
+        Some(Self::new(HttpFetcher))
+    }
+}
+
+impl<F: Fetcher> Importer<F> {
+    pub fn new(fetcher: F) -> Self {
+        Self { fetcher }
+    }
+
+    /// Fetches the build code shared at `url`.
+    ///
+    /// The code is only checked for being plausible, it still needs to be validated
+    /// before it is stored.
+    #[tracing::instrument(skip(self))]
+    pub async fn import(&self, url: &str) -> Result<Import> {
+        let (source, raw_url) =
+            find(url).ok_or_else(|| Error::BadRequest("Unsupported build URL".to_owned()))?;
+
+        tracing::info!(source = source.name(), raw_url, "importing build");
+        let content = self
+            .fetcher
+            .get(&raw_url, source.name())
+            .await?
+            .ok_or_else(|| Error::NotFound("build", url.to_owned()))?;
+
+        let content = content.trim();
+        if content.is_empty() || content.contains(char::is_whitespace) {
+            return Err(Error::BadRequest(format!(
+                "{} did not return a build",
+                source.name()
+            )));
+        }
+
+        Ok(Import {
+            source: source.name(),
+            content: content.to_owned(),
+        })
+    }
+
+    /// Fetches the content of a pastebin.com paste, see [`could_be_pastebin_id`].
+    pub async fn pastebin(&self, id: &PasteId) -> Result<Option<String>> {
+        let raw_url = PASTEBIN.raw.replace("{id}", id.id());
+        self.fetcher.get(&raw_url, PASTEBIN.name).await
+    }
+}
+
+/// Extracts the id from `path` matching a `pattern` with an `{id}` placeholder.
+fn capture<'a>(pattern: &str, path: &'a str) -> Option<&'a str> {
+    let (prefix, suffix) = pattern.split_once("{id}")?;
+    let path = path.strip_suffix('/').unwrap_or(path);
+    let id = path.strip_prefix(prefix)?.strip_suffix(suffix)?;
+
+    Some(id).filter(|id| is_id(id))
+}
+
+fn is_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn raw_url(url: &str) -> Option<(&'static str, String)> {
+        find(url).map(|(source, raw_url)| (source.name(), raw_url))
+    }
+
+    #[test]
+    fn test_find_sources() {
+        let pastebin = Some(("pastebin", "https://pastebin.com/raw/aBcD1234".to_owned()));
+        assert_eq!(raw_url("https://pastebin.com/aBcD1234"), pastebin);
+        assert_eq!(raw_url(" https://pastebin.com/raw/aBcD1234/ "), pastebin);
+        assert_eq!(raw_url("http://www.pastebin.com/aBcD1234"), pastebin);
+
+        assert_eq!(
+            raw_url("https://poe.ninja/pob/1a2B"),
+            Some(("poe.ninja", "https://poe.ninja/pob/raw/1a2B".to_owned()))
+        );
+        assert_eq!(
+            raw_url("https://rentry.co/paste/abc/raw"),
+            Some(("rentry", "https://rentry.co/paste/abc/raw".to_owned()))
+        );
+        assert_eq!(
+            raw_url("https://poedb.tw/pob/x_y-z"),
+            Some(("poedb", "https://poedb.tw/pob/x_y-z/raw".to_owned()))
+        );
+        assert_eq!(
+            raw_url("https://gist.github.com/someone/0123abcd"),
+            Some((
+                "gist",
+                "https://gist.githubusercontent.com/someone/0123abcd/raw".to_owned()
+            ))
+        );
+        let gist_raw = "https://gist.githubusercontent.com/someone/0123abcd/raw/f00/build.txt";
+        assert_eq!(raw_url(gist_raw), Some(("gist", gist_raw.to_owned())));
+
+        assert_eq!(raw_url("https://pastebin.com/"), None);
+        assert_eq!(raw_url("https://pastebin.com/u/someone/"), None);
+        assert_eq!(raw_url("https://poe.ninja/builds/ssf"), None);
+        assert_eq!(raw_url("https://gist.github.com/someone"), None);
+        assert_eq!(raw_url("https://example.com/aBcD1234"), None);
+        assert_eq!(raw_url("ftp://pastebin.com/aBcD1234"), None);
+        assert_eq!(raw_url("aBcD1234"), None);
+    }
+
+    #[test]
+    fn test_import_fetches_build() {
+        let fetcher = memory::MemoryFetcher::default()
+            .with("https://poe.ninja/pob/raw/1a2B", "\n eNrtXW1z2j \n")
+            .with(
+                "https://pastebin.com/raw/notabuil",
+                "<html>not a build</html>",
+            );
+        let importer = Importer::new(fetcher);
+
+        let import = block_on(importer.import("https://poe.ninja/pob/1a2B")).unwrap();
+        assert_eq!(
+            import,
+            Import {
+                source: "poe.ninja",
+                content: "eNrtXW1z2j".to_owned(),
+            }
+        );
+        assert_eq!(
+            importer.fetcher.requests(),
+            vec![("https://poe.ninja/pob/raw/1a2B".to_owned(), "poe.ninja")]
+        );
+
+        assert!(matches!(
+            block_on(importer.import("https://pastebin.com/notabuil")),
+            Err(Error::BadRequest(_))
+        ));
+        assert!(matches!(
+            block_on(importer.import("https://pastebin.com/missing1")),
+            Err(Error::NotFound("build", _))
+        ));
+        assert!(matches!(
+            block_on(importer.import("https://example.com/")),
+            Err(Error::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_pastebin_ids() {
+        let fetcher =
+            memory::MemoryFetcher::default().with("https://pastebin.com/raw/aBcD1234", "x");
+        let importer = Importer::new(fetcher);
+
+        let id = PasteId::Paste("aBcD1234".parse().unwrap());
+        assert!(could_be_pastebin_id(&id));
+        assert_eq!(
+            block_on(importer.pastebin(&id)).unwrap().as_deref(),
+            Some("x")
+        );
+    }
+}
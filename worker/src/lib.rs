@@ -1,28 +1,45 @@
 use sentry::WithSentry;
 use statsd::Counters;
-use worker::{event, Context, Env, Request, Response as WorkerResponse};
+use worker::{
+    event, Context, Env, Request, Response as WorkerResponse, ScheduleContext, ScheduledEvent,
+};
 
 mod api;
 mod app;
+mod archive;
 mod assets;
 mod cache;
+mod characters;
+mod collections;
 mod consts;
 mod crypto;
 mod dangerous;
 mod error;
+mod export;
+mod importers;
 mod layer;
+mod lifecycle;
+mod maintenance;
+mod moderation;
 mod net;
 mod pastes;
 mod poe_api;
+mod preview;
+mod rate_limit;
 mod request_context;
 mod response;
 mod retry;
 mod route;
+mod search;
 mod sentry_impl;
+mod sessions;
+mod shares;
 mod stats;
 mod statsd;
 mod storage;
+mod tokens;
 mod utils;
+mod webhooks;
 
 mod app_metadata {
     include!(concat!(env!("OUT_DIR"), "/app_metadata.rs"));
@@ -36,8 +53,7 @@ pub use self::response::Response;
 
 static LOG_INIT: std::sync::Once = std::sync::Once::new();
 
-#[event(fetch)]
-pub async fn main(req: Request, env: Env, ctx: Context) -> worker::Result<WorkerResponse> {
+fn init_log() {
     LOG_INIT.call_once(|| {
         use tracing_subscriber::prelude::*;
         tracing_subscriber::registry()
@@ -45,6 +61,11 @@ pub async fn main(req: Request, env: Env, ctx: Context) -> worker::Result<Worker
             .with(layer::Layer {})
             .init();
     });
+}
+
+#[event(fetch)]
+pub async fn main(req: Request, env: Env, ctx: Context) -> worker::Result<WorkerResponse> {
+    init_log();
 
     let mut rctx = RequestContext::new(req, env, ctx).await;
     let mut sentry = sentry::new(sentry_impl::Transport(rctx.owned_ctx()), rctx.inject_opt());
@@ -59,6 +80,11 @@ pub async fn main(req: Request, env: Env, ctx: Context) -> worker::Result<Worker
         });
 
     let response = cached(&mut rctx).with_sentry(&sentry).await;
+    // Applied after caching, the renewed session must never end up in the cache.
+    let response = match rctx.renewed_session() {
+        Some(session) => response.new_session(session),
+        None => response,
+    };
 
     sentry.update_transaction(sentry::Status::from(response.status_code()));
     if response.is_skip_sentry() {
@@ -70,6 +96,23 @@ pub async fn main(req: Request, env: Env, ctx: Context) -> worker::Result<Worker
     Ok(worker::Response::from(response))
 }
 
+#[event(scheduled)]
+pub async fn scheduled(_event: ScheduledEvent, env: Env, ctx: ScheduleContext) {
+    init_log();
+
+    let env = request_context::Env::new(env);
+    let options = <sentry::Options as request_context::FromEnv>::from_env(&env);
+    let sentry = sentry::new(sentry_impl::Transport(ctx), options);
+    sentry.start_transaction(sentry::TransactionContext {
+        op: "schedule".to_owned(),
+        name: "maintenance".to_owned(),
+    });
+
+    maintenance::run(&env, worker::Date::now().as_millis())
+        .with_sentry(&sentry)
+        .await;
+}
+
 #[tracing::instrument(skip_all)]
 async fn cached(rctx: &mut RequestContext) -> Response {
     sentry::counter(Counters::Request)
@@ -83,6 +126,7 @@ async fn cached(rctx: &mut RequestContext) -> Response {
             .inc(1)
             .tag("status", "hit")
             .tag("transaction", rctx.transaction());
+        lifecycle::on_paste_access(rctx, &response);
         return response;
     }
 
@@ -92,6 +136,7 @@ async fn cached(rctx: &mut RequestContext) -> Response {
         .tag("transaction", rctx.transaction());
 
     let response = cors(rctx).await;
+    lifecycle::on_paste_access(rctx, &response);
 
     rctx.cache_entry().store(response).await
 }
@@ -123,6 +168,10 @@ async fn cors(rctx: &mut RequestContext) -> Response {
 
 #[tracing::instrument(skip_all)]
 async fn handle(rctx: &mut RequestContext) -> Response {
+    if let Err(err) = rate_limit::check(rctx).await {
+        return err.into();
+    }
+
     let response = match rctx.route() {
         route::Route::Api(route) => api::handle(rctx, route.clone()).await,
         route::Route::App(route) => app::handle(rctx, route.clone()).await,
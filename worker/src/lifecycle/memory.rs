@@ -0,0 +1,78 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use shared::PasteId;
+
+use super::LifecycleStore;
+use crate::{storage::AnonymousPaste, Result};
+
+/// In memory lifecycle store, used for tests.
+///
+/// The cursor is the id of the last paste in the previous batch.
+pub struct MemoryStore {
+    pastes: RefCell<Vec<AnonymousPaste>>,
+    access: HashMap<String, u64>,
+    /// Amount of access lookups.
+    pub lookups: Cell<usize>,
+}
+
+impl MemoryStore {
+    pub fn new(pastes: Vec<(AnonymousPaste, Option<u64>)>) -> Self {
+        let access = pastes
+            .iter()
+            .filter_map(|(paste, accessed)| Some((paste.id.to_string(), (*accessed)?)))
+            .collect();
+        let mut pastes = pastes
+            .into_iter()
+            .map(|(paste, _)| paste)
+            .collect::<Vec<_>>();
+        pastes.sort_unstable_by_key(|paste| paste.id.to_string());
+
+        Self {
+            pastes: RefCell::new(pastes),
+            access,
+            lookups: Cell::new(0),
+        }
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.pastes
+            .borrow()
+            .iter()
+            .map(|paste| paste.id.to_string())
+            .collect()
+    }
+}
+
+impl LifecycleStore for MemoryStore {
+    async fn anonymous_pastes(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<AnonymousPaste>, Option<String>)> {
+        let pastes = self.pastes.borrow();
+        let mut remaining = pastes
+            .iter()
+            .filter(|paste| cursor.as_ref().is_none_or(|c| paste.id.to_string() > *c));
+
+        let batch = remaining.by_ref().take(limit).cloned().collect::<Vec<_>>();
+        let cursor = match remaining.next() {
+            Some(_) => batch.last().map(|paste| paste.id.to_string()),
+            None => None,
+        };
+
+        Ok((batch, cursor))
+    }
+
+    async fn last_access(&self, id: &PasteId) -> Result<Option<u64>> {
+        self.lookups.set(self.lookups.get() + 1);
+        Ok(self.access.get(&id.to_string()).copied())
+    }
+
+    async fn expire(&self, id: &PasteId) -> Result<()> {
+        self.pastes.borrow_mut().retain(|paste| &paste.id != id);
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+//! Lifecycle of anonymous pastes.
+//!
+//! Anonymous pastes are expired when they reach their explicit expiry time,
+//! given with `expires_in` on upload, or when they have not been accessed
+//! for a configurable period.
+//!
+//! Pastes are processed in batches, every run continues from the cursor of the previous run.
+//! Accesses are recorded at most once per [`consts::PASTE_ACCESS_INTERVAL`].
+use std::time::Duration;
+
+use serde::Serialize;
+use shared::PasteId;
+
+use crate::{
+    consts,
+    request_context::{Env, FromEnv, RequestContext},
+    route::{Api, GetEndpoints, Route},
+    storage::{AnonymousPaste, Storage},
+    utils::LenientId,
+    Result,
+};
+
+#[cfg(test)]
+mod memory;
+mod r2;
+
+pub use self::r2::R2LifecycleStore;
+
+/// Backend of the lifecycle job.
+pub trait LifecycleStore {
+    /// Lists the next batch of anonymous pastes, starting at `cursor`.
+    ///
+    /// Returns the cursor of the next batch, `None` once all pastes were listed.
+    async fn anonymous_pastes(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<AnonymousPaste>, Option<String>)>;
+
+    /// Returns the last recorded access of a paste.
+    async fn last_access(&self, id: &PasteId) -> Result<Option<u64>>;
+
+    /// Permanently deletes an expired paste.
+    async fn expire(&self, id: &PasteId) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LifecycleOptions {
+    /// Anonymous pastes not accessed for this duration are expired,
+    /// `None` only expires pastes with an explicit expiry time.
+    pub max_idle: Option<Duration>,
+    /// Only report the expired pastes, without deleting them.
+    pub dry_run: bool,
+    /// Amount of pastes processed per run.
+    pub batch_size: usize,
+}
+
+impl LifecycleOptions {
+    /// Reads the options from the environment.
+    ///
+    /// The job runs in dry-run mode, unless explicitly disabled.
+    pub fn from_env(env: &Env) -> Self {
+        let max_idle = env
+            .var(consts::ENV_PASTE_MAX_IDLE_DAYS)
+            .and_then(|days| days.parse::<u64>().ok())
+            .map(|days| Duration::from_secs(days * 24 * 3600));
+        let dry_run = env.var(consts::ENV_PASTE_EXPIRY_DRY_RUN).as_deref() != Some("false");
+
+        Self {
+            max_idle,
+            dry_run,
+            batch_size: consts::MAINTENANCE_BATCH_SIZE,
+        }
+    }
+}
+
+/// Result of a lifecycle run.
+#[derive(Debug, Default, Serialize)]
+pub struct LifecycleReport {
+    pub dry_run: bool,
+    /// Amount of anonymous pastes checked.
+    pub scanned: usize,
+    /// Pastes which were expired, or would have been expired in dry-run mode.
+    pub expired: Vec<String>,
+    /// Amount of pastes which failed to be checked or deleted.
+    pub failed: usize,
+    /// Cursor of the next batch, `None` once all pastes were processed.
+    pub cursor: Option<String>,
+}
+
+pub struct Lifecycle<S = R2LifecycleStore> {
+    store: S,
+}
+
+impl FromEnv for Lifecycle {
+    fn from_env(env: &Env) -> Option<Self> {
+        Some(Self::new(R2LifecycleStore::from_env(env)?))
+    }
+}
+
+impl<S: LifecycleStore> Lifecycle<S> {
+    pub fn new(store: S) -> Self {
+        Self { store }
+    }
+
+    /// Expires the anonymous pastes of the batch starting at `cursor` which are expired at `now`.
+    #[tracing::instrument(skip(self))]
+    pub async fn run(
+        &self,
+        options: LifecycleOptions,
+        cursor: Option<String>,
+        now: u64,
+    ) -> Result<LifecycleReport> {
+        let (pastes, cursor) = self
+            .store
+            .anonymous_pastes(cursor, options.batch_size)
+            .await?;
+
+        let mut report = LifecycleReport {
+            dry_run: options.dry_run,
+            scanned: pastes.len(),
+            cursor,
+            ..Default::default()
+        };
+
+        for paste in pastes {
+            match self.is_expired(&paste, options.max_idle, now).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(err) => {
+                    tracing::error!("failed to check paste {}: {err:?}", paste.id);
+                    report.failed += 1;
+                    continue;
+                }
+            }
+
+            if !options.dry_run {
+                if let Err(err) = self.store.expire(&paste.id).await {
+                    tracing::error!("failed to expire paste {}: {err:?}", paste.id);
+                    report.failed += 1;
+                    continue;
+                }
+            }
+
+            report.expired.push(paste.id.to_string());
+        }
+
+        tracing::info!(
+            dry_run = report.dry_run,
+            scanned = report.scanned,
+            expired = report.expired.len(),
+            failed = report.failed,
+            "paste lifecycle finished"
+        );
+
+        Ok(report)
+    }
+
+    /// Checks whether a paste is expired, the last access is only looked up
+    /// for pastes which would be expired without an access.
+    async fn is_expired(
+        &self,
+        paste: &AnonymousPaste,
+        max_idle: Option<Duration>,
+        now: u64,
+    ) -> Result<bool> {
+        if !is_expired(paste, None, max_idle, now) {
+            return Ok(false);
+        }
+        if paste.expires.is_some_and(|expires| expires <= now) {
+            return Ok(true);
+        }
+
+        let accessed = self.store.last_access(&paste.id).await?;
+        Ok(is_expired(paste, accessed, max_idle, now))
+    }
+}
+
+/// Records the access of an anonymous paste in the background.
+///
+/// Called for every successful response, cached responses are accesses as well.
+/// Every route serving the content of the paste, or a representation of it, is an access.
+///
+/// Accesses are deduplicated per data center through the cache, the access is only
+/// written once per [`consts::PASTE_ACCESS_INTERVAL`], see [`Storage::touch`].
+pub fn on_paste_access(rctx: &RequestContext, response: &crate::Response) {
+    if !response.is_2xx() {
+        return;
+    }
+
+    let id = match rctx.route() {
+        Route::App(app::Route::Paste(id) | app::Route::Embed(id))
+        | Route::Api(Api::Get(
+            GetEndpoints::Paste(id)
+            | GetEndpoints::PasteJson(id)
+            | GetEndpoints::PasteXml(id)
+            | GetEndpoints::PasteMarkdown(id)
+            | GetEndpoints::PasteBbCode(id)
+            | GetEndpoints::PasteBuildSummary(id)
+            | GetEndpoints::PastePreview(id),
+        )) => PasteId::Paste(id.clone()),
+        Route::Api(Api::Get(GetEndpoints::PobPaste(LenientId(id @ PasteId::Paste(_))))) => {
+            id.clone()
+        }
+        _ => return,
+    };
+
+    let storage = rctx.inject::<Storage>();
+    rctx.ctx().wait_until(async move {
+        let cache = worker::Cache::open("access".to_owned()).await;
+        let url = format!("https://access.pobb.in/{id}");
+        if let Ok(Some(_)) = cache.get(url.as_str(), true).await {
+            return;
+        }
+
+        if let Err(err) = storage.touch(&id).await {
+            tracing::warn!("failed to record access of paste {id}: {err:?}");
+            return;
+        }
+
+        if let Err(err) = cache_access(&cache, &url).await {
+            tracing::warn!("failed to cache access of paste {id}: {err:?}");
+        }
+    });
+}
+
+async fn cache_access(cache: &worker::Cache, url: &str) -> worker::Result<()> {
+    let mut response = worker::Response::empty()?;
+    response.headers_mut().set(
+        "Cache-Control",
+        &format!("max-age={}", consts::PASTE_ACCESS_INTERVAL.as_secs()),
+    )?;
+    cache.put(url, response).await
+}
+
+fn is_expired(
+    paste: &AnonymousPaste,
+    accessed: Option<u64>,
+    max_idle: Option<Duration>,
+    now: u64,
+) -> bool {
+    if paste.expires.is_some_and(|expires| expires <= now) {
+        return true;
+    }
+
+    let Some(max_idle) = max_idle else {
+        return false;
+    };
+
+    let last_used = accessed.unwrap_or(paste.created).max(paste.created);
+    last_used.saturating_add(max_idle.as_millis() as u64) <= now
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    const DAY: u64 = 24 * 3600 * 1000;
+
+    fn paste(
+        id: &str,
+        created: u64,
+        accessed: Option<u64>,
+        expires: Option<u64>,
+    ) -> (AnonymousPaste, Option<u64>) {
+        let paste = AnonymousPaste {
+            id: id.parse().unwrap(),
+            created,
+            expires,
+        };
+        (paste, accessed)
+    }
+
+    fn lifecycle() -> Lifecycle<memory::MemoryStore> {
+        Lifecycle::new(memory::MemoryStore::new(vec![
+            paste("aaaaa", 0, None, None),
+            paste("bbbbb", 0, Some(20 * DAY), None),
+            paste("ccccc", 25 * DAY, None, None),
+            paste("ddddd", 25 * DAY, None, Some(26 * DAY)),
+        ]))
+    }
+
+    fn options(max_idle_days: Option<u64>, dry_run: bool) -> LifecycleOptions {
+        LifecycleOptions {
+            max_idle: max_idle_days.map(|days| Duration::from_secs(days * 24 * 3600)),
+            dry_run,
+            batch_size: 10,
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_expires_idle_pastes() {
+        let lifecycle = lifecycle();
+
+        let report = block_on(lifecycle.run(options(Some(10), false), None, 30 * DAY)).unwrap();
+        assert_eq!(report.scanned, 4);
+        assert_eq!(report.expired, vec!["aaaaa", "bbbbb", "ddddd"]);
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.cursor, None);
+
+        assert_eq!(lifecycle.store.ids(), vec!["ccccc"]);
+    }
+
+    #[test]
+    fn test_lifecycle_only_looks_up_access_of_candidates() {
+        let lifecycle = lifecycle();
+
+        block_on(lifecycle.run(options(Some(10), true), None, 30 * DAY)).unwrap();
+        // `ccccc` is too young to be idle, `ddddd` has an explicit expiry time.
+        assert_eq!(lifecycle.store.lookups.get(), 2);
+    }
+
+    #[test]
+    fn test_lifecycle_without_max_idle_only_expires_explicit() {
+        let lifecycle = lifecycle();
+
+        let report = block_on(lifecycle.run(options(None, false), None, 30 * DAY)).unwrap();
+        assert_eq!(report.expired, vec!["ddddd"]);
+        assert_eq!(lifecycle.store.lookups.get(), 0);
+    }
+
+    #[test]
+    fn test_lifecycle_dry_run_keeps_pastes() {
+        let lifecycle = lifecycle();
+
+        let report = block_on(lifecycle.run(options(Some(10), true), None, 30 * DAY)).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.expired.len(), 3);
+        assert_eq!(lifecycle.store.ids().len(), 4);
+    }
+
+    #[test]
+    fn test_lifecycle_batches() {
+        let lifecycle = lifecycle();
+        let options = LifecycleOptions {
+            batch_size: 3,
+            ..options(Some(10), false)
+        };
+
+        let report = block_on(lifecycle.run(options, None, 30 * DAY)).unwrap();
+        assert_eq!(report.scanned, 3);
+        assert_eq!(report.expired, vec!["aaaaa", "bbbbb"]);
+        assert_eq!(report.cursor.as_deref(), Some("ccccc"));
+
+        let report = block_on(lifecycle.run(options, report.cursor, 30 * DAY)).unwrap();
+        assert_eq!(report.scanned, 1);
+        assert_eq!(report.expired, vec!["ddddd"]);
+        assert_eq!(report.cursor, None);
+    }
+}
@@ -0,0 +1,40 @@
+use shared::PasteId;
+
+use super::LifecycleStore;
+use crate::{
+    request_context::{Env, FromEnv},
+    storage::{AnonymousPaste, Storage},
+    Result,
+};
+
+/// Lifecycle store operating on the paste storage.
+pub struct R2LifecycleStore {
+    storage: Storage,
+}
+
+impl FromEnv for R2LifecycleStore {
+    fn from_env(env: &Env) -> Option<Self> {
+        Some(Self {
+            storage: Storage::from_env(env)?,
+        })
+    }
+}
+
+impl LifecycleStore for R2LifecycleStore {
+    async fn anonymous_pastes(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<AnonymousPaste>, Option<String>)> {
+        self.storage.list_anonymous(cursor, limit).await
+    }
+
+    async fn last_access(&self, id: &PasteId) -> Result<Option<u64>> {
+        self.storage.last_access(id).await
+    }
+
+    async fn expire(&self, id: &PasteId) -> Result<()> {
+        self.storage.delete(id).await?;
+        Ok(())
+    }
+}
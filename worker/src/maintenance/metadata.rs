@@ -0,0 +1,229 @@
+//! Recomputes the metadata of stored pastes.
+//!
+//! Pastes are processed in batches of [`consts::MAINTENANCE_BATCH_SIZE`] stored objects,
+//! the position of the batch is stored in R2 and the next run continues
+//! from there. After reaching the end, the task starts again from the beginning.
+use pob::SerdePathOfBuilding;
+use shared::{model::PasteMetadata, GameVersion, PasteId};
+
+use super::{Task, TaskReport, TaskState};
+use crate::{
+    consts,
+    request_context::{Env, FromEnv},
+    search::{Search, SearchEntry},
+    storage::{rederive, to_metadata, Storage, StoredMetadata, METADATA_VERSION},
+    Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Recomputes all derived metadata, including the title of anonymous pastes.
+    Recompute,
+    /// Only fills in the game and tree version of pastes which are missing them.
+    Backfill,
+    /// Recomputes the metadata of pastes with an outdated [`METADATA_VERSION`].
+    Migrate,
+}
+
+impl Mode {
+    fn task(self) -> Task {
+        match self {
+            Self::Recompute => Task::RecomputeMetadata,
+            Self::Backfill => Task::BackfillVersions,
+            Self::Migrate => Task::MigrateMetadata,
+        }
+    }
+
+    /// Whether the stored metadata of a paste needs to be looked at.
+    fn is_candidate(self, stored: &StoredMetadata) -> bool {
+        match self {
+            Self::Recompute => true,
+            Self::Backfill => stored
+                .metadata
+                .as_ref()
+                .is_none_or(|m| m.version.is_none() || m.game_version == GameVersion::default()),
+            Self::Migrate => stored.version < METADATA_VERSION,
+        }
+    }
+
+    /// Combines the stored metadata with the metadata derived from the build.
+    ///
+    /// Returns the new metadata and its version.
+    fn apply(self, stored: &StoredMetadata, derived: PasteMetadata) -> (PasteMetadata, u32) {
+        match (self, &stored.metadata) {
+            (Self::Backfill, Some(metadata)) => {
+                let metadata = PasteMetadata {
+                    game_version: derived.game_version,
+                    version: derived.version,
+                    ..metadata.clone()
+                };
+                // Everything else is still derived with the old version.
+                (metadata, stored.version)
+            }
+            (_, metadata) => {
+                // Titles of user pastes are chosen by the user.
+                let keep_title = matches!(stored.id, PasteId::UserPaste(_));
+                let metadata = rederive(metadata.as_ref(), derived, keep_title);
+                (metadata, METADATA_VERSION)
+            }
+        }
+    }
+}
+
+pub async fn run(env: &Env, mode: Mode) -> Result<TaskReport> {
+    let storage = Storage::from_env(env).ok_or_else(|| super::missing("storage"))?;
+    let search = Search::from_env(env).ok_or_else(|| super::missing("search"))?;
+    let bucket = env
+        .bucket(consts::R2_STORAGE_BUCKET)
+        .ok_or_else(|| super::missing("bucket"))?;
+
+    let state = super::load_state(&bucket, mode.task()).await?;
+    let (pastes, cursor) = storage
+        .list_metadata_batch(state.cursor, consts::MAINTENANCE_BATCH_SIZE)
+        .await?;
+
+    let mut report = TaskReport::default();
+    for stored in pastes {
+        report.processed += 1;
+
+        if !mode.is_candidate(&stored) {
+            continue;
+        }
+
+        match update(&storage, &search, mode, &stored).await {
+            Ok(true) => report.updated += 1,
+            Ok(false) => {}
+            Err(err) => {
+                tracing::warn!("failed to update metadata of {}: {err:?}", stored.id);
+                report.failed += 1;
+            }
+        }
+    }
+
+    // No cursor means the end was reached, start over on the next run.
+    super::save_state(&bucket, mode.task(), &TaskState { cursor }).await?;
+
+    Ok(report)
+}
+
+/// Updates the metadata of a single paste, returns whether the metadata changed.
+async fn update(
+    storage: &Storage,
+    search: &Search,
+    mode: Mode,
+    stored: &StoredMetadata,
+) -> Result<bool> {
+    let id = &stored.id;
+    let Some(paste) = storage.get(id).await? else {
+        return Ok(false);
+    };
+
+    let pob = SerdePathOfBuilding::from_export(&paste.content)
+        .map_err(|e| crate::Error::InvalidPoB(e, String::new()))?;
+    let (metadata, version) = mode.apply(stored, to_metadata(&pob));
+
+    if stored.metadata.as_ref() == Some(&metadata) && stored.version == version {
+        return Ok(false);
+    }
+
+    tracing::info!(%id, mode = mode.task().as_str(), version, "updating paste metadata");
+    storage.update_metadata(id, &metadata, version).await?;
+
+    // Anonymous pastes are never searchable, see `search::on_paste_change`.
+    if let PasteId::UserPaste(_) = id {
+        let entry = SearchEntry::new(id.clone(), &metadata, &pob, paste.last_modified);
+        search.update(id, entry).await?;
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::AscendancyOrClass;
+
+    use super::*;
+
+    fn metadata(title: &str, game_version: GameVersion, version: Option<&str>) -> PasteMetadata {
+        PasteMetadata {
+            title: title.to_owned(),
+            game_version,
+            ascendancy_or_class: "Scion".parse::<AscendancyOrClass>().unwrap(),
+            version: version.map(str::to_owned),
+            main_skill_name: None,
+            rank: None,
+            private: false,
+            forked_from: None,
+            tags: Vec::new(),
+            description: String::new(),
+        }
+    }
+
+    fn stored(id: &str, metadata: Option<PasteMetadata>, version: u32) -> StoredMetadata {
+        StoredMetadata {
+            id: id.parse().unwrap(),
+            metadata,
+            version,
+        }
+    }
+
+    #[test]
+    fn test_recompute_keeps_user_title() {
+        let derived = metadata("Scion Build", GameVersion::One, Some("3_25"));
+        let metadata = PasteMetadata {
+            private: true,
+            ..metadata("My Build", GameVersion::One, Some("3_24"))
+        };
+
+        let user = stored("foo:buildid", Some(metadata.clone()), 1);
+        let (result, version) = Mode::Recompute.apply(&user, derived.clone());
+        assert_eq!(result.title, "My Build");
+        assert_eq!(result.version.as_deref(), Some("3_25"));
+        assert!(result.private);
+        assert_eq!(version, METADATA_VERSION);
+
+        let anonymous = stored("abcdefgh", Some(metadata), 1);
+        let (result, _) = Mode::Recompute.apply(&anonymous, derived);
+        assert_eq!(result.title, "Scion Build");
+    }
+
+    #[test]
+    fn test_backfill_only_changes_versions() {
+        let old = stored(
+            "abcdefgh",
+            Some(metadata("Old Title", GameVersion::One, None)),
+            1,
+        );
+        let derived = metadata("New Title", GameVersion::Two, Some("0_1"));
+
+        let (result, version) = Mode::Backfill.apply(&old, derived);
+        assert_eq!(result.title, "Old Title");
+        assert_eq!(result.game_version, GameVersion::Two);
+        assert_eq!(result.version.as_deref(), Some("0_1"));
+        assert_eq!(version, 1);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let id = "abcdefgh";
+        assert!(Mode::Backfill.is_candidate(&stored(id, None, 1)));
+        assert!(Mode::Backfill.is_candidate(&stored(
+            id,
+            Some(metadata("", GameVersion::Two, None)),
+            1
+        )));
+        assert!(Mode::Backfill.is_candidate(&stored(
+            id,
+            Some(metadata("", GameVersion::One, Some("3_25"))),
+            1
+        )));
+        assert!(!Mode::Backfill.is_candidate(&stored(
+            id,
+            Some(metadata("", GameVersion::Two, Some("0_1"))),
+            1
+        )));
+
+        assert!(Mode::Migrate.is_candidate(&stored(id, None, 1)));
+        assert!(!Mode::Migrate.is_candidate(&stored(id, None, METADATA_VERSION)));
+    }
+}
@@ -0,0 +1,241 @@
+//! Maintenance tasks, run periodically by the scheduled event.
+//!
+//! Tasks are enabled with a comma separated list of task names in
+//! [`consts::ENV_MAINTENANCE_TASKS`], every task reports its results
+//! through the `maintenance.*` metrics.
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use worker::Bucket;
+
+use crate::{consts, request_context::Env, retry, statsd::Counters, Result};
+
+mod metadata;
+mod search;
+mod warm_cache;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    /// Expires anonymous pastes, see [`crate::lifecycle`].
+    Lifecycle,
+    /// Recomputes the stored metadata of pastes, e.g. after the title heuristics changed.
+    RecomputeMetadata,
+    /// Fills in the game and tree version of old pastes which were stored without.
+    BackfillVersions,
+    /// Requests the most popular pastes to populate the cache.
+    WarmCache,
+    /// Migrates the metadata of pastes stored with an outdated metadata version.
+    MigrateMetadata,
+    /// Adds all public user pastes to the search index, see [`crate::search`].
+    BackfillSearch,
+}
+
+impl Task {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lifecycle => "lifecycle",
+            Self::RecomputeMetadata => "recompute_metadata",
+            Self::BackfillVersions => "backfill_versions",
+            Self::WarmCache => "warm_cache",
+            Self::MigrateMetadata => "migrate_metadata",
+            Self::BackfillSearch => "backfill_search",
+        }
+    }
+}
+
+impl FromStr for Task {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "lifecycle" => Ok(Self::Lifecycle),
+            "recompute_metadata" => Ok(Self::RecomputeMetadata),
+            "backfill_versions" => Ok(Self::BackfillVersions),
+            "warm_cache" => Ok(Self::WarmCache),
+            "migrate_metadata" => Ok(Self::MigrateMetadata),
+            "backfill_search" => Ok(Self::BackfillSearch),
+            _ => Err(format!("unknown maintenance task '{s}'")),
+        }
+    }
+}
+
+/// Parses the list of enabled tasks, unknown tasks are ignored.
+///
+/// Only the paste lifecycle runs by default.
+fn parse_tasks(tasks: Option<&str>) -> Vec<Task> {
+    let Some(tasks) = tasks else {
+        return vec![Task::Lifecycle];
+    };
+
+    let mut result = Vec::new();
+    for task in tasks.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        match task.parse() {
+            Ok(task) if !result.contains(&task) => result.push(task),
+            Ok(_) => {}
+            Err(err) => tracing::warn!("{err}"),
+        }
+    }
+    result
+}
+
+/// Outcome of a single maintenance task.
+#[derive(Debug, Default, Serialize)]
+pub struct TaskReport {
+    /// Amount of items the task looked at.
+    pub processed: usize,
+    /// Amount of items which were changed.
+    pub updated: usize,
+    /// Amount of items the task failed to process.
+    pub failed: usize,
+}
+
+/// Runs all enabled maintenance tasks.
+///
+/// Tasks run independently, a failing task does not prevent other tasks from running.
+pub async fn run(env: &Env, now: u64) {
+    let tasks = parse_tasks(env.var(consts::ENV_MAINTENANCE_TASKS).as_deref());
+    tracing::info!(?tasks, "running maintenance");
+
+    for task in tasks {
+        if let Err(err) = run_task(env, task, now).await {
+            tracing::error!(task = task.as_str(), "maintenance task failed: {err:?}")
+        }
+    }
+}
+
+/// Runs a single maintenance task and records its results.
+pub async fn run_task(env: &Env, task: Task, now: u64) -> Result<TaskReport> {
+    let result = match task {
+        Task::Lifecycle => lifecycle(env, now).await,
+        Task::RecomputeMetadata => metadata::run(env, metadata::Mode::Recompute).await,
+        Task::BackfillVersions => metadata::run(env, metadata::Mode::Backfill).await,
+        Task::WarmCache => warm_cache::run(env).await,
+        Task::MigrateMetadata => metadata::run(env, metadata::Mode::Migrate).await,
+        Task::BackfillSearch => search::run(env).await,
+    };
+
+    let status = match &result {
+        Ok(_) => "ok",
+        Err(_) => "error",
+    };
+    sentry::counter(Counters::Maintenance)
+        .inc(1)
+        .tag("task", task.as_str())
+        .tag("status", status);
+
+    if let Ok(report) = &result {
+        tracing::info!(task = task.as_str(), ?report, "maintenance task finished");
+        record(task, "processed", report.processed);
+        record(task, "updated", report.updated);
+        record(task, "failed", report.failed);
+    }
+
+    result
+}
+
+async fn lifecycle(env: &Env, now: u64) -> Result<TaskReport> {
+    use crate::{lifecycle::Lifecycle, request_context::FromEnv};
+
+    let lifecycle = Lifecycle::from_env(env).ok_or_else(|| missing("lifecycle"))?;
+    let bucket = env
+        .bucket(crate::consts::R2_STORAGE_BUCKET)
+        .ok_or_else(|| missing("bucket"))?;
+
+    let state = load_state(&bucket, Task::Lifecycle).await?;
+    let options = crate::lifecycle::LifecycleOptions::from_env(env);
+    let report = lifecycle.run(options, state.cursor, now).await?;
+
+    // No cursor means the end was reached, start over on the next run.
+    let state = TaskState {
+        cursor: report.cursor.clone(),
+    };
+    save_state(&bucket, Task::Lifecycle, &state).await?;
+
+    Ok(TaskReport {
+        processed: report.scanned,
+        updated: if report.dry_run {
+            0
+        } else {
+            report.expired.len()
+        },
+        failed: report.failed,
+    })
+}
+
+/// Progress of a batched task, stored in R2 between runs.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct TaskState {
+    /// Position after the last processed paste.
+    cursor: Option<String>,
+}
+
+fn to_state_path(task: Task) -> String {
+    format!("maintenance/{}", task.as_str())
+}
+
+async fn load_state(bucket: &Bucket, task: Task) -> Result<TaskState> {
+    let path = to_state_path(task);
+    let obj = retry::retry_all(3, |_| bucket.get(&path).execute()).await?;
+
+    let Some(body) = obj.as_ref().and_then(|obj| obj.body()) else {
+        return Ok(TaskState::default());
+    };
+
+    Ok(serde_json::from_str(&body.text().await?)?)
+}
+
+async fn save_state(bucket: &Bucket, task: Task, state: &TaskState) -> Result<()> {
+    let path = to_state_path(task);
+    let state = serde_json::to_vec(state)?;
+    retry::retry_all(3, |_| {
+        bucket
+            .put(&path, worker::Data::Bytes(state.clone()))
+            .execute()
+    })
+    .await?;
+    Ok(())
+}
+
+fn record(task: Task, outcome: &'static str, count: usize) {
+    sentry::counter(Counters::MaintenanceItem)
+        .inc(count as i64)
+        .tag("task", task.as_str())
+        .tag("outcome", outcome);
+}
+
+fn missing(what: &str) -> crate::Error {
+    crate::Error::Error(format!("failed to create {what} from env"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tasks_default() {
+        assert_eq!(parse_tasks(None), vec![Task::Lifecycle]);
+        assert_eq!(parse_tasks(Some("")), vec![]);
+    }
+
+    #[test]
+    fn test_parse_tasks_list() {
+        assert_eq!(
+            parse_tasks(Some("warm_cache, backfill_versions,foo,warm_cache")),
+            vec![Task::WarmCache, Task::BackfillVersions]
+        );
+    }
+
+    #[test]
+    fn test_task_names_roundtrip() {
+        for task in [
+            Task::Lifecycle,
+            Task::RecomputeMetadata,
+            Task::BackfillVersions,
+            Task::WarmCache,
+            Task::MigrateMetadata,
+            Task::BackfillSearch,
+        ] {
+            assert_eq!(task.as_str().parse::<Task>(), Ok(task));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Backfills the search index with the stored public user pastes.
+//!
+//! Pastes are processed in batches of [`consts::MAINTENANCE_SEARCH_BATCH_SIZE`] stored objects,
+//! the position of the batch is stored in R2 and the next run continues from there.
+//! After reaching the end, the task starts again from the beginning.
+use pob::SerdePathOfBuilding;
+
+use super::{Task, TaskReport, TaskState};
+use crate::{
+    consts,
+    request_context::{Env, FromEnv},
+    search::{Search, SearchEntry},
+    storage::{Storage, StoredMetadata},
+    Result,
+};
+
+pub async fn run(env: &Env) -> Result<TaskReport> {
+    let storage = Storage::from_env(env).ok_or_else(|| super::missing("storage"))?;
+    let search = Search::from_env(env).ok_or_else(|| super::missing("search"))?;
+    let bucket = env
+        .bucket(consts::R2_STORAGE_BUCKET)
+        .ok_or_else(|| super::missing("bucket"))?;
+
+    let state = super::load_state(&bucket, Task::BackfillSearch).await?;
+    let (pastes, cursor) = storage
+        .list_user_metadata(state.cursor, consts::MAINTENANCE_SEARCH_BATCH_SIZE)
+        .await?;
+
+    let mut report = TaskReport::default();
+    for stored in pastes {
+        report.processed += 1;
+
+        match index(&storage, &search, &stored).await {
+            Ok(true) => report.updated += 1,
+            Ok(false) => {}
+            Err(err) => {
+                tracing::warn!("failed to index paste {}: {err:?}", stored.id);
+                report.failed += 1;
+            }
+        }
+    }
+
+    // No cursor means the end was reached, start over on the next run.
+    super::save_state(&bucket, Task::BackfillSearch, &TaskState { cursor }).await?;
+
+    Ok(report)
+}
+
+/// Adds a single paste to the search index, returns whether the paste is searchable.
+async fn index(storage: &Storage, search: &Search, stored: &StoredMetadata) -> Result<bool> {
+    let id = &stored.id;
+    let Some(metadata) = stored.metadata.as_ref().filter(|m| !m.private) else {
+        return Ok(false);
+    };
+    let Some(paste) = storage.get(id).await? else {
+        return Ok(false);
+    };
+
+    let pob = SerdePathOfBuilding::from_export(&paste.content)
+        .map_err(|e| crate::Error::InvalidPoB(e, String::new()))?;
+
+    let entry = SearchEntry::new(id.clone(), metadata, &pob, paste.last_modified);
+    let searchable = entry.is_some();
+    search.update(id, entry).await?;
+
+    Ok(searchable)
+}
@@ -0,0 +1,57 @@
+//! Warms the cache for popular pastes.
+//!
+//! Popular pastes are the newest public pastes, as shown by the default search.
+//! Pastes are requested through the public URL of the site, which stores
+//! the rendered pages in the cache.
+use shared::PasteId;
+
+use super::TaskReport;
+use crate::{
+    consts, net,
+    request_context::{Env, FromEnv},
+    search::{Search, SearchQuery},
+    Result,
+};
+
+pub async fn run(env: &Env) -> Result<TaskReport> {
+    let Some(public_url) = env.var(consts::ENV_PUBLIC_URL) else {
+        tracing::warn!("not warming caches, no public url configured");
+        return Ok(TaskReport::default());
+    };
+    let public_url = public_url.trim_end_matches('/');
+
+    let mut report = TaskReport::default();
+    for id in popular(env).await? {
+        report.processed += 1;
+
+        let url = format!("{public_url}{}", id.to_url().into_cow());
+        match net::Request::get(&url).tag("warm_cache").send().await {
+            Ok(response) if response.status_code() == 200 => report.updated += 1,
+            Ok(response) => {
+                tracing::warn!("failed to warm cache for {id}: {}", response.status_code());
+                report.failed += 1;
+            }
+            Err(err) => {
+                tracing::warn!("failed to warm cache for {id}: {err:?}");
+                report.failed += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+async fn popular(env: &Env) -> Result<Vec<PasteId>> {
+    let search = Search::from_env(env).ok_or_else(|| super::missing("search"))?;
+
+    let ids = search
+        .search(&SearchQuery::default())
+        .await?
+        .pastes
+        .into_iter()
+        .map(|paste| paste.id)
+        .take(consts::MAINTENANCE_WARM_CACHE_LIMIT)
+        .collect();
+
+    Ok(ids)
+}
@@ -0,0 +1,153 @@
+//! Moderation of pastes.
+//!
+//! Admins are configured as a comma separated list of PoE account names,
+//! they can take down any paste and review reports submitted by users.
+//!
+//! Reports are stored as empty objects with the report in the custom metadata,
+//! listing all reports does not require fetching every object.
+use std::collections::HashMap;
+
+use shared::{model::PasteReport, PasteId, User};
+use worker::{Bucket, Include};
+
+use crate::{
+    consts,
+    request_context::{Env, FromEnv},
+    retry,
+    utils::{b64_decode, b64_encode},
+    Result,
+};
+
+pub struct Moderation {
+    bucket: Bucket,
+    admins: Vec<User>,
+}
+
+impl FromEnv for Moderation {
+    fn from_env(env: &Env) -> Option<Self> {
+        let admins = env
+            .var(consts::ENV_ADMINS)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|admin| !admin.is_empty())
+            .map(User::new)
+            .collect();
+
+        Some(Self {
+            bucket: env.bucket(consts::R2_STORAGE_BUCKET)?,
+            admins,
+        })
+    }
+}
+
+impl Moderation {
+    pub fn is_admin(&self, user: &User) -> bool {
+        self.admins.contains(user)
+    }
+
+    /// Records a report for a paste.
+    #[tracing::instrument(skip(self))]
+    pub async fn report(
+        &self,
+        paste: PasteId,
+        reason: String,
+        reporter: Option<User>,
+    ) -> Result<PasteReport> {
+        let report = PasteReport {
+            id: crate::utils::random_string::<12>()?,
+            paste,
+            reason,
+            reporter,
+            created: worker::Date::now().as_millis(),
+        };
+
+        let mut custom_metadata = HashMap::new();
+        custom_metadata.insert(
+            "report".to_owned(),
+            b64_encode(serde_json::to_string(&report)?),
+        );
+
+        let path = to_path(&report.id);
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(&path, worker::Data::Empty)
+                .custom_metadata(custom_metadata.clone())
+                .execute()
+        })
+        .await?;
+
+        Ok(report)
+    }
+
+    /// Lists all open reports, the oldest report first.
+    #[tracing::instrument(skip(self))]
+    pub async fn reports(&self) -> Result<Vec<PasteReport>> {
+        let mut reports = Vec::new();
+        let mut cursor = None;
+        loop {
+            let objects = retry::retry_all(3, |_| {
+                let mut list = self
+                    .bucket
+                    .list()
+                    .prefix(PREFIX)
+                    .include(vec![Include::CustomMetadata])
+                    .limit(1000);
+                if let Some(cursor) = &cursor {
+                    list = list.cursor(String::clone(cursor));
+                }
+                list.execute()
+            })
+            .await?;
+
+            for obj in objects.objects() {
+                if let Some(report) = to_report(&obj)? {
+                    reports.push(report);
+                }
+            }
+
+            cursor = objects.cursor();
+            if !objects.truncated() || cursor.is_none() {
+                break;
+            }
+        }
+
+        reports.sort_unstable_by_key(|report| report.created);
+
+        Ok(reports)
+    }
+
+    /// Dismisses a report, dismissing a report which does not exist is not an error.
+    #[tracing::instrument(skip(self))]
+    pub async fn dismiss(&self, id: &str) -> Result<()> {
+        let path = to_path(id);
+        retry::retry_all(3, |_| self.bucket.delete(&path)).await?;
+        Ok(())
+    }
+
+    /// Dismisses all reports of a paste.
+    #[tracing::instrument(skip(self))]
+    pub async fn dismiss_paste(&self, paste: &PasteId) -> Result<()> {
+        for report in self.reports().await? {
+            if &report.paste == paste {
+                self.dismiss(&report.id).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+const PREFIX: &str = "reports/";
+
+fn to_report(obj: &worker::Object) -> Result<Option<PasteReport>> {
+    let Some(report) = obj.custom_metadata()?.remove("report") else {
+        tracing::warn!("report {} without metadata", obj.key());
+        return Ok(None);
+    };
+
+    Ok(Some(serde_json::from_slice(&b64_decode(report)?)?))
+}
+
+fn to_path(id: &str) -> String {
+    format!("{PREFIX}{id}")
+}
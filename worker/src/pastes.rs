@@ -1,10 +1,16 @@
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    num::NonZeroU8,
+};
 
 use pob::{PathOfBuilding, SerdePathOfBuilding};
+use serde::{Deserialize, Serialize};
 use shared::{
     model::{
         data::{self, NodeStat},
-        Paste, PasteSummary,
+        ListPastesQuery, Paste, PastePage, PasteRevision, PasteSort, PasteSummary,
     },
     GameVersion, PasteId, User, UserPasteId,
 };
@@ -13,6 +19,8 @@ use crate::request_context::{Env, FromEnv, Session};
 
 pub struct Meta {
     pub etag: String,
+    /// Expiry time of the paste in milliseconds.
+    pub expires: Option<u64>,
 }
 
 pub struct Pastes {
@@ -33,32 +41,65 @@ impl Pastes {
             return Ok(None);
         };
 
-        let pob = SerdePathOfBuilding::from_export(&stored.content)
-            .map_err(|e| crate::Error::InvalidPoB(e, String::new()))?;
+        to_paste(stored).map(Some)
+    }
 
-        let paste = Paste {
-            metadata: stored.metadata,
-            last_modified: stored.last_modified,
-            content: stored.content,
-            data: data::Data {
-                nodes: extract_node_info(&pob),
-                gems: extract_gem_info(&pob),
-            },
+    pub async fn get_revision(
+        &self,
+        id: &UserPasteId,
+        revision: u64,
+    ) -> crate::Result<Option<(Meta, Paste)>> {
+        let Some(stored) = self.storage.get_revision(id, revision).await? else {
+            return Ok(None);
         };
 
+        to_paste(stored).map(Some)
+    }
+
+    /// Lists all revisions of a paste.
+    ///
+    /// Access must be checked against the current paste,
+    /// see [`crate::shares::check_revision_access`].
+    pub async fn list_revisions(&self, id: &UserPasteId) -> crate::Result<Vec<PasteRevision>> {
+        self.storage.list_revisions(id).await
+    }
+
+    /// Returns a page of the pastes of a user, which are visible to the session.
+    ///
+    /// The etag is derived from the contents of the page.
+    pub async fn list_pastes(
+        &self,
+        session: Session<'_>,
+        user: &User,
+        query: &ListPastesQuery,
+    ) -> crate::Result<(Meta, PastePage)> {
+        let pastes = self
+            .list_summaries(session, user)
+            .await?
+            .into_iter()
+            .filter(|paste| matches_query(query, paste))
+            .collect();
+
+        let page = paginate(pastes, query, crate::consts::USER_PASTES_PAGE_SIZE)?;
+
+        let hash = crate::crypto::sha1(&serde_json::to_vec(&page)?).await?;
+        let hash = hash.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        let etag = format!("{}-{hash}", page.total);
         let meta = Meta {
-            etag: stored.entity_id,
+            etag,
+            expires: None,
         };
 
-        Ok(Some((meta, paste)))
+        Ok((meta, page))
     }
 
-    pub async fn list_pastes(
+    /// Lists the summaries of all pastes of a user, which are visible to the session.
+    pub async fn list_summaries(
         &self,
         session: Session<'_>,
         user: &User,
-    ) -> crate::Result<(Meta, Vec<PasteSummary>)> {
-        let mut pastes = self
+    ) -> crate::Result<Vec<PasteSummary>> {
+        let pastes = self
             .storage
             .list(user)
             .await?
@@ -85,26 +126,152 @@ impl Pastes {
                     last_modified: item.last_modified,
                     rank: metadata.rank,
                     private: metadata.private,
+                    tags: metadata.tags,
+                    description: metadata.description,
                 }
             })
-            .collect::<Vec<_>>();
+            .collect();
 
-        pastes.sort_unstable_by(|a, b| {
-            b.rank
-                .cmp(&a.rank)
-                .then(b.last_modified.cmp(&a.last_modified))
-        });
+        Ok(pastes)
+    }
+}
+
+fn matches_query(query: &ListPastesQuery, paste: &PasteSummary) -> bool {
+    let class = query.class.as_deref().is_none_or(|class| {
+        let aoc = paste.ascendancy_or_class;
+        class.eq_ignore_ascii_case(aoc.as_str()) || class.eq_ignore_ascii_case(aoc.class().as_str())
+    });
+    let skill = query.skill.as_deref().is_none_or(|skill| {
+        paste
+            .main_skill_name
+            .as_deref()
+            .is_some_and(|name| skill.eq_ignore_ascii_case(name))
+    });
+    let game_version = query.game_version.is_none_or(|gv| gv == paste.game_version);
+    let private = query.private.is_none_or(|private| private == paste.private);
+    let tag = query
+        .tag
+        .as_deref()
+        .is_none_or(|tag| paste.tags.iter().any(|t| t == tag));
+
+    class && skill && game_version && private && tag
+}
+
+/// Position of the last paste on a page, the next page starts after this position.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+struct Cursor<'a> {
+    #[serde(rename = "r", default, skip_serializing_if = "Option::is_none")]
+    rank: Option<NonZeroU8>,
+    #[serde(rename = "m")]
+    last_modified: u64,
+    #[serde(rename = "t", default, skip_serializing_if = "Option::is_none")]
+    title: Option<Cow<'a, str>>,
+    #[serde(rename = "i")]
+    id: Cow<'a, str>,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(sort: PasteSort, paste: &'a PasteSummary) -> Self {
+        Self {
+            rank: paste.rank,
+            last_modified: paste.last_modified,
+            // The title is only necessary for the title sort, keep the cursor short otherwise.
+            title: match sort {
+                PasteSort::Title => Some(Cow::Borrowed(&paste.title)),
+                _ => None,
+            },
+            id: Cow::Borrowed(paste.id.id()),
+        }
+    }
 
-        let etag = pastes
-            .first()
-            .map(|f| format!("{}-{}", pastes.len(), f.last_modified))
-            .unwrap_or_else(|| "empty".to_owned());
-        let meta = Meta { etag };
+    fn parse(cursor: &str) -> crate::Result<Cursor<'static>> {
+        crate::utils::b64_decode(cursor)
+            .ok()
+            .and_then(|cursor| serde_json::from_slice(&cursor).ok())
+            .ok_or_else(|| crate::Error::BadRequest("invalid cursor".to_owned()))
+    }
+
+    fn encode(&self) -> String {
+        let cursor = serde_json::to_vec(self).expect("cursor is always serializable");
+        crate::utils::b64_encode(cursor)
+    }
 
-        Ok((meta, pastes))
+    fn compare(&self, other: &Cursor<'_>, sort: PasteSort) -> Ordering {
+        let order = match sort {
+            PasteSort::Pinned => other
+                .rank
+                .cmp(&self.rank)
+                .then(other.last_modified.cmp(&self.last_modified)),
+            PasteSort::Newest => other.last_modified.cmp(&self.last_modified),
+            PasteSort::Oldest => self.last_modified.cmp(&other.last_modified),
+            PasteSort::Title => self
+                .title
+                .as_deref()
+                .map(str::to_lowercase)
+                .cmp(&other.title.as_deref().map(str::to_lowercase))
+                .then(other.last_modified.cmp(&self.last_modified)),
+        };
+        // Ids are unique, this makes the order total and the cursor unambiguous.
+        order.then_with(|| self.id.cmp(&other.id))
     }
 }
 
+/// Sorts the pastes and returns the page requested by the query.
+fn paginate(
+    mut pastes: Vec<PasteSummary>,
+    query: &ListPastesQuery,
+    page_size: usize,
+) -> crate::Result<PastePage> {
+    let sort = query.sort;
+    pastes.sort_unstable_by(|a, b| Cursor::new(sort, a).compare(&Cursor::new(sort, b), sort));
+
+    let total = pastes.len();
+
+    let start = match query.cursor.as_deref() {
+        Some(cursor) => {
+            let cursor = Cursor::parse(cursor)?;
+            pastes.partition_point(|paste| {
+                Cursor::new(sort, paste).compare(&cursor, sort) != Ordering::Greater
+            })
+        }
+        None => 0,
+    };
+
+    let end = total.min(start + page_size);
+    let next = (end < total).then(|| Cursor::new(sort, &pastes[end - 1]).encode());
+
+    pastes.truncate(end);
+    pastes.drain(..start);
+
+    Ok(PastePage {
+        pastes,
+        next,
+        total,
+    })
+}
+
+fn to_paste(stored: crate::storage::StoredPaste) -> crate::Result<(Meta, Paste)> {
+    let pob = SerdePathOfBuilding::from_export(&stored.content)
+        .map_err(|e| crate::Error::InvalidPoB(e, String::new()))?;
+
+    let paste = Paste {
+        metadata: stored.metadata,
+        last_modified: stored.last_modified,
+        content: stored.content,
+        data: data::Data {
+            nodes: extract_node_info(&pob),
+            gems: extract_gem_info(&pob),
+        },
+    };
+
+    let meta = Meta {
+        etag: stored.entity_id,
+        expires: stored.expires,
+    };
+
+    Ok((meta, paste))
+}
+
 fn extract_node_info(pob: &impl PathOfBuilding) -> Vec<data::Nodes> {
     let mut data = Vec::new();
     for spec in pob.tree_specs() {
@@ -225,3 +392,124 @@ fn extract_gem_info(pob: &impl PathOfBuilding) -> HashMap<String, data::Gem> {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use shared::{Ascendancy, Class};
+
+    use super::*;
+
+    fn paste(id: &str, title: &str, last_modified: u64, rank: Option<u8>) -> PasteSummary {
+        PasteSummary {
+            id: format!("user:{id}").parse().unwrap(),
+            title: title.to_owned(),
+            game_version: GameVersion::One,
+            ascendancy_or_class: Ascendancy::Chieftain.into(),
+            version: None,
+            main_skill_name: Some("Righteous Fire".to_owned()),
+            last_modified,
+            rank: rank.and_then(NonZeroU8::new),
+            private: false,
+            tags: Vec::new(),
+            description: String::new(),
+        }
+    }
+
+    fn pastes() -> Vec<PasteSummary> {
+        vec![
+            paste("aaaaa", "Bravo", 1, None),
+            paste("bbbbb", "alpha", 4, None),
+            paste("ccccc", "Charlie", 2, Some(1)),
+            paste("ddddd", "Delta", 3, None),
+            paste("eeeee", "Echo", 3, None),
+        ]
+    }
+
+    fn ids(page: &PastePage) -> Vec<&str> {
+        page.pastes.iter().map(|p| p.id.id()).collect()
+    }
+
+    fn all_pages(query: &ListPastesQuery, page_size: usize) -> Vec<Vec<String>> {
+        let mut pages = Vec::new();
+        let mut query = query.clone();
+        loop {
+            let page = paginate(pastes(), &query, page_size).unwrap();
+            assert_eq!(page.total, 5);
+            pages.push(ids(&page).into_iter().map(Into::into).collect());
+            match page.next {
+                Some(next) => query = query.with_cursor(Some(next)),
+                None => break,
+            }
+        }
+        pages
+    }
+
+    #[test]
+    fn test_paginate_sort() {
+        let mut query = ListPastesQuery::default();
+        let page = paginate(pastes(), &query, 10).unwrap();
+        assert_eq!(ids(&page), ["ccccc", "bbbbb", "ddddd", "eeeee", "aaaaa"]);
+        assert_eq!(page.next, None);
+
+        query.sort = PasteSort::Newest;
+        let page = paginate(pastes(), &query, 10).unwrap();
+        assert_eq!(ids(&page), ["bbbbb", "ddddd", "eeeee", "ccccc", "aaaaa"]);
+
+        query.sort = PasteSort::Oldest;
+        let page = paginate(pastes(), &query, 10).unwrap();
+        assert_eq!(ids(&page), ["aaaaa", "ccccc", "ddddd", "eeeee", "bbbbb"]);
+
+        query.sort = PasteSort::Title;
+        let page = paginate(pastes(), &query, 10).unwrap();
+        assert_eq!(ids(&page), ["bbbbb", "aaaaa", "ccccc", "ddddd", "eeeee"]);
+    }
+
+    #[test]
+    fn test_paginate_cursor() {
+        for sort in PasteSort::ALL {
+            let query = ListPastesQuery {
+                sort,
+                ..Default::default()
+            };
+
+            let all = all_pages(&query, 10).concat();
+            let pages = all_pages(&query, 2);
+            assert_eq!(pages.len(), 3);
+            assert_eq!(pages.concat(), all);
+        }
+    }
+
+    #[test]
+    fn test_paginate_invalid_cursor() {
+        let query = ListPastesQuery {
+            cursor: Some("garbage".to_owned()),
+            ..Default::default()
+        };
+        assert!(paginate(pastes(), &query, 2).is_err());
+    }
+
+    #[test]
+    fn test_matches_query() {
+        let mut p = paste("aaaaa", "Bravo", 1, None);
+
+        let mut query = ListPastesQuery {
+            class: Some("marauder".to_owned()),
+            skill: Some("righteous fire".to_owned()),
+            ..Default::default()
+        };
+        assert!(matches_query(&query, &p));
+
+        query.private = Some(true);
+        assert!(!matches_query(&query, &p));
+        p.private = true;
+        assert!(matches_query(&query, &p));
+
+        query.tag = Some("uber".to_owned());
+        assert!(!matches_query(&query, &p));
+        p.tags = vec!["budget".to_owned(), "uber".to_owned()];
+        assert!(matches_query(&query, &p));
+
+        query.class = Some(Class::Witch.as_str().to_owned());
+        assert!(!matches_query(&query, &p));
+    }
+}
@@ -4,6 +4,7 @@ use serde::Deserialize;
 use worker::Url;
 
 use crate::{
+    characters::{Character, CharacterResponse},
     net,
     request_context::{Env, FromEnv},
 };
@@ -156,6 +157,24 @@ impl PoeApi {
 
         Ok(response.json().await?)
     }
+
+    /// Fetches a character of the account, requires the `account:characters` scope.
+    #[tracing::instrument(skip(self))]
+    pub async fn fetch_character(&self, name: &str) -> crate::Result<Character> {
+        let url = format!("https://api.pathofexile.com/character/{name}");
+        let mut response = net::Request::get(url)
+            .tag("poe_character")
+            .header("Authorization", &format!("Bearer {}", self.access_token))
+            .header("User-Agent", POE_API_USER_AGENT)
+            .send()
+            .await?;
+
+        match response.status_code() {
+            200 => Ok(response.json::<CharacterResponse>().await?.character),
+            404 => Err(crate::Error::NotFound("character", name.to_owned())),
+            _ => Err(handle_error("Character", response).await.into()),
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
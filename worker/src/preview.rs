@@ -0,0 +1,109 @@
+//! Open Graph preview cards, see [`app::pob::preview`].
+//!
+//! External resources are embedded into the card, they would not be loaded
+//! when the card is displayed as an image. The card is rasterized to PNG
+//! with the font from [`FONT`], there are no system fonts in a worker.
+use std::sync::Arc;
+
+use app::pob::preview::{self, Card, Tree};
+use pob::PathOfBuilding;
+use resvg::{tiny_skia, usvg};
+
+use crate::{net, request_context::RequestContext, Error, Result};
+
+/// Font asset used for all text on the card.
+const FONT: &str = "assets/fonts/DejaVuSans.ttf";
+const FONT_FAMILY: &str = "DejaVu Sans";
+
+/// Renders the preview card of a build as PNG.
+///
+/// Missing portraits and tree assets are logged and left out of the card.
+pub async fn render(
+    rctx: &RequestContext,
+    pob: &impl PathOfBuilding,
+    title: &str,
+    author: Option<&str>,
+) -> Result<Vec<u8>> {
+    let (portrait, tree, font) = futures::join!(
+        portrait(pob),
+        tree(rctx, pob),
+        crate::assets::load_bytes(rctx, FONT)
+    );
+
+    let svg = preview::render(&Card {
+        title,
+        author,
+        pob,
+        portrait: portrait.as_deref(),
+        tree,
+    });
+
+    let font = font?.ok_or_else(|| Error::NotFound("asset", FONT.to_owned()))?;
+    rasterize(&svg, font)
+}
+
+fn rasterize(svg: &str, font: Vec<u8>) -> Result<Vec<u8>> {
+    let error = |e: String| Error::Error(format!("failed to render preview: {e}"));
+
+    let mut fontdb = usvg::fontdb::Database::new();
+    fontdb.load_font_data(font);
+    fontdb.set_sans_serif_family(FONT_FAMILY);
+
+    let options = usvg::Options {
+        fontdb: Arc::new(fontdb),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| error(e.to_string()))?;
+
+    let mut pixmap = tiny_skia::Pixmap::new(preview::WIDTH, preview::HEIGHT)
+        .ok_or_else(|| error("invalid card size".to_owned()))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    pixmap.encode_png().map_err(|e| error(e.to_string()))
+}
+
+async fn portrait(pob: &impl PathOfBuilding) -> Option<String> {
+    let url = preview::portrait_url(pob);
+
+    let response = net::Request::get(url).tag("preview").send().await;
+    let mut response = match response {
+        Ok(response) if response.status_code() == 200 => response,
+        Ok(response) => {
+            tracing::warn!("portrait {url} responded with {}", response.status_code());
+            return None;
+        }
+        Err(err) => {
+            tracing::warn!("failed to load portrait {url}: {err}");
+            return None;
+        }
+    };
+
+    let content_type = response.headers().get("Content-Type").ok().flatten();
+    let bytes = response.bytes().await.ok()?;
+
+    Some(format!(
+        "data:{};base64,{}",
+        content_type.as_deref().unwrap_or("image/webp"),
+        base64::encode(bytes)
+    ))
+}
+
+async fn tree(rctx: &RequestContext, pob: &impl PathOfBuilding) -> Option<Tree> {
+    let specs = pob.tree_specs();
+    let spec = specs.iter().find(|spec| spec.active)?;
+    let path = preview::tree_asset(pob, spec);
+
+    let svg = match crate::assets::load_text(rctx, path).await {
+        Ok(Some(svg)) => svg,
+        Ok(None) => {
+            tracing::warn!("tree asset {path} not found");
+            return None;
+        }
+        Err(err) => {
+            tracing::warn!("failed to load tree asset {path}: {err}");
+            return None;
+        }
+    };
+
+    Tree::from_svg(&svg, spec.nodes)
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use super::CounterStore;
+use crate::Result;
+
+/// Counter store backed by the Cache API.
+///
+/// The cache is local to a data center, counters are approximate
+/// and not shared globally, which is good enough for rate limiting.
+pub struct CacheStore;
+
+impl CounterStore for CacheStore {
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64> {
+        let cache = worker::Cache::open("ratelimit".to_owned()).await;
+        let url = format!("https://ratelimit.pobb.in/{key}");
+
+        let count = match cache.get(url.as_str(), true).await? {
+            Some(mut response) => response.text().await?.parse().unwrap_or(0),
+            None => 0,
+        } + 1;
+
+        let mut response = worker::Response::ok(count.to_string())?;
+        response
+            .headers_mut()
+            .set("Cache-Control", &format!("max-age={}", ttl.as_secs()))?;
+        cache.put(url.as_str(), response).await?;
+
+        Ok(count)
+    }
+}
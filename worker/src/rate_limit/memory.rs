@@ -0,0 +1,19 @@
+use std::{cell::RefCell, collections::HashMap, time::Duration};
+
+use super::CounterStore;
+use crate::Result;
+
+/// In memory counter store, used for tests.
+#[derive(Default)]
+pub struct MemoryStore {
+    counters: RefCell<HashMap<String, u64>>,
+}
+
+impl CounterStore for MemoryStore {
+    async fn increment(&self, key: &str, _ttl: Duration) -> Result<u64> {
+        let mut counters = self.counters.borrow_mut();
+        let count = counters.entry(key.to_owned()).or_default();
+        *count += 1;
+        Ok(*count)
+    }
+}
@@ -0,0 +1,203 @@
+//! Per-client rate limiting.
+//!
+//! Requests are counted in fixed windows per route budget and client,
+//! a client is identified by its session user or, for anonymous requests,
+//! by its IP address.
+use std::time::Duration;
+
+use crate::{
+    request_context::RequestContext,
+    route::{Api, GetEndpoints, PostEndpoints, Route},
+    statsd::Counters,
+    utils::RequestExt,
+    Error, Result,
+};
+
+mod cache;
+#[cfg(test)]
+mod memory;
+
+pub use self::cache::CacheStore;
+
+/// Backend storing the request counters.
+pub trait CounterStore {
+    /// Increments the counter for `key` and returns the new count.
+    ///
+    /// Counters are only required to live for `ttl`.
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64>;
+}
+
+/// Amount of requests a client can make within a window.
+#[derive(Debug, Clone, Copy)]
+pub struct Budget {
+    pub name: &'static str,
+    pub limit: u64,
+    pub window: Duration,
+}
+
+impl Budget {
+    /// Returns the budget for a route, `None` if the route is not rate limited.
+    pub fn for_route(route: &Route) -> Option<Self> {
+        let budget = match route {
+            Route::Api(Api::Post(PostEndpoints::Upload)) => Self {
+                name: "upload",
+                limit: 30,
+                window: Duration::from_secs(600),
+            },
+            Route::Api(Api::Post(PostEndpoints::PobUpload)) => Self {
+                name: "pob_upload",
+                limit: 30,
+                window: Duration::from_secs(600),
+            },
+            Route::Api(Api::Post(PostEndpoints::ImportUrl)) => Self {
+                name: "import_url",
+                limit: 30,
+                window: Duration::from_secs(600),
+            },
+            Route::Api(Api::Post(PostEndpoints::ReportPaste(_))) => Self {
+                name: "report",
+                limit: 10,
+                window: Duration::from_secs(3600),
+            },
+            Route::Api(Api::Get(GetEndpoints::Export)) => Self {
+                name: "export",
+                limit: 5,
+                window: Duration::from_secs(3600),
+            },
+            Route::Api(Api::Post(PostEndpoints::Import)) => Self {
+                name: "import",
+                limit: 5,
+                window: Duration::from_secs(3600),
+            },
+            Route::Api(Api::Get(GetEndpoints::Login)) => Self {
+                name: "login",
+                limit: 10,
+                window: Duration::from_secs(60),
+            },
+            Route::Api(Api::Get(GetEndpoints::ImportCharacter(_))) => Self {
+                name: "import_character",
+                limit: 10,
+                window: Duration::from_secs(600),
+            },
+            _ => return None,
+        };
+
+        Some(budget)
+    }
+}
+
+pub struct RateLimiter<S = CacheStore> {
+    store: S,
+}
+
+impl<S: CounterStore> RateLimiter<S> {
+    pub fn new(store: S) -> Self {
+        Self { store }
+    }
+
+    /// Counts a request of the client against the budget.
+    ///
+    /// Returns [`Error::TooManyRequests`] with the time until the budget resets,
+    /// if the client exceeded the budget.
+    pub async fn check(&self, client: &str, budget: &Budget, now: u64) -> Result<()> {
+        let window = budget.window.as_millis() as u64;
+        let index = now / window;
+
+        let key = format!("{}/{client}/{index}", budget.name);
+        let count = self.store.increment(&key, budget.window).await?;
+
+        if count > budget.limit {
+            let retry_after = Duration::from_millis((index + 1) * window - now);
+            return Err(Error::TooManyRequests(retry_after));
+        }
+
+        Ok(())
+    }
+}
+
+/// Rate limits the current request according to the budget of its route.
+///
+/// Errors of the counter store are logged and the request is allowed.
+pub async fn check(rctx: &RequestContext) -> Result<()> {
+    let Some(budget) = Budget::for_route(rctx.route()) else {
+        return Ok(());
+    };
+
+    let client = match rctx.session() {
+        Some(user) => format!("user:{}", user.name),
+        None => format!(
+            "ip:{}",
+            rctx.header("Cf-Connecting-Ip")
+                .as_deref()
+                .unwrap_or("unknown")
+        ),
+    };
+
+    let now = worker::Date::now().as_millis();
+    match RateLimiter::new(CacheStore)
+        .check(&client, &budget, now)
+        .await
+    {
+        Err(Error::TooManyRequests(retry_after)) => {
+            tracing::warn!(%client, budget = budget.name, ?retry_after, "rate limited");
+            sentry::counter(Counters::RateLimited)
+                .inc(1)
+                .tag("budget", budget.name)
+                .tag("transaction", rctx.transaction());
+            Err(Error::TooManyRequests(retry_after))
+        }
+        // A broken counter store must not take down the rate limited routes.
+        Err(err) => {
+            tracing::error!("rate limiting failed: {err:?}");
+            Ok(())
+        }
+        Ok(()) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    const BUDGET: Budget = Budget {
+        name: "test",
+        limit: 2,
+        window: Duration::from_secs(60),
+    };
+
+    #[test]
+    fn test_rate_limit_within_budget() {
+        let limiter = RateLimiter::new(memory::MemoryStore::default());
+
+        assert!(block_on(limiter.check("a", &BUDGET, 1_000)).is_ok());
+        assert!(block_on(limiter.check("a", &BUDGET, 2_000)).is_ok());
+        assert!(block_on(limiter.check("b", &BUDGET, 2_000)).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limit_exceeded() {
+        let limiter = RateLimiter::new(memory::MemoryStore::default());
+
+        assert!(block_on(limiter.check("a", &BUDGET, 1_000)).is_ok());
+        assert!(block_on(limiter.check("a", &BUDGET, 2_000)).is_ok());
+
+        let err = block_on(limiter.check("a", &BUDGET, 30_000)).unwrap_err();
+        assert!(matches!(err, Error::TooManyRequests(retry) if retry == Duration::from_secs(30)));
+
+        // Other clients are not affected.
+        assert!(block_on(limiter.check("b", &BUDGET, 30_000)).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limit_resets_with_window() {
+        let limiter = RateLimiter::new(memory::MemoryStore::default());
+
+        for _ in 0..3 {
+            let _ = block_on(limiter.check("a", &BUDGET, 59_000));
+        }
+        assert!(block_on(limiter.check("a", &BUDGET, 59_999)).is_err());
+        assert!(block_on(limiter.check("a", &BUDGET, 60_000)).is_ok());
+    }
+}
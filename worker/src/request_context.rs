@@ -12,6 +12,7 @@ pub struct RequestContext {
     route: route::Route,
     trace_id: sentry::TraceId,
     session: Option<app::User>,
+    renewed_session: Option<String>,
 }
 
 // TODO this could/should be a Session() type
@@ -21,7 +22,11 @@ impl RequestContext {
     pub async fn new(req: worker::Request, env: worker::Env, ctx: worker::Context) -> Self {
         let route = route::Route::new(&req);
         let env = Env::new(env);
-        let session = parse_session(&req, &env).await;
+        let (session, renewed_session) = match parse_session(&req, &env).await {
+            Some(session) => (Some(session.user), session.renewed),
+            None if accepts_api_token(&route) => (parse_api_token(&req, &env).await, None),
+            None => (None, None),
+        };
         Self {
             req,
             env,
@@ -29,6 +34,7 @@ impl RequestContext {
             route,
             trace_id: sentry::TraceId::default(),
             session,
+            renewed_session,
         }
     }
 
@@ -48,6 +54,10 @@ impl RequestContext {
         &self.ctx
     }
 
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
     pub fn owned_ctx(&self) -> worker::Context {
         // `worker::Context` doesn't implement Clone, but there is no reason it shouldn't.
         let ctx = self.ctx.as_ref().unchecked_ref::<js_sys::Object>().clone();
@@ -135,6 +145,20 @@ impl RequestContext {
         self.session.is_some()
     }
 
+    /// The session re-signed with the newest key, if the request session was signed
+    /// with an outdated key.
+    ///
+    /// There is no renewed session on login and logout, these replace the session.
+    pub fn renewed_session(&self) -> Option<&str> {
+        use route::{Api, GetEndpoints, PostEndpoints, Route};
+
+        match self.route {
+            Route::Api(Api::Get(GetEndpoints::Oauht2Poe)) => None,
+            Route::Api(Api::Post(PostEndpoints::Logout | PostEndpoints::LogoutEverywhere)) => None,
+            _ => self.renewed_session.as_deref(),
+        }
+    }
+
     pub fn cache_entry(&self) -> CacheEntry<'_> {
         self.into()
     }
@@ -153,7 +177,7 @@ pub struct Env {
 }
 
 impl Env {
-    fn new(inner: worker::Env) -> Self {
+    pub(crate) fn new(inner: worker::Env) -> Self {
         Self { inner }
     }
 
@@ -174,17 +198,49 @@ pub trait FromEnv: Sized {
     fn from_env(env: &Env) -> Option<Self>;
 }
 
-async fn parse_session(req: &worker::Request, env: &Env) -> Option<app::User> {
+/// Whether the route can be authenticated with a personal API token instead of a session.
+fn accepts_api_token(route: &route::Route) -> bool {
+    use route::{Api, DeleteEndpoints, PostEndpoints, Route};
+
+    matches!(
+        route,
+        Route::Api(Api::Post(PostEndpoints::Upload))
+            | Route::Api(Api::Delete(DeleteEndpoints::DeletePaste(_)))
+    )
+}
+
+async fn parse_api_token(req: &worker::Request, env: &Env) -> Option<app::User> {
+    let token = req.header("Authorization")?;
+    let token = token.strip_prefix("Bearer ")?.trim();
+
+    let Some(tokens) = crate::tokens::Tokens::from_env(env) else {
+        tracing::error!("failed to create Tokens");
+        return None;
+    };
+    match tokens.verify(token).await {
+        Ok(Some(name)) => Some(app::User { name }),
+        Ok(None) => None,
+        Err(err) => {
+            tracing::error!("failed to verify API token: {err:?}");
+            None
+        }
+    }
+}
+
+async fn parse_session(
+    req: &worker::Request,
+    env: &Env,
+) -> Option<crate::sessions::VerifiedSession> {
     let session = req.session()?;
 
-    let dangerous = crate::dangerous::Dangerous::from_env(env).expect("failed to create Dangerous");
-    match dangerous
-        .verify::<app::User>(&session, app::consts::MAX_SESSION_DURATION)
-        .await
-    {
-        Ok(user) => Some(user),
+    let Some(sessions) = crate::sessions::Sessions::from_env(env) else {
+        tracing::error!("failed to create Sessions");
+        return None;
+    };
+    match sessions.verify(&session).await {
+        Ok(user) => user,
         Err(err) => {
-            tracing::warn!("failed to decode session: {err:?}");
+            tracing::error!("failed to verify session: {err:?}");
             None
         }
     }
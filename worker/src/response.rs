@@ -84,6 +84,10 @@ pub trait PartialMeta {
     fn merge_with(self, meta: &mut Meta);
 }
 
+impl PartialMeta for () {
+    fn merge_with(self, _meta: &mut Meta) {}
+}
+
 impl PartialMeta for &StoredPaste {
     fn merge_with(self, meta: &mut Meta) {
         let Some(ref this) = self.metadata else {
@@ -137,6 +141,7 @@ pub struct Response {
     meta: Option<Meta>,
     // better would be some kind of Attribte->Value or Tag collection, good for now
     skip_sentry: bool,
+    private: bool,
 }
 
 impl Response {
@@ -155,6 +160,7 @@ impl Response {
             body: worker::ResponseBody::Empty,
             meta: None,
             skip_sentry: false,
+            private: false,
         }
     }
 
@@ -229,7 +235,18 @@ impl Response {
         )
     }
 
+    pub fn delete_session(self) -> Self {
+        self.append_header(
+            "Set-Cookie",
+            "session=none; Max-Age=0; Secure; SameSite=Lax; Path=/",
+        )
+    }
+
+    /// Sets the `Cache-Control` header, ignored for [private](Self::private) responses.
     pub fn cache(self, cache_control: CacheControl) -> Self {
+        if self.private {
+            return self;
+        }
         self.header("Cache-Control", &cache_control.to_string())
     }
 
@@ -242,6 +259,19 @@ impl Response {
         self
     }
 
+    /// Marks the response as containing private content,
+    /// private responses are never stored in any cache.
+    pub fn private(mut self, private: bool) -> Self {
+        self.private = private;
+        if !private {
+            return self;
+        }
+        self.header(
+            "Cache-Control",
+            &CacheControl::default().private().no_store().to_string(),
+        )
+    }
+
     pub fn result<T>(self) -> std::result::Result<Self, T> {
         Ok(self)
     }
@@ -317,6 +347,11 @@ impl Response {
     pub fn is_skip_sentry(&self) -> bool {
         self.skip_sentry
     }
+
+    /// Whether the response contains private content.
+    pub fn is_private(&self) -> bool {
+        self.private
+    }
 }
 
 // Cache related methods.
@@ -388,6 +423,7 @@ impl From<worker::Response> for Response {
             body,
             meta: None,
             skip_sentry: false,
+            private: false,
         }
     }
 }
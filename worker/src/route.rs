@@ -1,5 +1,5 @@
 use app::PercentRoute;
-use shared::{Id, PasteId, User};
+use shared::{Id, PasteId, User, UserPasteId};
 use worker::{Method, Request};
 
 use crate::{assets, utils::LenientId};
@@ -62,6 +62,18 @@ impl Route {
     }
 }
 
+/// Resolves the paste shown on a frontend page.
+pub fn paste_from_path(path: &str) -> Option<PasteId> {
+    use sycamore_router::Route;
+
+    let PercentRoute(route) = PercentRoute::<app::Route>::default().match_path(path);
+    match route {
+        app::Route::Paste(id) => Some(PasteId::Paste(id)),
+        app::Route::UserPaste(user, id) => Some(UserPasteId { user, id }.into()),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Api {
     Get(GetEndpoints),
@@ -76,6 +88,20 @@ pub enum GetEndpoints {
     // TODO: maybe this should be moved to a different path?
     #[to("/api/internal/user/<user>")]
     User(User),
+    #[to("/api/internal/user/<user>/collections")]
+    UserCollections(User),
+    #[to("/api/internal/user/<user>/collections/<id>")]
+    UserCollection(User, Id),
+    #[to("/api/search")]
+    Search,
+    #[to("/api/internal/tokens")]
+    ApiTokens,
+    #[to("/api/internal/webhooks")]
+    Webhooks,
+    #[to("/api/internal/export")]
+    Export,
+    #[to("/api/internal/admin/reports")]
+    Reports,
     #[to("/<id>/raw")]
     Paste(Id),
     #[to("/u/<name>/<id>/raw")]
@@ -88,6 +114,30 @@ pub enum GetEndpoints {
     PasteXml(Id),
     #[to("/u/<name>/<id>/xml")]
     UserPasteXml(User, Id),
+    #[to("/<id>/md")]
+    PasteMarkdown(Id),
+    #[to("/u/<name>/<id>/md")]
+    UserPasteMarkdown(User, Id),
+    #[to("/<id>/bbcode")]
+    PasteBbCode(Id),
+    #[to("/u/<name>/<id>/bbcode")]
+    UserPasteBbCode(User, Id),
+    #[to("/<id>/summary.json")]
+    PasteBuildSummary(Id),
+    #[to("/u/<name>/<id>/summary.json")]
+    UserPasteBuildSummary(User, Id),
+    #[to("/<id>/preview.png")]
+    PastePreview(Id),
+    #[to("/u/<name>/<id>/preview.png")]
+    UserPastePreview(User, Id),
+    #[to("/api/internal/paste/<id>/revisions")]
+    PasteRevisions(PasteId),
+    #[to("/api/internal/paste/<id>/share")]
+    PasteShare(PasteId),
+    #[to("/u/<name>/<id>/revisions/<revision>/raw")]
+    UserPasteRevision(User, Id, u64),
+    #[to("/u/<name>/<id>/revisions/<revision>/json")]
+    UserPasteRevisionJson(User, Id, u64),
     /// Path of Building endpoint for importing builds.
     /// This supports the anonymous and user scoped paste IDs.
     /// User scoped paste IDs are used in `pob://` protocol links.
@@ -101,6 +151,8 @@ pub enum GetEndpoints {
     Login,
     #[to("/oauth2/authorization/poe")]
     Oauht2Poe,
+    #[to("/login/character/<name>")]
+    ImportCharacter(String),
     #[not_found]
     NotFound,
 }
@@ -111,6 +163,36 @@ pub enum PostEndpoints {
     Upload,
     #[to("/pob/")]
     PobUpload,
+    #[to("/api/internal/paste/<id>/revisions/<revision>/restore")]
+    RestorePasteRevision(PasteId, u64),
+    #[to("/api/internal/paste/<id>/fork")]
+    ForkPaste(PasteId),
+    #[to("/api/internal/paste/<id>/rename")]
+    RenamePaste(PasteId),
+    #[to("/api/internal/paste/<id>/share")]
+    CreatePasteShare(PasteId),
+    #[to("/api/internal/paste/<id>/report")]
+    ReportPaste(PasteId),
+    #[to("/api/internal/admin/paste/<id>/takedown")]
+    TakedownPaste(PasteId),
+    #[to("/api/internal/admin/lifecycle")]
+    RunLifecycle,
+    #[to("/api/internal/admin/maintenance/<task>")]
+    RunMaintenance(String),
+    #[to("/api/internal/tokens")]
+    CreateApiToken,
+    #[to("/api/internal/webhooks")]
+    CreateWebhook,
+    #[to("/api/internal/collections")]
+    SaveCollection,
+    #[to("/api/internal/import")]
+    Import,
+    #[to("/api/internal/import/url")]
+    ImportUrl,
+    #[to("/api/internal/logout")]
+    Logout,
+    #[to("/api/internal/logout/all")]
+    LogoutEverywhere,
     #[not_found]
     NotFound,
 }
@@ -119,6 +201,16 @@ pub enum PostEndpoints {
 pub enum DeleteEndpoints {
     #[to("/api/internal/paste/<id>")]
     DeletePaste(PasteId),
+    #[to("/api/internal/paste/<id>/share")]
+    RevokePasteShare(PasteId),
+    #[to("/api/internal/tokens/<id>")]
+    RevokeApiToken(String),
+    #[to("/api/internal/webhooks/<id>")]
+    DeleteWebhook(String),
+    #[to("/api/internal/collections/<id>")]
+    DeleteCollection(Id),
+    #[to("/api/internal/admin/reports/<id>")]
+    DismissReport(String),
     #[not_found]
     NotFound,
 }
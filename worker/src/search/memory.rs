@@ -0,0 +1,39 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+use shared::PasteId;
+
+use super::{Partition, SearchEntry, SearchIndex};
+use crate::Result;
+
+/// In memory search index, used for tests.
+#[derive(Default)]
+pub struct MemoryIndex {
+    entries: RefCell<BTreeMap<String, SearchEntry>>,
+}
+
+impl SearchIndex for MemoryIndex {
+    async fn put(&self, entry: &SearchEntry) -> Result<()> {
+        self.entries
+            .borrow_mut()
+            .insert(entry.id.to_string(), entry.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, id: &PasteId) -> Result<()> {
+        self.entries.borrow_mut().remove(&id.to_string());
+        Ok(())
+    }
+
+    async fn entries(&self, partition: &Partition, limit: usize) -> Result<Vec<SearchEntry>> {
+        let mut entries = self
+            .entries
+            .borrow()
+            .values()
+            .filter(|entry| Partition::of(entry).contains(partition))
+            .cloned()
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|entry| std::cmp::Reverse(entry.last_modified));
+        entries.truncate(limit);
+        Ok(entries)
+    }
+}
@@ -0,0 +1,501 @@
+//! Search over the metadata of all public user pastes.
+//!
+//! The search index stores a small [`SearchEntry`] per public user paste in multiple
+//! [`Partition`]s. A query only looks at the newest [`crate::consts::MAX_SEARCH_SCAN`]
+//! entries of the most selective partition and evaluates the remaining filters in memory.
+//!
+//! Anonymous pastes are only reachable through their id and are never indexed.
+use std::collections::HashMap;
+
+use pob::PathOfBuilding;
+use serde::{Deserialize, Serialize};
+use shared::{
+    model::{FacetCount, PasteMetadata, PasteSummary, SearchFacets, SearchResults},
+    AscendancyOrClass, GameVersion, PasteId,
+};
+
+use crate::{
+    request_context::{Env, FromEnv, RequestContext},
+    Result,
+};
+
+#[cfg(test)]
+mod memory;
+mod r2;
+
+pub use self::r2::R2Index;
+
+/// Backend of the search index.
+pub trait SearchIndex {
+    /// Adds an entry to the index, replaces an existing entry with the same id.
+    async fn put(&self, entry: &SearchEntry) -> Result<()>;
+
+    /// Removes the entry for a paste from the index.
+    async fn remove(&self, id: &PasteId) -> Result<()>;
+
+    /// Returns up to `limit` entries of a partition, the most recently modified entries first.
+    async fn entries(&self, partition: &Partition, limit: usize) -> Result<Vec<SearchEntry>>;
+}
+
+/// Subset of the index containing all entries with the same value.
+///
+/// Values are stored in lowercase, filters on them are case insensitive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Partition {
+    All,
+    AscendancyOrClass(String),
+    MainSkill(String),
+}
+
+impl Partition {
+    /// All partitions which contain the entry.
+    pub fn of(entry: &SearchEntry) -> Vec<Self> {
+        let mut partitions = vec![
+            Self::All,
+            Self::AscendancyOrClass(entry.ascendancy_or_class.as_str().to_lowercase()),
+        ];
+        if let Some(skill) = &entry.main_skill_name {
+            partitions.push(Self::MainSkill(skill.to_lowercase()));
+        }
+        partitions
+    }
+}
+
+/// Searchable metadata of a public paste.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SearchEntry {
+    pub id: PasteId,
+    pub title: String,
+    #[serde(default)]
+    pub game_version: GameVersion,
+    pub ascendancy_or_class: AscendancyOrClass,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_skill_name: Option<String>,
+    /// Archetype tags of the build, see [`app::pob::archetypes`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub last_modified: u64,
+}
+
+impl SearchEntry {
+    /// Creates the search entry for a paste.
+    ///
+    /// Returns `None` if the paste must not be searchable, only public user pastes are.
+    pub fn new(
+        id: PasteId,
+        metadata: &PasteMetadata,
+        pob: &impl PathOfBuilding,
+        last_modified: u64,
+    ) -> Option<Self> {
+        if metadata.private || !matches!(id, PasteId::UserPaste(_)) {
+            return None;
+        }
+
+        Some(Self {
+            id,
+            title: metadata.title.clone(),
+            game_version: metadata.game_version,
+            ascendancy_or_class: metadata.ascendancy_or_class,
+            version: metadata.version.clone(),
+            main_skill_name: metadata.main_skill_name.clone(),
+            tags: app::pob::archetypes(pob)
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            last_modified,
+        })
+    }
+
+    fn game_version_str(&self) -> &'static str {
+        match self.game_version {
+            GameVersion::One => "1",
+            GameVersion::Two => "2",
+        }
+    }
+
+    fn into_summary(self) -> PasteSummary {
+        PasteSummary {
+            id: self.id,
+            title: self.title,
+            game_version: self.game_version,
+            ascendancy_or_class: self.ascendancy_or_class,
+            version: self.version,
+            main_skill_name: self.main_skill_name,
+            last_modified: self.last_modified,
+            rank: None,
+            private: false,
+            tags: self.tags,
+            description: String::new(),
+        }
+    }
+}
+
+/// A search query, all specified filters must match.
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    /// Free text search on the title, all words need to be contained in the title.
+    pub text: Option<String>,
+    pub ascendancy_or_class: Option<String>,
+    pub main_skill_name: Option<String>,
+    /// Major game version, `1` or `2`.
+    pub game_version: Option<String>,
+    /// Tree version, e.g. `3_25`.
+    pub version: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl SearchQuery {
+    /// Extracts the query from the query parameters of a URL.
+    ///
+    /// Supported parameters are `q`, `class`, `skill`, `game_version`,
+    /// `version` and `tag`, which can be repeated.
+    pub fn from_url(url: &url::Url) -> Self {
+        let mut query = Self::default();
+
+        for (key, value) in url.query_pairs() {
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            let value = Some(value.to_owned());
+
+            match key.as_ref() {
+                "q" => query.text = value,
+                "class" => query.ascendancy_or_class = value,
+                "skill" => query.main_skill_name = value,
+                "game_version" => query.game_version = value,
+                "version" => query.version = value,
+                "tag" => query.tags.extend(value),
+                _ => {}
+            }
+        }
+
+        query
+    }
+
+    /// The partition containing all entries which can match the query.
+    ///
+    /// The main skill is usually the most selective filter.
+    fn partition(&self) -> Partition {
+        if let Some(skill) = &self.main_skill_name {
+            return Partition::MainSkill(skill.to_lowercase());
+        }
+        if let Some(aoc) = &self.ascendancy_or_class {
+            return Partition::AscendancyOrClass(aoc.to_lowercase());
+        }
+        Partition::All
+    }
+
+    fn matches(&self, entry: &SearchEntry) -> bool {
+        fn eq(filter: &Option<String>, value: Option<&str>) -> bool {
+            match (filter, value) {
+                (None, _) => true,
+                (Some(filter), Some(value)) => filter.eq_ignore_ascii_case(value),
+                (Some(_), None) => false,
+            }
+        }
+
+        let text = self.text.as_deref().is_none_or(|text| {
+            let title = entry.title.to_lowercase();
+            text.to_lowercase()
+                .split_whitespace()
+                .all(|word| title.contains(word))
+        });
+
+        text && eq(
+            &self.ascendancy_or_class,
+            Some(entry.ascendancy_or_class.as_str()),
+        ) && eq(&self.main_skill_name, entry.main_skill_name.as_deref())
+            && eq(&self.game_version, Some(entry.game_version_str()))
+            && eq(&self.version, entry.version.as_deref())
+            && self
+                .tags
+                .iter()
+                .all(|tag| entry.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+}
+
+pub struct Search<I = R2Index> {
+    index: I,
+}
+
+impl FromEnv for Search {
+    fn from_env(env: &Env) -> Option<Self> {
+        Some(Self::new(R2Index::from_env(env)?))
+    }
+}
+
+impl<I: SearchIndex> Search<I> {
+    pub fn new(index: I) -> Self {
+        Self { index }
+    }
+
+    /// Updates the index for a paste, private pastes are removed from the index.
+    pub async fn update(&self, id: &PasteId, entry: Option<SearchEntry>) -> Result<()> {
+        match entry {
+            Some(entry) => self.index.put(&entry).await,
+            None => self.index.remove(id).await,
+        }
+    }
+
+    pub async fn remove(&self, id: &PasteId) -> Result<()> {
+        self.index.remove(id).await
+    }
+
+    /// Searches the index, returns at most [`crate::consts::MAX_SEARCH_RESULTS`] pastes.
+    ///
+    /// Only the newest [`crate::consts::MAX_SEARCH_SCAN`] entries of the query's partition
+    /// are looked at, the total and facets are calculated over the matching entries among them.
+    pub async fn search(&self, query: &SearchQuery) -> Result<SearchResults> {
+        let mut entries = self
+            .index
+            .entries(&query.partition(), crate::consts::MAX_SEARCH_SCAN)
+            .await?
+            .into_iter()
+            .filter(|entry| query.matches(entry))
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|entry| std::cmp::Reverse(entry.last_modified));
+
+        let facets = SearchFacets {
+            ascendancy_or_class: facet(&entries, |e| Some(e.ascendancy_or_class.as_str())),
+            main_skill_name: facet(&entries, |e| e.main_skill_name.as_deref()),
+            game_version: facet(&entries, |e| Some(e.game_version_str())),
+            version: facet(&entries, |e| e.version.as_deref()),
+            tags: facet_many(&entries, |e| e.tags.iter().map(String::as_str)),
+        };
+
+        let total = entries.len();
+        let pastes = entries
+            .into_iter()
+            .take(crate::consts::MAX_SEARCH_RESULTS)
+            .map(SearchEntry::into_summary)
+            .collect();
+
+        Ok(SearchResults {
+            total,
+            pastes,
+            facets,
+        })
+    }
+}
+
+fn facet<'a>(
+    entries: &'a [SearchEntry],
+    value: impl Fn(&'a SearchEntry) -> Option<&'a str>,
+) -> Vec<FacetCount> {
+    facet_many(entries, |entry| value(entry).into_iter())
+}
+
+fn facet_many<'a, I>(
+    entries: &'a [SearchEntry],
+    values: impl Fn(&'a SearchEntry) -> I,
+) -> Vec<FacetCount>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let mut counts = HashMap::<&str, usize>::new();
+    for value in entries.iter().flat_map(values) {
+        *counts.entry(value).or_default() += 1;
+    }
+
+    let mut facets = counts
+        .into_iter()
+        .map(|(value, count)| FacetCount {
+            value: value.to_owned(),
+            count,
+        })
+        .collect::<Vec<_>>();
+    facets.sort_unstable_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    facets
+}
+
+/// Updates the search index in the background after a paste has been changed.
+///
+/// Anonymous pastes are never indexed, they are ignored.
+pub(crate) fn on_paste_change(
+    rctx: &RequestContext,
+    id: PasteId,
+    metadata: &PasteMetadata,
+    pob: &impl PathOfBuilding,
+) {
+    if let PasteId::Paste(_) = id {
+        return;
+    }
+
+    let now = worker::Date::now().as_millis();
+    let entry = SearchEntry::new(id.clone(), metadata, pob, now);
+
+    let search = rctx.inject::<Search>();
+    rctx.ctx().wait_until(async move {
+        if let Err(err) = search.update(&id, entry).await {
+            tracing::error!("failed to update search index for {id}: {err:?}");
+        }
+    });
+}
+
+/// Removes a deleted paste from the search index in the background.
+pub(crate) fn on_paste_delete(rctx: &RequestContext, id: PasteId) {
+    let search = rctx.inject::<Search>();
+    rctx.ctx().wait_until(async move {
+        if let Err(err) = search.remove(&id).await {
+            tracing::error!("failed to remove {id} from search index: {err:?}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use shared::{Ascendancy, Class};
+
+    use super::*;
+
+    fn entry(id: &str, title: &str, asc: AscendancyOrClass, skill: &str) -> SearchEntry {
+        SearchEntry {
+            id: id.parse().unwrap(),
+            title: title.to_owned(),
+            game_version: GameVersion::One,
+            ascendancy_or_class: asc,
+            version: Some("3_25".to_owned()),
+            main_skill_name: Some(skill.to_owned()),
+            tags: Vec::new(),
+            last_modified: 0,
+        }
+    }
+
+    fn search() -> Search<memory::MemoryIndex> {
+        let search = Search::new(memory::MemoryIndex::default());
+
+        let mut a = entry(
+            "user:aaaaa",
+            "Cheap Boneshatter Juggernaut",
+            Ascendancy::Juggernaut.into(),
+            "Boneshatter",
+        );
+        a.last_modified = 3;
+        let mut b = entry(
+            "user:bbbbb",
+            "Boneshatter Slayer Crit",
+            Ascendancy::Slayer.into(),
+            "Boneshatter",
+        );
+        b.tags = vec!["Crit".to_owned()];
+        b.last_modified = 2;
+        let mut c = entry("user:ccccc", "League Starter", Class::Witch.into(), "Arc");
+        c.game_version = GameVersion::Two;
+        c.version = None;
+        c.last_modified = 1;
+
+        for entry in [a, b, c] {
+            block_on(search.update(&entry.id.clone(), Some(entry))).unwrap();
+        }
+
+        search
+    }
+
+    fn ids(results: &SearchResults) -> Vec<String> {
+        results.pastes.iter().map(|p| p.id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_search_all() {
+        let results = block_on(search().search(&SearchQuery::default())).unwrap();
+
+        assert_eq!(results.total, 3);
+        assert_eq!(ids(&results), ["user:aaaaa", "user:bbbbb", "user:ccccc"]);
+        assert_eq!(
+            results.facets.main_skill_name,
+            [
+                FacetCount {
+                    value: "Boneshatter".to_owned(),
+                    count: 2
+                },
+                FacetCount {
+                    value: "Arc".to_owned(),
+                    count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_text() {
+        let query = SearchQuery {
+            text: Some("boneshatter CHEAP".to_owned()),
+            ..Default::default()
+        };
+        let results = block_on(search().search(&query)).unwrap();
+
+        assert_eq!(ids(&results), ["user:aaaaa"]);
+    }
+
+    #[test]
+    fn test_search_facets() {
+        let query = SearchQuery {
+            main_skill_name: Some("boneshatter".to_owned()),
+            tags: vec!["crit".to_owned()],
+            ..Default::default()
+        };
+        let results = block_on(search().search(&query)).unwrap();
+        assert_eq!(ids(&results), ["user:bbbbb"]);
+        assert_eq!(results.pastes[0].tags, ["Crit"]);
+
+        let query = SearchQuery {
+            game_version: Some("2".to_owned()),
+            ..Default::default()
+        };
+        let results = block_on(search().search(&query)).unwrap();
+        assert_eq!(ids(&results), ["user:ccccc"]);
+        assert!(results.facets.version.is_empty());
+    }
+
+    #[test]
+    fn test_search_partition() {
+        let query = SearchQuery {
+            ascendancy_or_class: Some("slayer".to_owned()),
+            main_skill_name: Some("BONESHATTER".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            query.partition(),
+            Partition::MainSkill("boneshatter".to_owned())
+        );
+        let results = block_on(search().search(&query)).unwrap();
+        assert_eq!(ids(&results), ["user:bbbbb"]);
+
+        let query = SearchQuery {
+            ascendancy_or_class: Some("Witch".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            query.partition(),
+            Partition::AscendancyOrClass("witch".to_owned())
+        );
+        let results = block_on(search().search(&query)).unwrap();
+        assert_eq!(ids(&results), ["user:ccccc"]);
+    }
+
+    #[test]
+    fn test_search_remove() {
+        let search = search();
+        block_on(search.update(&"user:bbbbb".parse().unwrap(), None)).unwrap();
+
+        let results = block_on(search.search(&SearchQuery::default())).unwrap();
+        assert_eq!(ids(&results), ["user:aaaaa", "user:ccccc"]);
+    }
+
+    #[test]
+    fn test_query_from_url() {
+        let url = "https://pobb.in/api/search?q=rf&class=Chieftain&tag=CI&tag=LL&skill="
+            .parse()
+            .unwrap();
+        let query = SearchQuery::from_url(&url);
+
+        assert_eq!(query.text.as_deref(), Some("rf"));
+        assert_eq!(query.ascendancy_or_class.as_deref(), Some("Chieftain"));
+        assert_eq!(query.main_skill_name, None);
+        assert_eq!(query.tags, ["CI", "LL"]);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use shared::PasteId;
+use worker::{Bucket, Include, Object};
+
+use super::{Partition, SearchEntry, SearchIndex};
+use crate::{
+    request_context::{Env, FromEnv},
+    retry,
+    statsd::Counters,
+    utils::{b64_decode, b64_encode},
+    Result,
+};
+
+const PREFIX: &str = "search/";
+
+/// Search index stored in R2.
+///
+/// Every entry is stored as an empty object per [`Partition`], the entry itself is stored
+/// in the custom metadata, this allows fetching entries with just list operations.
+/// Keys within a partition start with the inverted modification time,
+/// listing a partition returns the newest entries first.
+///
+/// The current entry of a paste is stored under its id as well,
+/// to find the keys to remove when the paste changes.
+pub struct R2Index {
+    bucket: Bucket,
+}
+
+impl FromEnv for R2Index {
+    fn from_env(env: &Env) -> Option<Self> {
+        Some(Self {
+            bucket: env.bucket(crate::consts::R2_STORAGE_BUCKET)?,
+        })
+    }
+}
+
+impl R2Index {
+    /// Returns the entry currently stored for a paste.
+    async fn get(&self, id: &PasteId) -> Result<Option<SearchEntry>> {
+        let path = to_id_path(id);
+
+        sentry::counter(Counters::StorageGet)
+            .inc(1)
+            .tag("type", "search");
+        let obj = retry::retry_all(3, |_| self.bucket.head(&path)).await?;
+
+        match obj {
+            Some(obj) => decode(&obj),
+            None => Ok(None),
+        }
+    }
+
+    async fn put_path(&self, path: &str, custom_metadata: &HashMap<String, String>) -> Result<()> {
+        sentry::counter(Counters::StoragePut)
+            .inc(1)
+            .tag("type", "search");
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(path, worker::Data::Empty)
+                .custom_metadata(custom_metadata.clone())
+                .execute()
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_path(&self, path: &str) -> Result<()> {
+        sentry::counter(Counters::StorageDelete)
+            .inc(1)
+            .tag("type", "search");
+        retry::retry_all(3, |_| self.bucket.delete(path)).await?;
+
+        Ok(())
+    }
+}
+
+impl SearchIndex for R2Index {
+    #[tracing::instrument(skip(self, entry), fields(id = %entry.id))]
+    async fn put(&self, entry: &SearchEntry) -> Result<()> {
+        let previous = self.get(&entry.id).await?;
+
+        let mut custom_metadata = HashMap::new();
+        custom_metadata.insert(
+            "entry".to_owned(),
+            b64_encode(serde_json::to_string(entry)?),
+        );
+
+        let paths = to_paths(entry);
+        for path in &paths {
+            self.put_path(path, &custom_metadata).await?;
+        }
+        self.put_path(&to_id_path(&entry.id), &custom_metadata)
+            .await?;
+
+        // New keys are written first, the paste never disappears from the index.
+        for path in previous.iter().flat_map(to_paths) {
+            if !paths.contains(&path) {
+                self.delete_path(&path).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn remove(&self, id: &PasteId) -> Result<()> {
+        let Some(previous) = self.get(id).await? else {
+            return Ok(());
+        };
+
+        for path in to_paths(&previous) {
+            self.delete_path(&path).await?;
+        }
+        self.delete_path(&to_id_path(id)).await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn entries(&self, partition: &Partition, limit: usize) -> Result<Vec<SearchEntry>> {
+        let prefix = to_prefix(partition);
+
+        let mut entries = Vec::new();
+        let mut cursor = None;
+        while entries.len() < limit {
+            let batch = (limit - entries.len()).min(1000) as u32;
+            sentry::counter(Counters::StorageList)
+                .inc(1)
+                .tag("type", "search");
+            let objects = retry::retry_all(3, |_| {
+                let mut list = self
+                    .bucket
+                    .list()
+                    .prefix(&prefix)
+                    .include(vec![Include::CustomMetadata])
+                    .limit(batch);
+                if let Some(cursor) = &cursor {
+                    list = list.cursor(String::clone(cursor));
+                }
+                list.execute()
+            })
+            .await?;
+
+            for obj in objects.objects() {
+                match decode(&obj)? {
+                    Some(entry) => entries.push(entry),
+                    None => tracing::warn!("search entry {} without metadata", obj.key()),
+                }
+            }
+
+            cursor = objects.cursor();
+            if !objects.truncated() || cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+fn decode(obj: &Object) -> Result<Option<SearchEntry>> {
+    let Some(entry) = obj.custom_metadata()?.remove("entry") else {
+        return Ok(None);
+    };
+    Ok(Some(serde_json::from_slice(&b64_decode(entry)?)?))
+}
+
+fn to_prefix(partition: &Partition) -> String {
+    let encode = |value: &str| utf8_percent_encode(value, NON_ALPHANUMERIC).to_string();
+    match partition {
+        Partition::All => format!("{PREFIX}all/"),
+        Partition::AscendancyOrClass(aoc) => format!("{PREFIX}class/{}/", encode(aoc)),
+        Partition::MainSkill(skill) => format!("{PREFIX}skill/{}/", encode(skill)),
+    }
+}
+
+/// Paths of the entry in all partitions containing the entry.
+fn to_paths(entry: &SearchEntry) -> Vec<String> {
+    let inverted = u64::MAX - entry.last_modified;
+    Partition::of(entry)
+        .iter()
+        .map(|partition| format!("{}{inverted:016x}/{}", to_prefix(partition), entry.id))
+        .collect()
+}
+
+fn to_id_path(id: &PasteId) -> String {
+    format!("{PREFIX}ids/{id}")
+}
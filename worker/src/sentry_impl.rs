@@ -11,9 +11,34 @@ impl FromEnv for sentry::Options {
     }
 }
 
-pub struct Transport(pub worker::Context);
+/// Execution context which can keep the worker alive for background tasks.
+pub trait WaitUntil {
+    fn wait_until<F>(&self, f: F)
+    where
+        F: std::future::Future<Output = ()> + 'static;
+}
+
+impl WaitUntil for worker::Context {
+    fn wait_until<F>(&self, f: F)
+    where
+        F: std::future::Future<Output = ()> + 'static,
+    {
+        worker::Context::wait_until(self, f)
+    }
+}
+
+impl WaitUntil for worker::ScheduleContext {
+    fn wait_until<F>(&self, f: F)
+    where
+        F: std::future::Future<Output = ()> + 'static,
+    {
+        worker::ScheduleContext::wait_until(self, f)
+    }
+}
+
+pub struct Transport<C = worker::Context>(pub C);
 
-impl sentry::Transport for Transport {
+impl<C: WaitUntil> sentry::Transport for Transport<C> {
     fn send(&self, url: String, auth: String, content: Vec<u8>) {
         self.0.wait_until(async move {
             let response = net::Request::post(url)
@@ -0,0 +1,333 @@
+//! User sessions.
+//!
+//! Sessions are signed with [`Dangerous`] and stored in a cookie, additionally
+//! every user has a small session state stored in R2 which allows revoking sessions
+//! before they expire:
+//!
+//! - a single session is revoked by adding its id to the list of revoked sessions,
+//! - all sessions of a user are revoked by incrementing the session generation.
+//!
+//! Verifying a session uses the state cached in the data center for
+//! [`consts::CACHE_SESSION_STATE`], revoked sessions can stay valid in other data centers
+//! for that long. Revoking is a read-modify-write of the state and not atomic,
+//! concurrent revocations for the same user can overwrite each other.
+use serde::{Deserialize, Serialize};
+use shared::User;
+use worker::Bucket;
+
+use crate::{
+    consts,
+    dangerous::Dangerous,
+    request_context::{Env, FromEnv},
+    retry, Result,
+};
+
+/// Signed payload of a session cookie.
+///
+/// The user is flattened into the payload, the app reads the user directly from the cookie.
+#[derive(Debug, Deserialize, Serialize)]
+struct SessionPayload {
+    #[serde(flatten)]
+    user: app::User,
+    /// Unique id of the session, sessions created before revocation was introduced
+    /// do not have an id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sid: Option<String>,
+    #[serde(default)]
+    generation: u64,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct SessionState {
+    generation: u64,
+    revoked: Vec<RevokedSession>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RevokedSession {
+    sid: String,
+    /// Timestamp after which the revoked session would be expired anyways.
+    expires: u64,
+}
+
+impl SessionState {
+    fn is_valid(&self, payload: &SessionPayload) -> bool {
+        if payload.generation != self.generation {
+            return false;
+        }
+
+        match &payload.sid {
+            Some(sid) => !self.revoked.iter().any(|revoked| &revoked.sid == sid),
+            None => true,
+        }
+    }
+
+    fn revoke(&mut self, sid: String, now: u64) {
+        self.revoked.retain(|revoked| revoked.expires > now);
+        let expires = now + app::consts::MAX_SESSION_DURATION.as_millis() as u64;
+        self.revoked.push(RevokedSession { sid, expires });
+    }
+
+    fn revoke_all(&mut self) {
+        self.generation += 1;
+        self.revoked.clear();
+    }
+}
+
+/// A successfully verified session.
+pub struct VerifiedSession {
+    pub user: app::User,
+    /// The session signed with the newest key, if it was signed with an outdated key.
+    pub renewed: Option<String>,
+}
+
+pub struct Sessions {
+    bucket: Bucket,
+    dangerous: Dangerous,
+}
+
+impl FromEnv for Sessions {
+    fn from_env(env: &Env) -> Option<Self> {
+        Some(Self {
+            bucket: env.bucket(consts::R2_STORAGE_BUCKET)?,
+            dangerous: Dangerous::from_env(env)?,
+        })
+    }
+}
+
+impl Sessions {
+    /// Creates a new signed session for the user.
+    #[tracing::instrument(skip(self))]
+    pub async fn create(&self, user: app::User) -> Result<String> {
+        let state = self.state(&user.name).await?;
+
+        let payload = SessionPayload {
+            user,
+            sid: Some(crate::utils::random_string::<12>()?),
+            generation: state.generation,
+        };
+
+        Ok(self.dangerous.sign(&payload).await?)
+    }
+
+    /// Verifies a session and returns the user it belongs to.
+    ///
+    /// Sessions signed with an outdated key are re-signed with the newest key.
+    /// Returns `None` for invalid, expired or revoked sessions.
+    #[tracing::instrument(skip(self, session))]
+    pub async fn verify(&self, session: &str) -> Result<Option<VerifiedSession>> {
+        let Some(payload) = self.decode(session).await else {
+            return Ok(None);
+        };
+
+        let state = self.cached_state(&payload.user.name).await?;
+        if !state.is_valid(&payload) {
+            tracing::warn!(user = %payload.user.name, "session was revoked");
+            return Ok(None);
+        }
+
+        let renewed = match self.dangerous.is_outdated(session) {
+            true => self.renew(session).await,
+            false => None,
+        };
+
+        Ok(Some(VerifiedSession {
+            user: payload.user,
+            renewed,
+        }))
+    }
+
+    /// Revokes a single session.
+    ///
+    /// Invalid sessions and sessions without an id are ignored,
+    /// the latter expire naturally. Not atomic, see the [module docs](self).
+    #[tracing::instrument(skip(self, session))]
+    pub async fn revoke(&self, session: &str) -> Result<()> {
+        let Some(SessionPayload {
+            user,
+            sid: Some(sid),
+            ..
+        }) = self.decode(session).await
+        else {
+            return Ok(());
+        };
+
+        let mut state = self.state(&user.name).await?;
+        state.revoke(sid, worker::Date::now().as_millis());
+        self.set_state(&user.name, &state).await
+    }
+
+    /// Revokes all sessions of a user.
+    ///
+    /// Not atomic, see the [module docs](self).
+    #[tracing::instrument(skip(self))]
+    pub async fn revoke_all(&self, user: &User) -> Result<()> {
+        let mut state = self.state(user).await?;
+        state.revoke_all();
+        self.set_state(user, &state).await
+    }
+
+    async fn renew(&self, session: &str) -> Option<String> {
+        match self
+            .dangerous
+            .resign(session, app::consts::MAX_SESSION_DURATION)
+            .await
+        {
+            Ok(session) => Some(session),
+            Err(err) => {
+                tracing::warn!("failed to renew session: {err:?}");
+                None
+            }
+        }
+    }
+
+    async fn decode(&self, session: &str) -> Option<SessionPayload> {
+        match self
+            .dangerous
+            .verify::<SessionPayload>(session, app::consts::MAX_SESSION_DURATION)
+            .await
+        {
+            Ok(payload) => Some(payload),
+            Err(err) => {
+                tracing::warn!("failed to decode session: {err:?}");
+                None
+            }
+        }
+    }
+
+    /// Returns the session state from the cache of the data center,
+    /// the state is loaded from R2 and cached if it is not cached yet.
+    async fn cached_state(&self, user: &User) -> Result<SessionState> {
+        let cache = worker::Cache::open("sessions".to_owned()).await;
+        let url = to_cache_url(user);
+
+        if let Some(mut response) = cache.get(url.as_str(), true).await? {
+            match serde_json::from_str(&response.text().await?) {
+                Ok(state) => return Ok(state),
+                Err(err) => tracing::warn!("invalid cached session state: {err:?}"),
+            }
+        }
+
+        let state = self.state(user).await?;
+        cache_state(&cache, &url, &state).await;
+        Ok(state)
+    }
+
+    async fn state(&self, user: &User) -> Result<SessionState> {
+        let path = to_path(user);
+        let obj = retry::retry_all(3, |_| self.bucket.get(&path).execute()).await?;
+
+        let Some(body) = obj.as_ref().and_then(|obj| obj.body()) else {
+            return Ok(SessionState::default());
+        };
+
+        Ok(serde_json::from_str(&body.text().await?)?)
+    }
+
+    async fn set_state(&self, user: &User, state: &SessionState) -> Result<()> {
+        let path = to_path(user);
+        let data = serde_json::to_vec(state)?;
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(&path, worker::Data::Bytes(data.clone()))
+                .execute()
+        })
+        .await?;
+
+        // Only the cache of this data center is updated, others expire on their own.
+        let cache = worker::Cache::open("sessions".to_owned()).await;
+        cache_state(&cache, &to_cache_url(user), &state).await;
+
+        Ok(())
+    }
+}
+
+/// Caches a session state, failing to cache the state is not an error.
+async fn cache_state(cache: &worker::Cache, url: &str, state: &SessionState) {
+    if let Err(err) = try_cache_state(cache, url, state).await {
+        tracing::warn!("failed to cache session state: {err:?}");
+    }
+}
+
+async fn try_cache_state(
+    cache: &worker::Cache,
+    url: &str,
+    state: &SessionState,
+) -> worker::Result<()> {
+    let mut response = worker::Response::from_json(state)?;
+    response.headers_mut().set(
+        "Cache-Control",
+        &format!("max-age={}", consts::CACHE_SESSION_STATE.as_secs()),
+    )?;
+    cache.put(url, response).await
+}
+
+fn to_path(user: &User) -> String {
+    format!("users/{user}/sessions")
+}
+
+fn to_cache_url(user: &User) -> String {
+    format!("https://sessions.pobb.in/{user}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(sid: Option<&str>, generation: u64) -> SessionPayload {
+        SessionPayload {
+            user: app::User {
+                name: User::new("user"),
+            },
+            sid: sid.map(str::to_owned),
+            generation,
+        }
+    }
+
+    #[test]
+    fn test_session_state_revoke() {
+        let mut state = SessionState::default();
+        assert!(state.is_valid(&payload(Some("a"), 0)));
+        assert!(state.is_valid(&payload(None, 0)));
+
+        state.revoke("a".to_owned(), 0);
+        assert!(!state.is_valid(&payload(Some("a"), 0)));
+        assert!(state.is_valid(&payload(Some("b"), 0)));
+        assert!(state.is_valid(&payload(None, 0)));
+    }
+
+    #[test]
+    fn test_session_state_revoke_prunes_expired() {
+        let mut state = SessionState::default();
+        state.revoke("a".to_owned(), 0);
+
+        let later = app::consts::MAX_SESSION_DURATION.as_millis() as u64 + 1;
+        state.revoke("b".to_owned(), later);
+
+        assert_eq!(state.revoked.len(), 1);
+        assert_eq!(state.revoked[0].sid, "b");
+    }
+
+    #[test]
+    fn test_session_state_revoke_all() {
+        let mut state = SessionState::default();
+        state.revoke("a".to_owned(), 0);
+        state.revoke_all();
+
+        assert!(state.revoked.is_empty());
+        assert!(!state.is_valid(&payload(Some("b"), 0)));
+        assert!(!state.is_valid(&payload(None, 0)));
+        assert!(state.is_valid(&payload(Some("b"), 1)));
+    }
+
+    #[test]
+    fn test_session_payload_is_compatible_with_app() {
+        let payload = serde_json::to_string(&payload(Some("a"), 3)).unwrap();
+        let user: app::User = serde_json::from_str(&payload).unwrap();
+        assert_eq!(user.name.as_str(), "user");
+
+        let legacy: SessionPayload = serde_json::from_str(r#"{"name":"user"}"#).unwrap();
+        assert_eq!(legacy.sid, None);
+        assert_eq!(legacy.generation, 0);
+    }
+}
@@ -0,0 +1,238 @@
+//! Access control of private pastes.
+//!
+//! Private pastes are only visible to their owner and to everyone with an unlisted
+//! share link. A share link carries a secret token as `share` query parameter,
+//! the token is stored in R2 next to the pastes of the user.
+//! Deleting the record revokes the share link.
+use std::collections::HashMap;
+
+use shared::{
+    model::{PasteMetadata, ShareLink},
+    PasteId, UserPasteId,
+};
+use worker::Bucket;
+
+use crate::{
+    consts,
+    request_context::{Env, FromEnv, RequestContext},
+    retry,
+    utils::{b64_decode, b64_encode},
+    Error, Result,
+};
+
+/// Query parameter carrying the share token.
+pub const SHARE_PARAM: &str = "share";
+
+/// How a paste is accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// The paste is public.
+    Public,
+    /// A private paste accessed by its owner.
+    Owner,
+    /// A private paste accessed through a share link.
+    Shared,
+}
+
+impl Access {
+    /// Whether the paste is private, responses for private pastes must never
+    /// be stored in a cache.
+    pub fn is_private(&self) -> bool {
+        !matches!(self, Self::Public)
+    }
+}
+
+pub struct Shares {
+    bucket: Bucket,
+}
+
+impl FromEnv for Shares {
+    fn from_env(env: &Env) -> Option<Self> {
+        Some(Self {
+            bucket: env.bucket(consts::R2_STORAGE_BUCKET)?,
+        })
+    }
+}
+
+impl Shares {
+    /// Returns the current share link of a paste.
+    #[tracing::instrument(skip(self))]
+    pub async fn get(&self, id: &UserPasteId) -> Result<Option<ShareLink>> {
+        let path = to_path(id);
+        let Some(obj) = retry::retry_all(3, |_| self.bucket.head(&path)).await? else {
+            return Ok(None);
+        };
+
+        to_share_link(&obj)
+    }
+
+    /// Creates a new share link for a paste, replacing the previous link.
+    #[tracing::instrument(skip(self))]
+    pub async fn create(&self, id: &UserPasteId) -> Result<ShareLink> {
+        let link = ShareLink {
+            token: crate::utils::random_string::<18>()?,
+            created: worker::Date::now().as_millis(),
+        };
+        self.put(id, &link).await?;
+
+        Ok(link)
+    }
+
+    /// Moves the share link of a renamed paste, the link keeps its token.
+    #[tracing::instrument(skip(self))]
+    pub async fn rename(&self, from: &UserPasteId, to: &UserPasteId) -> Result<()> {
+        match self.get(from).await? {
+            Some(link) => self.put(to, &link).await?,
+            None => self.revoke(to).await?,
+        }
+        self.revoke(from).await
+    }
+
+    async fn put(&self, id: &UserPasteId, link: &ShareLink) -> Result<()> {
+        let mut custom_metadata = HashMap::new();
+        custom_metadata.insert("share".to_owned(), b64_encode(serde_json::to_string(link)?));
+
+        let path = to_path(id);
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(&path, worker::Data::Empty)
+                .custom_metadata(custom_metadata.clone())
+                .execute()
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Revokes the share link of a paste, revoking a link which does not exist is not an error.
+    #[tracing::instrument(skip(self))]
+    pub async fn revoke(&self, id: &UserPasteId) -> Result<()> {
+        let path = to_path(id);
+        retry::retry_all(3, |_| self.bucket.delete(&path)).await?;
+        Ok(())
+    }
+
+    /// Verifies a share token of a paste.
+    #[tracing::instrument(skip(self, token))]
+    pub async fn verify(&self, id: &UserPasteId, token: &str) -> Result<bool> {
+        let Some(link) = self.get(id).await? else {
+            return Ok(false);
+        };
+
+        Ok(is_same_token(&link.token, token))
+    }
+}
+
+/// Checks whether the current request may access a paste.
+///
+/// Private pastes which are neither accessed by their owner nor through a valid share link
+/// are not found, their existence is not revealed.
+pub(crate) async fn check_access(
+    rctx: &RequestContext,
+    id: &PasteId,
+    metadata: Option<&PasteMetadata>,
+) -> Result<Access> {
+    let is_private = metadata.is_some_and(|m| m.private);
+    let PasteId::UserPaste(up) = id else {
+        return Ok(Access::Public);
+    };
+    if !is_private {
+        return Ok(Access::Public);
+    }
+
+    if rctx.session().map(|u| &u.name) == Some(&up.user) {
+        return Ok(Access::Owner);
+    }
+
+    let not_found = || Error::NotFound("paste", id.to_string());
+    let token = share_token(rctx).ok_or_else(not_found)?;
+
+    let shares = rctx.inject::<Shares>();
+    match shares.verify(up, &token).await? {
+        true => Ok(Access::Shared),
+        false => {
+            tracing::warn!("invalid share token for paste {up}");
+            Err(not_found())
+        }
+    }
+}
+
+/// Checks whether the current request may access the revisions of a paste.
+///
+/// Revisions are only accessible with access to the current paste,
+/// the privacy of a revision itself is not relevant.
+pub(crate) async fn check_revision_access(
+    rctx: &RequestContext,
+    id: &UserPasteId,
+) -> Result<Access> {
+    let id = PasteId::UserPaste(id.clone());
+    let paste = rctx
+        .inject::<crate::storage::Storage>()
+        .get(&id)
+        .await?
+        .ok_or_else(|| Error::NotFound("paste", id.to_string()))?;
+
+    check_access(rctx, &id, paste.metadata.as_ref()).await
+}
+
+/// Extracts the share token from the request.
+pub(crate) fn share_token(rctx: &RequestContext) -> Option<String> {
+    rctx.url()
+        .ok()?
+        .query_pairs()
+        .find_map(|(k, v)| (k == SHARE_PARAM).then(|| v.into_owned()))
+        .filter(|token| !token.is_empty())
+}
+
+/// Appends the share token of the request to a location, keeping access through redirects.
+pub(crate) fn with_share_token(rctx: &RequestContext, location: &str) -> String {
+    match share_token(rctx) {
+        Some(token) => {
+            let token = url::form_urlencoded::byte_serialize(token.as_bytes()).collect::<String>();
+            format!("{location}?{SHARE_PARAM}={token}")
+        }
+        None => location.to_owned(),
+    }
+}
+
+/// Compares two tokens in constant time.
+fn is_same_token(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn to_share_link(obj: &worker::Object) -> Result<Option<ShareLink>> {
+    let Some(link) = obj.custom_metadata()?.remove("share") else {
+        tracing::warn!("share link {} without metadata", obj.key());
+        return Ok(None);
+    };
+
+    Ok(Some(serde_json::from_slice(&b64_decode(link)?)?))
+}
+
+fn to_path(id: &UserPasteId) -> String {
+    format!("users/{}/shares/{}", id.user, id.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_tokens() {
+        assert!(is_same_token("abc-_123", "abc-_123"));
+        assert!(!is_same_token("abc-_123", "abc-_124"));
+        assert!(!is_same_token("abc", "abcd"));
+        assert!(!is_same_token("", "a"));
+    }
+
+    #[test]
+    fn test_access_is_private() {
+        assert!(!Access::Public.is_private());
+        assert!(Access::Owner.is_private());
+        assert!(Access::Shared.is_private());
+    }
+}
@@ -10,6 +10,11 @@ pub enum Counters {
     PobUpload,
     ApiLogin,
     ApiLoginSuccess,
+    Search,
+    RateLimited,
+    Maintenance,
+    MaintenanceItem,
+    Webhook,
 }
 
 impl sentry::MetricName for Counters {
@@ -26,6 +31,11 @@ impl sentry::MetricName for Counters {
             Counters::PobUpload => "pob.upload",
             Counters::ApiLogin => "api.login",
             Counters::ApiLoginSuccess => "api.login_success",
+            Counters::Search => "search.query",
+            Counters::RateLimited => "request.rate_limited",
+            Counters::Maintenance => "maintenance.task",
+            Counters::MaintenanceItem => "maintenance.item",
+            Counters::Webhook => "webhook.delivery",
         }
     }
 }
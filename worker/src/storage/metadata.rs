@@ -0,0 +1,132 @@
+//! Versioning of the paste metadata stored alongside every paste.
+//!
+//! The metadata is derived from the build once on upload, the version of the
+//! derivation is stored with it. Outdated metadata is upgraded when it is read,
+//! the [`crate::maintenance`] migration persists the upgrade for all stored pastes.
+//!
+//! Bump [`METADATA_VERSION`] whenever the derivation changes, e.g. when the title
+//! heuristics or the main skill detection are improved. Changes to the stored schema
+//! must stay backwards compatible through serde defaults.
+use pob::SerdePathOfBuilding;
+use shared::model::PasteMetadata;
+
+use crate::Result;
+
+/// Current version of the stored metadata.
+///
+/// - `1`: unversioned metadata, stored before versioning was introduced.
+/// - `2`: first versioned metadata.
+pub const METADATA_VERSION: u32 = 2;
+
+/// Derives the metadata from a build.
+pub fn to_metadata(pob: &SerdePathOfBuilding) -> PasteMetadata {
+    use pob::{PathOfBuilding, PathOfBuildingExt};
+
+    PasteMetadata {
+        title: app::pob::title(pob),
+        game_version: pob.game_version(),
+        ascendancy_or_class: pob.ascendancy_or_class().to_owned(),
+        version: pob.max_tree_version(),
+        main_skill_name: pob.main_skill_name().map(|x| x.to_owned()),
+        rank: None,
+        private: false,
+        forked_from: None,
+        tags: Vec::new(),
+        description: String::new(),
+    }
+}
+
+/// Combines stored metadata with freshly derived metadata.
+///
+/// Fields chosen by the user are kept, custom titles are only kept when `keep_title` is set,
+/// anonymous pastes always use the derived title.
+pub fn rederive(
+    stored: Option<&PasteMetadata>,
+    derived: PasteMetadata,
+    keep_title: bool,
+) -> PasteMetadata {
+    let Some(stored) = stored else {
+        return derived;
+    };
+
+    PasteMetadata {
+        title: match keep_title {
+            true => stored.title.clone(),
+            false => derived.title,
+        },
+        rank: stored.rank,
+        private: stored.private,
+        forked_from: stored.forked_from.clone(),
+        tags: stored.tags.clone(),
+        description: stored.description.clone(),
+        ..derived
+    }
+}
+
+/// Decodes stored metadata of any version.
+///
+/// Metadata derived with an older version should additionally be
+/// derived again from the build, see [`rederive`].
+pub fn decode(data: &[u8]) -> Result<PasteMetadata> {
+    Ok(serde_json::from_slice(data)?)
+}
+
+/// Parses the stored version, metadata without a version is version 1.
+pub fn parse_version(version: Option<&str>) -> u32 {
+    version.and_then(|v| v.parse().ok()).unwrap_or(1)
+}
+
+/// Whether metadata of this version needs to be derived again.
+pub fn is_outdated(version: u32) -> bool {
+    version < METADATA_VERSION
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::GameVersion;
+
+    use super::*;
+
+    const V1: &str = r#"{"title":"Foo","ascendancy_or_class":"Scion","version":"3_24"}"#;
+
+    #[test]
+    fn test_decode_unversioned() {
+        let metadata = decode(V1.as_bytes()).unwrap();
+        assert_eq!(metadata.title, "Foo");
+        assert_eq!(metadata.game_version, GameVersion::One);
+        assert_eq!(metadata.version.as_deref(), Some("3_24"));
+        assert!(!metadata.private);
+    }
+
+    #[test]
+    fn test_versions() {
+        assert_eq!(parse_version(None), 1);
+        assert_eq!(parse_version(Some("invalid")), 1);
+        assert_eq!(parse_version(Some("2")), 2);
+        assert!(is_outdated(1));
+        assert!(!is_outdated(METADATA_VERSION));
+    }
+
+    #[test]
+    fn test_rederive_keeps_user_fields() {
+        let stored =
+            decode(br#"{"title":"Mine","ascendancy_or_class":"Scion","private":true,"rank":1,"tags":["uber"],"description":"Notes"}"#)
+                .unwrap();
+        let derived =
+            decode(br#"{"title":"Derived","ascendancy_or_class":"Witch","game_version":"Two"}"#)
+                .unwrap();
+
+        let metadata = rederive(Some(&stored), derived.clone(), true);
+        assert_eq!(metadata.title, "Mine");
+        assert_eq!(metadata.game_version, GameVersion::Two);
+        assert!(metadata.private);
+        assert_eq!(metadata.rank.map(|r| r.get()), Some(1));
+        assert_eq!(metadata.tags, ["uber"]);
+        assert_eq!(metadata.description, "Notes");
+
+        let metadata = rederive(Some(&stored), derived.clone(), false);
+        assert_eq!(metadata.title, "Derived");
+
+        assert_eq!(rederive(None, derived.clone(), true), derived);
+    }
+}
@@ -1,21 +1,28 @@
 use serde::{Deserialize, Serialize};
 use shared::{
-    model::{ListPaste, PasteMetadata},
-    PasteId, User,
+    model::{ListPaste, PasteMetadata, PasteRevision},
+    Id, PasteId, User, UserPasteId,
 };
 
 use crate::{
     crypto::Sha1,
+    importers::{self, HttpFetcher, Importer},
     request_context::{Env, FromEnv},
     statsd::Counters,
     Result,
 };
 
-mod pastebin;
+mod metadata;
 mod r2;
+mod revisions;
 mod utils;
 
-pub(crate) use utils::{strip_prefix, to_path_r2, to_prefix_r2};
+pub use metadata::{rederive, to_metadata, METADATA_VERSION};
+pub(crate) use utils::{
+    from_paste_path_r2, from_user_paste_path_r2, strip_prefix, to_access_path_r2,
+    to_listing_path_r2, to_path_r2, to_prefix_r2, to_redirect_path_r2, to_revision_path_r2,
+    to_revision_prefix_r2, to_tombstone_path_r2, ACCESS_PREFIX, PASTES_PREFIX, USERS_PREFIX,
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct StoredPaste {
@@ -25,6 +32,44 @@ pub struct StoredPaste {
     pub last_modified: u64,
     pub entity_id: String,
     pub content: String,
+    /// Expiry time in milliseconds, `None` for pastes which do not expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+
+/// Left behind in place of a paste which was taken down by a moderator.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Tombstone {
+    pub reason: String,
+    pub removed_by: User,
+    pub removed_at: u64,
+}
+
+/// Left behind in place of a user paste which was renamed.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Redirect {
+    /// New id of the paste, pastes are only renamed within the same user.
+    pub to: Id,
+    pub renamed_at: u64,
+}
+
+/// Stored metadata of a paste, as returned by [`Storage::list_metadata_batch`].
+#[derive(Debug)]
+pub struct StoredMetadata {
+    pub id: PasteId,
+    pub metadata: Option<PasteMetadata>,
+    /// Version of the metadata, see [`METADATA_VERSION`].
+    pub version: u32,
+}
+
+/// Lifecycle information of an anonymous paste.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnonymousPaste {
+    pub id: PasteId,
+    /// Time of the last upload in milliseconds.
+    pub created: u64,
+    /// Expiry time in milliseconds, `None` for pastes which do not expire.
+    pub expires: Option<u64>,
 }
 
 pub struct Storage {
@@ -40,24 +85,132 @@ impl FromEnv for Storage {
 }
 
 impl Storage {
+    /// Retrieves a paste.
+    ///
+    /// Returns [`Error::Gone`](crate::Error::Gone) for pastes which were taken down.
     pub async fn get(&self, id: &PasteId) -> Result<Option<StoredPaste>> {
-        if pastebin::could_be_pastebin_id(id) {
+        if importers::could_be_pastebin_id(id) {
+            if self.is_removed(id).await? {
+                return Err(crate::Error::Gone("paste", id.to_string()));
+            }
+
             tracing::info!("fetching from pastebin.com");
             sentry::counter(Counters::StorageGet)
                 .inc(1)
                 .tag("type", "pastebin");
-            return pastebin::get(id).await;
+            let content = Importer::new(HttpFetcher).pastebin(id).await?;
+            return Ok(content.map(|content| StoredPaste {
+                content,
+                entity_id: format!("pastebin-{id}"),
+                last_modified: 0,
+                metadata: None,
+                expires: None,
+            }));
         }
 
         sentry::counter(Counters::StorageGet)
             .inc(1)
             .tag("type", "r2");
-        self.r2.get(id).await
+        let paste = self.r2.get(id).await?;
+
+        // Tombstones are only relevant for pastes which do not exist (anymore).
+        if paste.is_none() && self.is_removed(id).await? {
+            return Err(crate::Error::Gone("paste", id.to_string()));
+        }
+
+        Ok(paste)
+    }
+
+    /// Takes down a paste and all its revisions, leaving a tombstone in its place.
+    ///
+    /// Returns the deleted revisions, see [`Self::delete`].
+    pub async fn takedown(&self, id: &PasteId, tombstone: &Tombstone) -> Result<Vec<u64>> {
+        self.r2.put_tombstone(id, tombstone).await?;
+        self.delete(id).await
+    }
+
+    /// Whether the paste was taken down.
+    pub async fn is_removed(&self, id: &PasteId) -> Result<bool> {
+        sentry::counter(Counters::StorageGet)
+            .inc(1)
+            .tag("type", "tombstone");
+        Ok(self.r2.get_tombstone(id).await?.is_some())
+    }
+
+    /// Moves a user paste and all its revisions to a new id.
+    ///
+    /// A redirect to the new id is left in place of the old paste, see [`Self::get_redirect`].
+    /// The new id must not be in use.
+    ///
+    /// Returns the revisions deleted from the old id, see [`Self::delete`].
+    pub async fn rename(&self, from: &UserPasteId, to: &UserPasteId) -> Result<Vec<u64>> {
+        sentry::counter(Counters::StoragePut)
+            .inc(1)
+            .tag("type", "rename");
+        self.r2.rename(from, to).await?;
+        self.update_listing(&from.user, |pastes| {
+            if let Some(paste) = pastes.iter_mut().find(|p| p.name == from.id.as_str()) {
+                paste.name = to.id.to_string();
+            }
+        })
+        .await;
+
+        let redirect = Redirect {
+            to: to.id.clone(),
+            renamed_at: worker::Date::now().as_millis(),
+        };
+        self.r2.put_redirect(from, &redirect).await?;
+        // The new id may have been renamed before, the paste replaces the redirect.
+        self.r2.delete_redirect(to).await?;
+
+        self.delete(&from.clone().into()).await
     }
 
-    pub async fn delete(&self, id: &PasteId) -> Result<()> {
+    /// Returns the new id of a renamed user paste.
+    ///
+    /// Only pastes which no longer exist are redirected, callers should check the paste first.
+    pub async fn get_redirect(&self, id: &UserPasteId) -> Result<Option<UserPasteId>> {
+        sentry::counter(Counters::StorageGet)
+            .inc(1)
+            .tag("type", "redirect");
+        let redirect = self.r2.get_redirect(id).await?;
+
+        Ok(redirect.map(|redirect| UserPasteId {
+            user: id.user.clone(),
+            id: redirect.to,
+        }))
+    }
+
+    /// Deletes a paste and all its revisions.
+    ///
+    /// Returns the deleted revisions of a user paste, their cached URLs still need to be purged.
+    pub async fn delete(&self, id: &PasteId) -> Result<Vec<u64>> {
         sentry::counter(Counters::StorageDelete).inc(1);
-        self.r2.delete(id).await
+        self.r2.delete(id).await?;
+
+        if let PasteId::Paste(_) = id {
+            self.r2.delete_access(id).await?;
+        }
+
+        if let PasteId::UserPaste(up) = id {
+            self.update_listing(&up.user, |pastes| {
+                pastes.retain(|p| p.name != up.id.as_str())
+            })
+            .await;
+
+            let revisions = self
+                .r2
+                .list_revisions(up)
+                .await?
+                .into_iter()
+                .map(|r| r.revision)
+                .collect::<Vec<_>>();
+            self.r2.delete_revisions(up, &revisions).await?;
+
+            return Ok(revisions);
+        }
+
+        Ok(Vec::new())
     }
 
     pub async fn put(
@@ -68,11 +221,241 @@ impl Storage {
         metadata: Option<&PasteMetadata>,
     ) -> Result<()> {
         sentry::counter(Counters::StoragePut).inc(1);
-        self.r2.put(id, sha1, data, metadata).await
+        self.r2.put(id, sha1, data, metadata, None).await?;
+
+        if let (PasteId::UserPaste(up), Some(metadata)) = (id, metadata) {
+            let paste = ListPaste {
+                name: up.id.to_string(),
+                metadata: metadata.clone(),
+                last_modified: worker::Date::now().as_millis(),
+            };
+            self.update_listing(&up.user, |pastes| {
+                pastes.retain(|p| p.name != paste.name);
+                pastes.push(paste);
+            })
+            .await;
+        }
+
+        Ok(())
+    }
+
+    /// Stores a paste which expires at the `expires` timestamp in milliseconds.
+    ///
+    /// Expired pastes are no longer returned and eventually deleted by [`crate::lifecycle`].
+    /// Anonymous ids are derived from the content, an existing paste which lives longer
+    /// is kept as is.
+    pub async fn put_expiring(
+        &self,
+        id: &PasteId,
+        sha1: &Sha1,
+        data: &[u8],
+        metadata: Option<&PasteMetadata>,
+        expires: u64,
+    ) -> Result<()> {
+        if let Some(existing) = self.r2.expires(id).await? {
+            if existing.is_none_or(|existing| existing >= expires) {
+                tracing::debug!("keeping existing paste {id}, it expires later");
+                return Ok(());
+            }
+        }
+
+        sentry::counter(Counters::StoragePut)
+            .inc(1)
+            .tag("type", "expiring");
+        self.r2.put(id, sha1, data, metadata, Some(expires)).await
     }
 
+    /// Records an access of an anonymous paste, used to expire pastes which are no longer used.
+    ///
+    /// The paste is not checked to exist, only record accesses of successfully loaded pastes.
+    /// The access is only written when the recorded access is older than
+    /// [`crate::consts::PASTE_ACCESS_INTERVAL`].
+    pub async fn touch(&self, id: &PasteId) -> Result<()> {
+        if !matches!(id, PasteId::Paste(_)) || importers::could_be_pastebin_id(id) {
+            return Ok(());
+        }
+
+        let now = worker::Date::now().as_millis();
+        let interval = crate::consts::PASTE_ACCESS_INTERVAL.as_millis() as u64;
+        if self
+            .last_access(id)
+            .await?
+            .is_some_and(|accessed| accessed.saturating_add(interval) > now)
+        {
+            return Ok(());
+        }
+
+        sentry::counter(Counters::StoragePut)
+            .inc(1)
+            .tag("type", "access");
+        self.r2.touch(id).await
+    }
+
+    /// Lists the ids and stored metadata of all pastes, anonymous and user pastes, in batches.
+    ///
+    /// A batch looks at up to `limit` stored objects, not all of them are pastes.
+    /// Returns the cursor of the next batch, `None` once all pastes were listed.
+    pub async fn list_metadata_batch(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<StoredMetadata>, Option<String>)> {
+        sentry::counter(Counters::StorageList)
+            .inc(1)
+            .tag("type", "all");
+        self.r2.list_metadata_batch(cursor, limit).await
+    }
+
+    /// Lists the ids and stored metadata of user pastes in batches.
+    ///
+    /// A batch looks at up to `limit` objects stored for users, not all of them are pastes.
+    /// Returns the cursor of the next batch, `None` once all pastes were listed.
+    pub async fn list_user_metadata(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<StoredMetadata>, Option<String>)> {
+        sentry::counter(Counters::StorageList)
+            .inc(1)
+            .tag("type", "users");
+        self.r2.list_user_metadata(cursor, limit).await
+    }
+
+    /// Replaces the metadata of a stored paste, without changing the paste itself.
+    ///
+    /// The `version` is the [`METADATA_VERSION`] the metadata was derived with.
+    pub async fn update_metadata(
+        &self,
+        id: &PasteId,
+        metadata: &PasteMetadata,
+        version: u32,
+    ) -> Result<()> {
+        sentry::counter(Counters::StoragePut)
+            .inc(1)
+            .tag("type", "metadata");
+        self.r2.update_metadata(id, metadata, version).await?;
+
+        if let PasteId::UserPaste(up) = id {
+            self.update_listing(&up.user, |pastes| {
+                if let Some(paste) = pastes.iter_mut().find(|p| p.name == up.id.as_str()) {
+                    paste.metadata = metadata.clone();
+                }
+            })
+            .await;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the last recorded access of an anonymous paste, see [`Self::touch`].
+    pub async fn last_access(&self, id: &PasteId) -> Result<Option<u64>> {
+        sentry::counter(Counters::StorageGet)
+            .inc(1)
+            .tag("type", "access");
+        self.r2.last_access(id).await
+    }
+
+    /// Lists anonymous pastes in batches.
+    ///
+    /// A batch looks at up to `limit` stored anonymous pastes.
+    /// Returns the cursor of the next batch, `None` once all pastes were listed.
+    pub async fn list_anonymous(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<AnonymousPaste>, Option<String>)> {
+        sentry::counter(Counters::StorageList)
+            .inc(1)
+            .tag("type", "anonymous");
+        self.r2.list_anonymous(cursor, limit).await
+    }
+
+    /// Lists all pastes of a user.
+    ///
+    /// Pastes are listed from a single stored listing object, which is kept up to date
+    /// by all changes made through the storage. A missing listing is rebuilt by listing
+    /// all pastes of the user.
     pub async fn list(&self, user: &User) -> Result<Vec<ListPaste>> {
+        sentry::counter(Counters::StorageGet)
+            .inc(1)
+            .tag("type", "listing");
+        if let Some(pastes) = self.r2.get_listing(user).await? {
+            return Ok(pastes);
+        }
+
+        sentry::counter(Counters::StorageList).inc(1);
+        let pastes = self.r2.list(user).await?;
+        if let Err(err) = self.r2.put_listing(user, &pastes).await {
+            tracing::warn!("failed to store listing of user {user}: {err:?}");
+        }
+
+        Ok(pastes)
+    }
+
+    /// Applies a change to the stored listing of a user, see [`Self::list`].
+    ///
+    /// Users without a stored listing are skipped, the listing is built on the next access.
+    /// The read-modify-write is not atomic, concurrent changes of the same user can lose
+    /// an update. The listing is deleted when the update fails, to be rebuilt on the next access.
+    async fn update_listing(&self, user: &User, f: impl FnOnce(&mut Vec<ListPaste>)) {
+        let result = async {
+            let Some(mut pastes) = self.r2.get_listing(user).await? else {
+                return Ok(());
+            };
+            f(&mut pastes);
+            self.r2.put_listing(user, &pastes).await
+        };
+
+        if let Err(err) = result.await {
+            tracing::error!("failed to update listing of user {user}: {err:?}");
+            if let Err(err) = self.r2.delete_listing(user).await {
+                tracing::error!("failed to delete listing of user {user}: {err:?}");
+            }
+        }
+    }
+
+    pub async fn get_revision(
+        &self,
+        id: &UserPasteId,
+        revision: u64,
+    ) -> Result<Option<StoredPaste>> {
+        sentry::counter(Counters::StorageGet)
+            .inc(1)
+            .tag("type", "revision");
+        self.r2.get_revision(id, revision).await
+    }
+
+    /// Lists all revisions of a user paste, the newest revision first.
+    pub async fn list_revisions(&self, id: &UserPasteId) -> Result<Vec<PasteRevision>> {
         sentry::counter(Counters::StorageList).inc(1);
-        self.r2.list(user).await
+        revisions::list(&self.r2, id).await
+    }
+
+    /// Stores the already loaded current version of a user paste as a revision.
+    ///
+    /// Only the newest [`crate::consts::MAX_PASTE_REVISIONS`] revisions are kept,
+    /// returns the pruned revisions.
+    pub async fn archive(&self, id: &UserPasteId, current: &StoredPaste) -> Result<Vec<u64>> {
+        sentry::counter(Counters::StoragePut)
+            .inc(1)
+            .tag("type", "revision");
+        revisions::archive(&self.r2, id, current).await
+    }
+
+    /// Archives the current version of a user paste and returns the revision to restore,
+    /// together with the pruned revisions.
+    ///
+    /// The restored revision still needs to be stored with [`Self::put`].
+    /// Returns `None` if the revision does not exist.
+    pub async fn restore_revision(
+        &self,
+        id: &UserPasteId,
+        revision: u64,
+    ) -> Result<Option<(StoredPaste, Vec<u64>)>> {
+        sentry::counter(Counters::StorageGet)
+            .inc(1)
+            .tag("type", "revision");
+        let current = self.r2.get(&id.clone().into()).await?;
+        revisions::restore(&self.r2, id, current.as_ref(), revision).await
     }
 }
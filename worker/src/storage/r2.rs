@@ -1,12 +1,15 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
 use shared::{
-    model::{ListPaste, PasteMetadata},
-    PasteId, User,
+    model::{ListPaste, PasteMetadata, PasteRevision},
+    PasteId, User, UserPasteId,
 };
 use worker::{Bucket, HttpMetadata, Include, Object};
 
-use super::StoredPaste;
+use super::{
+    revisions::RevisionStore, AnonymousPaste, Redirect, StoredMetadata, StoredPaste, Tombstone,
+};
 use crate::{
     crypto::Sha1,
     request_context::{Env, FromEnv},
@@ -31,25 +34,59 @@ impl R2Storage {
     #[tracing::instrument(skip(self))]
     pub async fn get(&self, id: &PasteId) -> Result<Option<StoredPaste>> {
         let path = super::to_path_r2(id)?;
+        self.get_path(&path).await
+    }
 
-        let obj = retry::retry_all(3, |_| self.bucket.get(&path).execute()).await?;
+    #[tracing::instrument(skip(self))]
+    pub async fn get_revision(
+        &self,
+        id: &UserPasteId,
+        revision: u64,
+    ) -> Result<Option<StoredPaste>> {
+        let path = super::to_revision_path_r2(id, revision);
+        let stored = self.get_path(&path).await?;
+
+        // The object was uploaded when the revision was archived,
+        // the revision itself is the original modification time.
+        Ok(stored.map(|stored| StoredPaste {
+            last_modified: revision,
+            ..stored
+        }))
+    }
+
+    async fn get_path(&self, path: &str) -> Result<Option<StoredPaste>> {
+        let obj = retry::retry_all(3, |_| self.bucket.get(path).execute()).await?;
 
         let Some(obj) = obj else {
             return Ok(None);
         };
 
+        let now = worker::Date::now().as_millis();
+        let Lifecycle { expires, .. } = lifecycle(&obj)?;
+        if expires.is_some_and(|expires| expires <= now) {
+            tracing::debug!("paste {path} expired");
+            return Ok(None);
+        }
+
         let content = match obj.body() {
             Some(body) => body.text().await?,
             None => return Ok(None),
         };
 
         let (mtime, metadata) = to_metadata(&obj)?;
+        let metadata = match metadata {
+            Some(metadata) if super::metadata::is_outdated(metadata_version(&obj)?) => {
+                Some(upgrade(path, metadata, &content))
+            }
+            metadata => metadata,
+        };
 
         Ok(Some(StoredPaste {
             content,
             metadata,
             entity_id: obj.etag(),
             last_modified: mtime,
+            expires,
         }))
     }
 
@@ -62,6 +99,16 @@ impl R2Storage {
         Ok(())
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn delete_revisions(&self, id: &UserPasteId, revisions: &[u64]) -> Result<()> {
+        for &revision in revisions {
+            let path = super::to_revision_path_r2(id, revision);
+            retry::retry_all(3, |_| self.bucket.delete(&path)).await?;
+        }
+
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self, sha1, data))]
     pub async fn put(
         &self,
@@ -69,9 +116,74 @@ impl R2Storage {
         sha1: &Sha1,
         data: &[u8],
         metadata: Option<&PasteMetadata>,
+        expires: Option<u64>,
     ) -> Result<()> {
         let path = super::to_path_r2(id)?;
+        self.put_path(&path, Some(sha1), data, metadata, expires)
+            .await
+    }
+
+    /// Returns the expiry of a paste, `Some(None)` for a paste which never expires
+    /// and `None` if the paste does not exist or already expired.
+    #[tracing::instrument(skip(self))]
+    pub async fn expires(&self, id: &PasteId) -> Result<Option<Option<u64>>> {
+        let path = super::to_path_r2(id)?;
+        let obj = retry::retry_all(3, |_| self.bucket.head(&path)).await?;
+
+        let now = worker::Date::now().as_millis();
+        match obj {
+            Some(obj) => match lifecycle(&obj)?.expires {
+                Some(expires) if expires <= now => Ok(None),
+                expires => Ok(Some(expires)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the last access time of a paste, the upload time of its access marker.
+    #[tracing::instrument(skip(self))]
+    pub async fn last_access(&self, id: &PasteId) -> Result<Option<u64>> {
+        let path = super::to_access_path_r2(id)?;
+        let obj = retry::retry_all(3, |_| self.bucket.head(&path)).await?;
+        Ok(obj.map(|obj| obj.uploaded().as_millis()))
+    }
+
+    /// Updates the access marker of a paste, the upload time of the marker
+    /// is the last access time of the paste.
+    #[tracing::instrument(skip(self))]
+    pub async fn touch(&self, id: &PasteId) -> Result<()> {
+        let path = super::to_access_path_r2(id)?;
+        retry::retry_all(3, |_| self.bucket.put(&path, worker::Data::Empty).execute()).await?;
+        Ok(())
+    }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn delete_access(&self, id: &PasteId) -> Result<()> {
+        let path = super::to_access_path_r2(id)?;
+        retry::retry_all(3, |_| self.bucket.delete(&path)).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, data))]
+    pub async fn put_revision(
+        &self,
+        id: &UserPasteId,
+        revision: u64,
+        data: &[u8],
+        metadata: Option<&PasteMetadata>,
+    ) -> Result<()> {
+        let path = super::to_revision_path_r2(id, revision);
+        self.put_path(&path, None, data, metadata, None).await
+    }
+
+    async fn put_path(
+        &self,
+        path: &str,
+        sha1: Option<&Sha1>,
+        data: &[u8],
+        metadata: Option<&PasteMetadata>,
+        expires: Option<u64>,
+    ) -> Result<()> {
         let metadata = metadata
             .map(serde_json::to_string)
             .transpose()?
@@ -80,29 +192,412 @@ impl R2Storage {
         let mut custom_metdata = HashMap::new();
         if let Some(metadata) = metadata {
             custom_metdata.insert("metadata".to_owned(), metadata);
+            custom_metdata.insert(
+                "metadata_version".to_owned(),
+                super::METADATA_VERSION.to_string(),
+            );
+        }
+        let created = worker::Date::now().as_millis();
+        custom_metdata.insert("created".to_owned(), created.to_string());
+        if let Some(expires) = expires {
+            custom_metdata.insert("expires".to_owned(), expires.to_string());
         }
 
         retry::retry_all(3, |_| {
-            self.bucket
+            let mut put = self
+                .bucket
                 // TODO the to_vec() is wasted compute, but the worker crate sucks.
-                .put(&path, worker::Data::Bytes(data.to_vec()))
+                .put(path, worker::Data::Bytes(data.to_vec()))
+                .http_metadata(HttpMetadata {
+                    content_type: Some("text/plain".to_owned()),
+                    ..Default::default()
+                })
+                .custom_metadata(custom_metdata.clone());
+            if let Some(sha1) = sha1 {
+                put = put.sha1(sha1.0);
+            }
+            put.execute()
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn put_tombstone(&self, id: &PasteId, tombstone: &Tombstone) -> Result<()> {
+        let path = super::to_tombstone_path_r2(id)?;
+        let data = serde_json::to_vec(tombstone)?;
+
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(&path, worker::Data::Bytes(data.clone()))
+                .execute()
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_tombstone(&self, id: &PasteId) -> Result<Option<Tombstone>> {
+        let path = super::to_tombstone_path_r2(id)?;
+        let obj = retry::retry_all(3, |_| self.bucket.get(&path).execute()).await?;
+
+        let Some(body) = obj.as_ref().and_then(|obj| obj.body()) else {
+            return Ok(None);
+        };
+
+        Ok(Some(serde_json::from_str(&body.text().await?)?))
+    }
+
+    /// Copies a paste and all its revisions to a new id, the copies keep their modification time.
+    #[tracing::instrument(skip(self))]
+    pub async fn rename(&self, from: &UserPasteId, to: &UserPasteId) -> Result<()> {
+        let from_path = super::to_path_r2(&from.clone().into())?;
+        let to_path = super::to_path_r2(&to.clone().into())?;
+        if !self.copy_path(&from_path, &to_path).await? {
+            return Err(crate::Error::NotFound("paste", from.to_string()));
+        }
+
+        for revision in self.list_revisions(from).await? {
+            let from_path = super::to_revision_path_r2(from, revision.revision);
+            let to_path = super::to_revision_path_r2(to, revision.revision);
+            self.copy_path(&from_path, &to_path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies an object with all its metadata, returns `false` if the object does not exist.
+    async fn copy_path(&self, from: &str, to: &str) -> Result<bool> {
+        let obj = retry::retry_all(3, |_| self.bucket.get(from).execute()).await?;
+
+        let Some((obj, body)) = obj.and_then(|obj| obj.body().map(|body| (obj, body))) else {
+            return Ok(false);
+        };
+        let data = body.bytes().await?;
+
+        let (mtime, _) = to_metadata(&obj)?;
+        let mut custom_metdata = obj.custom_metadata()?;
+        custom_metdata.insert("mtime".to_owned(), (mtime as f64 / 1000.0).to_string());
+
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(to, worker::Data::Bytes(data.clone()))
                 .http_metadata(HttpMetadata {
                     content_type: Some("text/plain".to_owned()),
                     ..Default::default()
                 })
                 .custom_metadata(custom_metdata.clone())
-                .sha1(sha1.0)
                 .execute()
         })
         .await?;
 
+        Ok(true)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn put_redirect(&self, id: &UserPasteId, redirect: &Redirect) -> Result<()> {
+        let path = super::to_redirect_path_r2(id);
+        let data = serde_json::to_vec(redirect)?;
+
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(&path, worker::Data::Bytes(data.clone()))
+                .execute()
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_redirect(&self, id: &UserPasteId) -> Result<Option<Redirect>> {
+        let path = super::to_redirect_path_r2(id);
+        let obj = retry::retry_all(3, |_| self.bucket.get(&path).execute()).await?;
+
+        let Some(body) = obj.as_ref().and_then(|obj| obj.body()) else {
+            return Ok(None);
+        };
+
+        Ok(Some(serde_json::from_str(&body.text().await?)?))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn delete_redirect(&self, id: &UserPasteId) -> Result<()> {
+        let path = super::to_redirect_path_r2(id);
+        retry::retry_all(3, |_| self.bucket.delete(&path)).await?;
         Ok(())
     }
 
+    /// Lists the anonymous pastes within the next `limit` objects.
+    #[tracing::instrument(skip(self))]
+    pub async fn list_anonymous(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<AnonymousPaste>, Option<String>)> {
+        let mut pastes = Vec::new();
+        let cursor = self
+            .list_batch(super::PASTES_PREFIX, cursor, limit, |obj| {
+                let Some(id) = super::from_paste_path_r2(&obj.key()) else {
+                    tracing::warn!("invalid anonymous paste path {}", obj.key());
+                    return Ok(());
+                };
+                let Lifecycle { created, expires } = lifecycle(obj)?;
+                pastes.push(AnonymousPaste {
+                    id,
+                    created,
+                    expires,
+                });
+                Ok(())
+            })
+            .await?;
+
+        Ok((pastes, cursor))
+    }
+
+    /// Lists the metadata of all pastes within the next `limit` objects,
+    /// anonymous pastes are listed before user pastes.
+    ///
+    /// The cursor is the R2 cursor prefixed with the storage prefix it belongs to.
+    #[tracing::instrument(skip(self))]
+    pub async fn list_metadata_batch(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<StoredMetadata>, Option<String>)> {
+        let (prefix, cursor) = match cursor.as_deref() {
+            Some(cursor) if cursor.starts_with(super::USERS_PREFIX) => {
+                (super::USERS_PREFIX, &cursor[super::USERS_PREFIX.len()..])
+            }
+            Some(cursor) => (
+                super::PASTES_PREFIX,
+                cursor.strip_prefix(super::PASTES_PREFIX).unwrap_or(cursor),
+            ),
+            None => (super::PASTES_PREFIX, ""),
+        };
+        let cursor = Some(cursor.to_owned()).filter(|cursor| !cursor.is_empty());
+
+        let (pastes, cursor) = self.list_metadata(prefix, cursor, limit).await?;
+
+        let cursor = match cursor {
+            Some(cursor) => Some(format!("{prefix}{cursor}")),
+            // Continue with the user pastes after all anonymous pastes were listed.
+            None if prefix == super::PASTES_PREFIX => Some(super::USERS_PREFIX.to_owned()),
+            None => None,
+        };
+
+        Ok((pastes, cursor))
+    }
+
+    /// Lists the metadata of the user pastes within the next `limit` user objects.
+    #[tracing::instrument(skip(self))]
+    pub async fn list_user_metadata(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<StoredMetadata>, Option<String>)> {
+        self.list_metadata(super::USERS_PREFIX, cursor, limit).await
+    }
+
+    async fn list_metadata(
+        &self,
+        prefix: &str,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<StoredMetadata>, Option<String>)> {
+        let mut pastes = Vec::new();
+        let cursor = self
+            .list_batch(prefix, cursor, limit, |obj| {
+                let key = obj.key();
+                let id = match prefix == super::USERS_PREFIX {
+                    true => super::from_user_paste_path_r2(&key),
+                    false => super::from_paste_path_r2(&key),
+                };
+                if let Some(id) = id {
+                    pastes.push(StoredMetadata {
+                        id,
+                        metadata: to_metadata(obj)?.1,
+                        version: metadata_version(obj)?,
+                    });
+                }
+                Ok(())
+            })
+            .await?;
+
+        Ok((pastes, cursor))
+    }
+
+    /// Rewrites a paste with new metadata.
+    ///
+    /// R2 does not support updating metadata in place, the object is uploaded again
+    /// and keeps its modification time through the `mtime` metadata.
+    #[tracing::instrument(skip(self, metadata))]
+    pub async fn update_metadata(
+        &self,
+        id: &PasteId,
+        metadata: &PasteMetadata,
+        version: u32,
+    ) -> Result<()> {
+        let path = super::to_path_r2(id)?;
+        let obj = retry::retry_all(3, |_| self.bucket.get(&path).execute()).await?;
+
+        let Some((obj, body)) = obj.and_then(|obj| obj.body().map(|body| (obj, body))) else {
+            return Err(crate::Error::NotFound("paste", id.to_string()));
+        };
+        let data = body.bytes().await?;
+
+        let (mtime, _) = to_metadata(&obj)?;
+        let mut custom_metdata = obj.custom_metadata()?;
+        custom_metdata.insert(
+            "metadata".to_owned(),
+            b64_encode(serde_json::to_string(metadata)?),
+        );
+        custom_metdata.insert("metadata_version".to_owned(), version.to_string());
+        custom_metdata.insert("mtime".to_owned(), (mtime as f64 / 1000.0).to_string());
+
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(&path, worker::Data::Bytes(data.clone()))
+                .http_metadata(HttpMetadata {
+                    content_type: Some("text/plain".to_owned()),
+                    ..Default::default()
+                })
+                .custom_metadata(custom_metdata.clone())
+                .execute()
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists up to `limit` objects, continuing from `cursor`.
+    ///
+    /// Returns the cursor of the next batch, `None` once all objects were listed.
+    async fn list_batch(
+        &self,
+        prefix: &str,
+        cursor: Option<String>,
+        limit: usize,
+        mut f: impl FnMut(&Object) -> Result<()>,
+    ) -> Result<Option<String>> {
+        let objects = retry::retry_all(3, |_| {
+            let mut list = self
+                .bucket
+                .list()
+                .prefix(prefix)
+                .include(vec![Include::CustomMetadata])
+                .limit(limit.min(1000) as u32);
+            if let Some(cursor) = &cursor {
+                list = list.cursor(String::clone(cursor));
+            }
+            list.execute()
+        })
+        .await?;
+
+        for obj in objects.objects() {
+            f(&obj)?;
+        }
+
+        Ok(objects.cursor().filter(|_| objects.truncated()))
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn list(&self, user: &User) -> Result<Vec<ListPaste>> {
         let prefix = super::to_prefix_r2(user);
 
+        let mut pastes = Vec::new();
+        let mut cursor = None;
+        loop {
+            let objects = retry::retry_all(3, |_| {
+                let mut list = self
+                    .bucket
+                    .list()
+                    .prefix(&prefix)
+                    .include(vec![Include::CustomMetadata])
+                    .limit(1000);
+                if let Some(cursor) = &cursor {
+                    list = list.cursor(String::clone(cursor));
+                }
+                list.execute()
+            })
+            .await?;
+
+            for obj in objects.objects() {
+                let (mtime, metadata) = to_metadata(&obj)?;
+                let metadata = metadata.ok_or_else(|| {
+                    crate::Error::StorageError(format!(
+                        "missing metadata on user paste {user}:{}",
+                        obj.key()
+                    ))
+                })?;
+                pastes.push(ListPaste {
+                    name: super::strip_prefix(&obj.key(), &prefix)?,
+                    metadata,
+                    last_modified: mtime,
+                });
+            }
+
+            cursor = objects.cursor();
+            if !objects.truncated() || cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(pastes)
+    }
+
+    /// Returns the stored listing of all pastes of a user, see [`Self::put_listing`].
+    ///
+    /// A listing which cannot be read is treated as missing.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_listing(&self, user: &User) -> Result<Option<Vec<ListPaste>>> {
+        let path = super::to_listing_path_r2(user);
+        let obj = retry::retry_all(3, |_| self.bucket.get(&path).execute()).await?;
+
+        let Some(body) = obj.as_ref().and_then(|obj| obj.body()) else {
+            return Ok(None);
+        };
+
+        match serde_json::from_slice::<Vec<ListingEntry>>(&body.bytes().await?) {
+            Ok(entries) => Ok(Some(entries.into_iter().map(Into::into).collect())),
+            Err(err) => {
+                tracing::warn!("invalid listing of user {user}: {err:?}");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Stores the listing of all pastes of a user in a single object,
+    /// reading it is a lot cheaper than listing all pastes.
+    #[tracing::instrument(skip(self, pastes))]
+    pub async fn put_listing(&self, user: &User, pastes: &[ListPaste]) -> Result<()> {
+        let path = super::to_listing_path_r2(user);
+        let entries = pastes.iter().map(ListingEntry::from).collect::<Vec<_>>();
+        let data = serde_json::to_vec(&entries)?;
+
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(&path, worker::Data::Bytes(data.clone()))
+                .execute()
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn delete_listing(&self, user: &User) -> Result<()> {
+        let path = super::to_listing_path_r2(user);
+        retry::retry_all(3, |_| self.bucket.delete(&path)).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn list_revisions(&self, id: &UserPasteId) -> Result<Vec<PasteRevision>> {
+        let prefix = super::to_revision_prefix_r2(id);
+
         let objects = retry::retry_all(3, |_| {
             self.bucket
                 .list()
@@ -117,29 +612,105 @@ impl R2Storage {
             .objects()
             .into_iter()
             .map(|obj| {
-                let (mtime, metadata) = to_metadata(&obj)?;
+                let (_, metadata) = to_metadata(&obj)?;
                 let metadata = metadata.ok_or_else(|| {
                     crate::Error::StorageError(format!(
-                        "missing metadata on user paste {user}:{}",
+                        "missing metadata on paste revision {id}:{}",
                         obj.key()
                     ))
                 })?;
-                Ok(ListPaste {
-                    name: super::strip_prefix(&obj.key(), &prefix)?,
-                    metadata,
-                    last_modified: mtime,
-                })
+                let revision = super::strip_prefix(&obj.key(), &prefix)?
+                    .parse()
+                    .map_err(|_| {
+                        crate::Error::StorageError(format!("invalid revision {}", obj.key()))
+                    })?;
+                Ok(PasteRevision { revision, metadata })
             })
             .collect::<Result<_>>()
     }
 }
 
+impl RevisionStore for R2Storage {
+    async fn get_revision(&self, id: &UserPasteId, revision: u64) -> Result<Option<StoredPaste>> {
+        R2Storage::get_revision(self, id, revision).await
+    }
+
+    async fn list_revisions(&self, id: &UserPasteId) -> Result<Vec<PasteRevision>> {
+        R2Storage::list_revisions(self, id).await
+    }
+
+    async fn put_revision(&self, id: &UserPasteId, paste: &StoredPaste) -> Result<()> {
+        R2Storage::put_revision(
+            self,
+            id,
+            paste.last_modified,
+            paste.content.as_bytes(),
+            paste.metadata.as_ref(),
+        )
+        .await
+    }
+
+    async fn delete_revisions(&self, id: &UserPasteId, revisions: &[u64]) -> Result<()> {
+        R2Storage::delete_revisions(self, id, revisions).await
+    }
+}
+
+/// A paste in the stored listing of a user.
+#[derive(Deserialize, Serialize)]
+struct ListingEntry {
+    name: String,
+    metadata: PasteMetadata,
+    last_modified: u64,
+}
+
+impl From<&ListPaste> for ListingEntry {
+    fn from(paste: &ListPaste) -> Self {
+        Self {
+            name: paste.name.clone(),
+            metadata: paste.metadata.clone(),
+            last_modified: paste.last_modified,
+        }
+    }
+}
+
+impl From<ListingEntry> for ListPaste {
+    fn from(entry: ListingEntry) -> Self {
+        Self {
+            name: entry.name,
+            metadata: entry.metadata,
+            last_modified: entry.last_modified,
+        }
+    }
+}
+
+/// Lifecycle information stored with a paste.
+struct Lifecycle {
+    /// Creation time in milliseconds, this is the time of the last upload.
+    created: u64,
+    /// Expiry time in milliseconds, `None` for pastes which do not expire.
+    expires: Option<u64>,
+}
+
+fn lifecycle(obj: &Object) -> Result<Lifecycle> {
+    let custom_metdata = obj.custom_metadata()?;
+
+    let created = custom_metdata
+        .get("created")
+        .and_then(|created| created.parse().ok())
+        .unwrap_or_else(|| obj.uploaded().as_millis());
+    let expires = custom_metdata
+        .get("expires")
+        .and_then(|expires| expires.parse().ok());
+
+    Ok(Lifecycle { created, expires })
+}
+
 fn to_metadata(obj: &Object) -> Result<(u64, Option<PasteMetadata>)> {
     let custom_metdata = obj.custom_metadata()?;
 
     let mtime = custom_metdata
         .get("mtime")
-        .and_then(|mtime| mtime.parse::<f32>().ok())
+        .and_then(|mtime| mtime.parse::<f64>().ok())
         .map(|mtime| (mtime * 1000.0) as u64)
         .unwrap_or_else(|| obj.uploaded().as_millis());
 
@@ -147,8 +718,33 @@ fn to_metadata(obj: &Object) -> Result<(u64, Option<PasteMetadata>)> {
         .get("metadata")
         .map(b64_decode)
         .transpose()?
-        .map(|m| serde_json::from_slice(&m))
+        .map(|m| super::metadata::decode(&m))
         .transpose()?;
 
     Ok((mtime, metadata))
 }
+
+fn metadata_version(obj: &Object) -> Result<u32> {
+    let custom_metdata = obj.custom_metadata()?;
+    let version = custom_metdata.get("metadata_version").map(String::as_str);
+    Ok(super::metadata::parse_version(version))
+}
+
+/// Upgrades outdated metadata by deriving it again from the paste content.
+///
+/// The upgrade is not persisted, this happens through the metadata migration.
+/// If the content cannot be parsed, the outdated metadata is returned.
+fn upgrade(path: &str, metadata: PasteMetadata, content: &str) -> PasteMetadata {
+    let pob = match pob::SerdePathOfBuilding::from_export(content) {
+        Ok(pob) => pob,
+        Err(err) => {
+            tracing::warn!("failed to upgrade metadata of {path}: {err:?}");
+            return metadata;
+        }
+    };
+
+    // Titles of user pastes are chosen by the user.
+    let keep_title = path.starts_with(super::USERS_PREFIX);
+    let derived = super::to_metadata(&pob);
+    super::metadata::rederive(Some(&metadata), derived, keep_title)
+}
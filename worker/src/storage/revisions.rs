@@ -0,0 +1,176 @@
+//! Revisions of user pastes.
+//!
+//! Every update of a user paste archives the previous version as a revision,
+//! the revision is identified by the time the version was last modified.
+//! Only the newest [`consts::MAX_PASTE_REVISIONS`] revisions are kept.
+use shared::{model::PasteRevision, UserPasteId};
+
+use super::StoredPaste;
+use crate::{consts, Result};
+
+#[cfg(test)]
+mod memory;
+
+/// Backend storing the revisions of user pastes.
+pub trait RevisionStore {
+    /// Retrieves a single revision, the last modification time is the revision.
+    async fn get_revision(&self, id: &UserPasteId, revision: u64) -> Result<Option<StoredPaste>>;
+
+    /// Lists all revisions of a paste, in no particular order.
+    async fn list_revisions(&self, id: &UserPasteId) -> Result<Vec<PasteRevision>>;
+
+    /// Stores a version of a paste as the revision of its last modification time.
+    async fn put_revision(&self, id: &UserPasteId, paste: &StoredPaste) -> Result<()>;
+
+    /// Deletes the given revisions, missing revisions are ignored.
+    async fn delete_revisions(&self, id: &UserPasteId, revisions: &[u64]) -> Result<()>;
+}
+
+/// Lists all revisions of a paste, the newest revision first.
+pub async fn list(store: &impl RevisionStore, id: &UserPasteId) -> Result<Vec<PasteRevision>> {
+    let mut revisions = store.list_revisions(id).await?;
+    revisions.sort_unstable_by_key(|r| std::cmp::Reverse(r.revision));
+    Ok(revisions)
+}
+
+/// Stores the current version of a paste as a revision and prunes outdated revisions.
+///
+/// Returns the pruned revisions.
+pub async fn archive(
+    store: &impl RevisionStore,
+    id: &UserPasteId,
+    current: &StoredPaste,
+) -> Result<Vec<u64>> {
+    store.put_revision(id, current).await?;
+
+    let outdated = list(store, id)
+        .await?
+        .into_iter()
+        .skip(consts::MAX_PASTE_REVISIONS)
+        .map(|r| r.revision)
+        .collect::<Vec<_>>();
+    if !outdated.is_empty() {
+        tracing::info!("pruning {} outdated revisions of {id}", outdated.len());
+        store.delete_revisions(id, &outdated).await?;
+    }
+
+    Ok(outdated)
+}
+
+/// Archives the current version of a paste and returns the revision which replaces it,
+/// together with the pruned revisions.
+///
+/// Returns `None` if the revision does not exist, nothing is archived in that case.
+pub async fn restore(
+    store: &impl RevisionStore,
+    id: &UserPasteId,
+    current: Option<&StoredPaste>,
+    revision: u64,
+) -> Result<Option<(StoredPaste, Vec<u64>)>> {
+    let Some(paste) = store.get_revision(id, revision).await? else {
+        return Ok(None);
+    };
+
+    let pruned = match current {
+        Some(current) => archive(store, id, current).await?,
+        None => Vec::new(),
+    };
+
+    Ok(Some((paste, pruned)))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn id() -> UserPasteId {
+        UserPasteId {
+            user: "foo".parse().unwrap(),
+            id: "barbaz".parse().unwrap(),
+        }
+    }
+
+    fn paste(last_modified: u64) -> StoredPaste {
+        StoredPaste {
+            metadata: Some(
+                serde_json::from_str(r#"{"title":"Foo","ascendancy_or_class":"Scion"}"#).unwrap(),
+            ),
+            last_modified,
+            entity_id: format!("etag-{last_modified}"),
+            content: format!("content-{last_modified}"),
+            expires: None,
+        }
+    }
+
+    fn revisions(store: &memory::MemoryStore) -> Vec<u64> {
+        block_on(list(store, &id()))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.revision)
+            .collect()
+    }
+
+    #[test]
+    fn test_archive_on_update() {
+        let store = memory::MemoryStore::default();
+
+        assert!(block_on(archive(&store, &id(), &paste(1)))
+            .unwrap()
+            .is_empty());
+        assert!(block_on(archive(&store, &id(), &paste(2)))
+            .unwrap()
+            .is_empty());
+        assert_eq!(revisions(&store), [2, 1]);
+
+        let revision = block_on(store.get_revision(&id(), 1)).unwrap().unwrap();
+        assert_eq!(revision.content, "content-1");
+        assert_eq!(revision.last_modified, 1);
+    }
+
+    #[test]
+    fn test_archive_prunes_outdated() {
+        let store = memory::MemoryStore::default();
+        let max = consts::MAX_PASTE_REVISIONS as u64;
+
+        for revision in 1..=max {
+            assert!(block_on(archive(&store, &id(), &paste(revision)))
+                .unwrap()
+                .is_empty());
+        }
+        assert_eq!(revisions(&store).len(), consts::MAX_PASTE_REVISIONS);
+
+        let pruned = block_on(archive(&store, &id(), &paste(max + 1))).unwrap();
+        assert_eq!(pruned, [1]);
+
+        let revisions = revisions(&store);
+        assert_eq!(revisions.len(), consts::MAX_PASTE_REVISIONS);
+        assert_eq!(revisions.first(), Some(&(max + 1)));
+        assert_eq!(revisions.last(), Some(&2));
+    }
+
+    #[test]
+    fn test_restore() {
+        let store = memory::MemoryStore::default();
+        block_on(archive(&store, &id(), &paste(1))).unwrap();
+
+        let (restored, pruned) = block_on(restore(&store, &id(), Some(&paste(2)), 1))
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.content, "content-1");
+        assert!(pruned.is_empty());
+        // The replaced version is archived, the restored revision is kept.
+        assert_eq!(revisions(&store), [2, 1]);
+    }
+
+    #[test]
+    fn test_restore_missing() {
+        let store = memory::MemoryStore::default();
+
+        assert!(block_on(restore(&store, &id(), Some(&paste(2)), 1))
+            .unwrap()
+            .is_none());
+        assert!(revisions(&store).is_empty());
+    }
+}
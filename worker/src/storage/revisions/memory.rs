@@ -0,0 +1,63 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+use shared::{model::PasteRevision, UserPasteId};
+
+use super::RevisionStore;
+use crate::{storage::StoredPaste, Result};
+
+/// In memory revision store, used for tests.
+#[derive(Default)]
+pub struct MemoryStore {
+    revisions: RefCell<BTreeMap<(String, u64), StoredPaste>>,
+}
+
+impl RevisionStore for MemoryStore {
+    async fn get_revision(&self, id: &UserPasteId, revision: u64) -> Result<Option<StoredPaste>> {
+        let revisions = self.revisions.borrow();
+        Ok(revisions
+            .get(&(id.to_string(), revision))
+            .map(|paste| StoredPaste {
+                metadata: paste.metadata.clone(),
+                last_modified: revision,
+                entity_id: paste.entity_id.clone(),
+                content: paste.content.clone(),
+                expires: None,
+            }))
+    }
+
+    async fn list_revisions(&self, id: &UserPasteId) -> Result<Vec<PasteRevision>> {
+        let id = id.to_string();
+        Ok(self
+            .revisions
+            .borrow()
+            .iter()
+            .filter(|((paste, _), _)| *paste == id)
+            .map(|(&(_, revision), paste)| PasteRevision {
+                revision,
+                metadata: paste.metadata.clone().unwrap(),
+            })
+            .collect())
+    }
+
+    async fn put_revision(&self, id: &UserPasteId, paste: &StoredPaste) -> Result<()> {
+        let revision = StoredPaste {
+            metadata: paste.metadata.clone(),
+            last_modified: paste.last_modified,
+            entity_id: paste.entity_id.clone(),
+            content: paste.content.clone(),
+            expires: None,
+        };
+        self.revisions
+            .borrow_mut()
+            .insert((id.to_string(), paste.last_modified), revision);
+        Ok(())
+    }
+
+    async fn delete_revisions(&self, id: &UserPasteId, revisions: &[u64]) -> Result<()> {
+        let mut stored = self.revisions.borrow_mut();
+        for &revision in revisions {
+            stored.remove(&(id.to_string(), revision));
+        }
+        Ok(())
+    }
+}
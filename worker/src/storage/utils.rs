@@ -1,18 +1,72 @@
-use shared::{PasteId, User};
+use shared::{PasteId, User, UserPasteId};
 
 use crate::Result;
 
 pub(crate) fn to_path_r2(id: &PasteId) -> Result<String> {
     match id {
-        PasteId::Paste(id) => Ok(format!("pastes/{}", crate::utils::to_path(id))),
+        PasteId::Paste(id) => Ok(format!("{PASTES_PREFIX}{}", crate::utils::to_path(id))),
         PasteId::UserPaste(up) => Ok(format!("users/{}/pastes/{}", up.user, up.id)),
     }
 }
 
+pub(crate) fn to_access_path_r2(id: &PasteId) -> Result<String> {
+    Ok(format!("{ACCESS_PREFIX}{}", to_path_r2(id)?))
+}
+
+/// Prefix of all access markers, see [`to_access_path_r2`].
+pub(crate) const ACCESS_PREFIX: &str = "access/";
+
+/// Prefix of all anonymous pastes.
+pub(crate) const PASTES_PREFIX: &str = "pastes/";
+
+/// Inverse of [`to_path_r2`] for anonymous pastes.
+pub(crate) fn from_paste_path_r2(path: &str) -> Option<PasteId> {
+    let id = path.strip_prefix(PASTES_PREFIX)?.replace('/', "");
+    id.parse().ok().map(PasteId::Paste)
+}
+
+/// Prefix of all user scoped data, see [`to_prefix_r2`].
+pub(crate) const USERS_PREFIX: &str = "users/";
+
+/// Inverse of [`to_path_r2`] for user pastes.
+pub(crate) fn from_user_paste_path_r2(path: &str) -> Option<PasteId> {
+    let mut parts = path.strip_prefix(USERS_PREFIX)?.split('/');
+    let (Some(user), Some("pastes"), Some(id), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+
+    Some(PasteId::UserPaste(UserPasteId {
+        user: user.parse().ok()?,
+        id: id.parse().ok()?,
+    }))
+}
+
+pub(crate) fn to_tombstone_path_r2(id: &PasteId) -> Result<String> {
+    Ok(format!("tombstones/{}", to_path_r2(id)?))
+}
+
+pub(crate) fn to_redirect_path_r2(id: &UserPasteId) -> String {
+    format!("users/{}/redirects/{}", id.user, id.id)
+}
+
+pub(crate) fn to_listing_path_r2(user: &User) -> String {
+    format!("users/{user}/listing")
+}
+
 pub(crate) fn to_prefix_r2(user: &User) -> String {
     format!("users/{user}/pastes/")
 }
 
+pub(crate) fn to_revision_prefix_r2(id: &UserPasteId) -> String {
+    format!("users/{}/revisions/{}/", id.user, id.id)
+}
+
+pub(crate) fn to_revision_path_r2(id: &UserPasteId, revision: u64) -> String {
+    format!("{}{revision}", to_revision_prefix_r2(id))
+}
+
 pub(crate) fn strip_prefix(file: &str, prefix: &str) -> Result<String> {
     file.strip_prefix(prefix).map(Into::into).ok_or_else(|| {
         crate::Error::Error(format!("expected file '{file}' to start with '{prefix}'"))
@@ -0,0 +1,169 @@
+//! Personal API tokens.
+//!
+//! Tokens are signed with [`Dangerous`] and additionally stored in R2,
+//! a token is only valid as long as its stored record exists.
+//! Deleting the record revokes the token.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use shared::{model::ApiToken, User};
+use worker::{Bucket, Include};
+
+use crate::{
+    consts,
+    dangerous::Dangerous,
+    request_context::{Env, FromEnv},
+    retry,
+    utils::{b64_decode, b64_encode},
+    Result,
+};
+
+/// Signed payload of a token.
+#[derive(Debug, Deserialize, Serialize)]
+struct TokenPayload {
+    user: User,
+    id: String,
+}
+
+pub struct Tokens {
+    bucket: Bucket,
+    dangerous: Dangerous,
+}
+
+impl FromEnv for Tokens {
+    fn from_env(env: &Env) -> Option<Self> {
+        Some(Self {
+            bucket: env.bucket(consts::R2_STORAGE_BUCKET)?,
+            dangerous: Dangerous::from_env(env)?,
+        })
+    }
+}
+
+impl Tokens {
+    /// Creates a new token for the user, returns the token information and the secret token.
+    #[tracing::instrument(skip(self))]
+    pub async fn create(&self, user: &User, name: String) -> Result<(ApiToken, String)> {
+        let existing = self.list(user).await?;
+        if existing.len() >= consts::MAX_API_TOKENS {
+            return Err(crate::Error::BadRequest(format!(
+                "Too many API tokens, at most {} tokens are allowed",
+                consts::MAX_API_TOKENS
+            )));
+        }
+
+        let info = ApiToken {
+            id: crate::utils::random_string::<9>()?,
+            name,
+            created: worker::Date::now().as_millis(),
+        };
+
+        let payload = TokenPayload {
+            user: user.clone(),
+            id: info.id.clone(),
+        };
+        let token = b64_encode(self.dangerous.sign(&payload).await?);
+
+        let mut custom_metadata = HashMap::new();
+        custom_metadata.insert(
+            "token".to_owned(),
+            b64_encode(serde_json::to_string(&info)?),
+        );
+
+        let path = to_path(user, &info.id);
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(&path, worker::Data::Empty)
+                .custom_metadata(custom_metadata.clone())
+                .execute()
+        })
+        .await?;
+
+        Ok((info, token))
+    }
+
+    /// Lists all tokens of a user, the oldest token first.
+    #[tracing::instrument(skip(self))]
+    pub async fn list(&self, user: &User) -> Result<Vec<ApiToken>> {
+        let prefix = to_prefix(user);
+
+        let objects = retry::retry_all(3, |_| {
+            self.bucket
+                .list()
+                .prefix(&prefix)
+                .include(vec![Include::CustomMetadata])
+                .limit(100)
+                .execute()
+        })
+        .await?;
+
+        let mut tokens = objects
+            .objects()
+            .into_iter()
+            .filter_map(|obj| to_token(&obj).transpose())
+            .collect::<Result<Vec<_>>>()?;
+        tokens.sort_unstable_by_key(|token| token.created);
+
+        Ok(tokens)
+    }
+
+    /// Revokes a token, revoking a token which does not exist is not an error.
+    #[tracing::instrument(skip(self))]
+    pub async fn revoke(&self, user: &User, id: &str) -> Result<()> {
+        let path = to_path(user, id);
+        retry::retry_all(3, |_| self.bucket.delete(&path)).await?;
+        Ok(())
+    }
+
+    /// Verifies a token and returns the user it belongs to.
+    ///
+    /// Returns `None` for invalid, expired or revoked tokens.
+    #[tracing::instrument(skip(self, token))]
+    pub async fn verify(&self, token: &str) -> Result<Option<User>> {
+        let Some(token) = b64_decode(token)
+            .ok()
+            .and_then(|token| String::from_utf8(token).ok())
+        else {
+            return Ok(None);
+        };
+
+        let payload = match self
+            .dangerous
+            .verify::<TokenPayload>(&token, consts::API_TOKEN_MAX_AGE)
+            .await
+        {
+            Ok(payload) => payload,
+            Err(err) => {
+                tracing::warn!("failed to verify API token: {err:?}");
+                return Ok(None);
+            }
+        };
+
+        let path = to_path(&payload.user, &payload.id);
+        let exists = retry::retry_all(3, |_| self.bucket.head(&path))
+            .await?
+            .is_some();
+        if !exists {
+            tracing::warn!(user = %payload.user, id = %payload.id, "API token was revoked");
+            return Ok(None);
+        }
+
+        Ok(Some(payload.user))
+    }
+}
+
+fn to_token(obj: &worker::Object) -> Result<Option<ApiToken>> {
+    let Some(token) = obj.custom_metadata()?.remove("token") else {
+        tracing::warn!("API token {} without metadata", obj.key());
+        return Ok(None);
+    };
+
+    Ok(Some(serde_json::from_slice(&b64_decode(token)?)?))
+}
+
+fn to_prefix(user: &User) -> String {
+    format!("users/{user}/tokens/")
+}
+
+fn to_path(user: &User, id: &str) -> String {
+    format!("{}{id}", to_prefix(user))
+}
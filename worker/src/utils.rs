@@ -63,7 +63,6 @@ pub fn to_link(p: &[app::Prefetch], rel: &str) -> String {
 #[derive(Copy, Clone, Debug)]
 pub enum Cachability {
     Public,
-    #[allow(dead_code)]
     Private,
     #[allow(dead_code)]
     NoCache,
@@ -84,6 +83,7 @@ pub struct CacheControl {
     pub cachability: Option<Cachability>,
     pub max_age: Option<Duration>,
     pub s_max_age: Option<Duration>,
+    pub no_store: bool,
 }
 
 impl CacheControl {
@@ -102,9 +102,18 @@ impl CacheControl {
         self
     }
 
+    pub fn no_store(mut self) -> Self {
+        self.no_store = true;
+        self
+    }
+
     pub fn public(self) -> Self {
         self.cachability(Cachability::Public)
     }
+
+    pub fn private(self) -> Self {
+        self.cachability(Cachability::Private)
+    }
 }
 
 impl fmt::Display for CacheControl {
@@ -126,6 +135,7 @@ impl fmt::Display for CacheControl {
         }
 
         w!(self.cachability, "{}");
+        w!(self.no_store.then_some("no-store"), "{}");
         w!(self.max_age.map(|d| d.as_secs()), "max-age={}");
         w!(self.s_max_age.map(|d| d.as_secs()), "s-max-age={}");
 
@@ -255,6 +265,10 @@ mod tests {
                 .cachability(Cachability::Private)
                 .to_string()
         );
+        assert_eq!(
+            "private, no-store",
+            CacheControl::default().private().no_store().to_string()
+        );
         assert_eq!(
             "no-cache",
             CacheControl::default()
@@ -0,0 +1,410 @@
+//! Outgoing webhooks, notifying users about changes of their pastes.
+//!
+//! Webhooks are stored in R2 next to the API tokens of a user, together with
+//! the secret used to sign the payloads. Every delivery carries an
+//! `X-Pobbin-Signature-256` header, `sha256=` followed by the hex encoded
+//! HMAC-SHA256 of the body.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::{
+    model::{PasteMetadata, Webhook, WebhookFormat},
+    PasteId, User,
+};
+use worker::{Bucket, Include};
+
+use crate::{
+    consts, crypto, net,
+    request_context::{Env, FromEnv, RequestContext},
+    retry::{self, Retry},
+    statsd::Counters,
+    utils::{b64_decode, b64_encode},
+    Error, Result,
+};
+
+const SIGNATURE_HEADER: &str = "X-Pobbin-Signature-256";
+const EVENT_HEADER: &str = "X-Pobbin-Event";
+
+/// Color of the Discord embed, the same amber as the website.
+const DISCORD_COLOR: u32 = 0xfcd34d;
+
+/// Stored webhook, including its secret.
+#[derive(Debug, Deserialize, Serialize)]
+struct StoredWebhook {
+    #[serde(flatten)]
+    info: Webhook,
+    secret: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Event {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl Event {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+/// Payload of the [`WebhookFormat::Json`] format.
+#[derive(Debug, Serialize)]
+struct Payload {
+    event: Event,
+    id: PasteId,
+    title: Option<String>,
+    ascendancy_or_class: Option<String>,
+    main_skill_name: Option<String>,
+    url: String,
+    /// Time of the event in milliseconds.
+    timestamp: u64,
+}
+
+impl Payload {
+    fn new(
+        event: Event,
+        id: PasteId,
+        metadata: Option<&PasteMetadata>,
+        base_url: &str,
+        timestamp: u64,
+    ) -> Self {
+        Self {
+            event,
+            url: format!("{base_url}{}", id.to_url()),
+            id,
+            title: metadata.map(|m| m.title.clone()),
+            ascendancy_or_class: metadata.map(|m| m.ascendancy_or_class.as_str().to_owned()),
+            main_skill_name: metadata.and_then(|m| m.main_skill_name.clone()),
+            timestamp,
+        }
+    }
+}
+
+pub struct Webhooks {
+    bucket: Bucket,
+}
+
+impl FromEnv for Webhooks {
+    fn from_env(env: &Env) -> Option<Self> {
+        Some(Self {
+            bucket: env.bucket(consts::R2_STORAGE_BUCKET)?,
+        })
+    }
+}
+
+impl Webhooks {
+    /// Registers a new webhook for the user, returns the webhook and its secret.
+    #[tracing::instrument(skip(self))]
+    pub async fn create(
+        &self,
+        user: &User,
+        url: String,
+        format: WebhookFormat,
+    ) -> Result<(Webhook, String)> {
+        let existing = self.list(user).await?;
+        if existing.len() >= consts::MAX_WEBHOOKS {
+            return Err(Error::BadRequest(format!(
+                "Too many webhooks, at most {} webhooks are allowed",
+                consts::MAX_WEBHOOKS
+            )));
+        }
+
+        let webhook = StoredWebhook {
+            info: Webhook {
+                id: crate::utils::random_string::<9>()?,
+                url,
+                format,
+                created: worker::Date::now().as_millis(),
+            },
+            secret: crate::utils::random_string::<32>()?,
+        };
+
+        let mut custom_metadata = HashMap::new();
+        custom_metadata.insert(
+            "webhook".to_owned(),
+            b64_encode(serde_json::to_string(&webhook)?),
+        );
+
+        let path = to_path(user, &webhook.info.id);
+        retry::retry_all(3, |_| {
+            self.bucket
+                .put(&path, worker::Data::Empty)
+                .custom_metadata(custom_metadata.clone())
+                .execute()
+        })
+        .await?;
+
+        Ok((webhook.info, webhook.secret))
+    }
+
+    /// Lists all webhooks of a user, the oldest webhook first.
+    #[tracing::instrument(skip(self))]
+    pub async fn list(&self, user: &User) -> Result<Vec<Webhook>> {
+        let webhooks = self.list_stored(user).await?;
+        Ok(webhooks.into_iter().map(|webhook| webhook.info).collect())
+    }
+
+    /// Deletes a webhook, deleting a webhook which does not exist is not an error.
+    #[tracing::instrument(skip(self))]
+    pub async fn delete(&self, user: &User, id: &str) -> Result<()> {
+        let path = to_path(user, id);
+        retry::retry_all(3, |_| self.bucket.delete(&path)).await?;
+        Ok(())
+    }
+
+    /// Delivers an event to all webhooks of the user.
+    ///
+    /// Failed deliveries are logged, they never fail the whole notification.
+    async fn notify(&self, user: &User, payload: &Payload) -> Result<()> {
+        for webhook in self.list_stored(user).await? {
+            let result = deliver(&webhook, payload).await;
+
+            sentry::counter(Counters::Webhook)
+                .inc(1)
+                .tag("format", format_as_str(webhook.info.format))
+                .tag("status", if result.is_ok() { "success" } else { "failure" });
+
+            if let Err(err) = result {
+                tracing::warn!(
+                    id = %webhook.info.id,
+                    "failed to deliver webhook for {}: {err:?}",
+                    payload.id
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn list_stored(&self, user: &User) -> Result<Vec<StoredWebhook>> {
+        let prefix = to_prefix(user);
+
+        let objects = retry::retry_all(3, |_| {
+            self.bucket
+                .list()
+                .prefix(&prefix)
+                .include(vec![Include::CustomMetadata])
+                .limit(100)
+                .execute()
+        })
+        .await?;
+
+        let mut webhooks = objects
+            .objects()
+            .into_iter()
+            .filter_map(|obj| to_webhook(&obj).transpose())
+            .collect::<Result<Vec<_>>>()?;
+        webhooks.sort_unstable_by_key(|webhook| webhook.info.created);
+
+        Ok(webhooks)
+    }
+}
+
+/// Notifies the webhooks of the paste owner about a change in the background.
+///
+/// Anonymous pastes have no owner and never trigger a webhook.
+pub(crate) fn on_paste_change(
+    rctx: &RequestContext,
+    event: Event,
+    id: PasteId,
+    metadata: Option<&PasteMetadata>,
+) {
+    let Some(user) = id.user().cloned() else {
+        return;
+    };
+    let base_url = match rctx.url() {
+        Ok(url) => format!("https://{}", url.host_str().unwrap_or_default()),
+        Err(err) => {
+            tracing::error!("unable to determine url for webhooks: {err:?}");
+            return;
+        }
+    };
+
+    let now = worker::Date::now().as_millis();
+    let payload = Payload::new(event, id, metadata, &base_url, now);
+
+    let webhooks = rctx.inject::<Webhooks>();
+    rctx.ctx().wait_until(async move {
+        if let Err(err) = webhooks.notify(&user, &payload).await {
+            tracing::error!("failed to notify webhooks for {}: {err:?}", payload.id);
+        }
+    });
+}
+
+/// Whether the url can be used as a webhook, only https urls are allowed.
+pub fn is_valid_url(url: &str) -> bool {
+    url.len() <= 500
+        && url::Url::parse(url).is_ok_and(|url| url.scheme() == "https" && url.host_str().is_some())
+}
+
+async fn deliver(webhook: &StoredWebhook, payload: &Payload) -> Result<()> {
+    let body = to_body(webhook.info.format, payload)?;
+    let signature = crypto::sign_hmac_256(webhook.secret.as_bytes(), &body).await?;
+    let signature = format!("sha256={}", to_hex(&signature));
+
+    retry::retry(3, |_| {
+        let (url, body, signature) = (&webhook.info.url, &body, &signature);
+        async move {
+            let response = net::Request::post(url.as_str())
+                .tag("webhook")
+                .header("Content-Type", "application/json")
+                .header(SIGNATURE_HEADER, signature)
+                .header(EVENT_HEADER, payload.event.as_str())
+                .body_u8(body)
+                .send()
+                .await;
+
+            match response.map(|response| response.status_code()) {
+                Ok(200..=299) => Retry::ok(()),
+                // Rate limits and server errors are worth another attempt.
+                Ok(status @ (429 | 500..)) => {
+                    Retry::err(Error::RemoteFailed(status, "webhook failed".to_owned()))
+                }
+                Ok(status) => Err(Error::RemoteFailed(status, "webhook rejected".to_owned())),
+                Err(err) => Retry::err(err.into()),
+            }
+        }
+    })
+    .await
+}
+
+fn to_body(format: WebhookFormat, payload: &Payload) -> Result<Vec<u8>> {
+    let body = match format {
+        WebhookFormat::Json => serde_json::to_vec(payload)?,
+        WebhookFormat::Discord => serde_json::to_vec(&to_discord(payload))?,
+    };
+    Ok(body)
+}
+
+fn to_discord(payload: &Payload) -> serde_json::Value {
+    let mut fields = Vec::new();
+    if let Some(class) = &payload.ascendancy_or_class {
+        fields.push(json!({ "name": "Class", "value": class, "inline": true }));
+    }
+    if let Some(skill) = &payload.main_skill_name {
+        fields.push(json!({ "name": "Main Skill", "value": skill, "inline": true }));
+    }
+
+    let description = match payload.event {
+        Event::Created => "Build created",
+        Event::Updated => "Build updated",
+        Event::Deleted => "Build deleted",
+    };
+
+    let mut embed = json!({
+        "title": payload.title.as_deref().unwrap_or(payload.id.id()),
+        "description": description,
+        "color": DISCORD_COLOR,
+        "fields": fields,
+    });
+    // There is nothing to link to anymore for deleted pastes.
+    if payload.event != Event::Deleted {
+        embed["url"] = payload.url.as_str().into();
+    }
+
+    json!({ "embeds": [embed] })
+}
+
+fn format_as_str(format: WebhookFormat) -> &'static str {
+    match format {
+        WebhookFormat::Json => "json",
+        WebhookFormat::Discord => "discord",
+    }
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn to_webhook(obj: &worker::Object) -> Result<Option<StoredWebhook>> {
+    let Some(webhook) = obj.custom_metadata()?.remove("webhook") else {
+        tracing::warn!("webhook {} without metadata", obj.key());
+        return Ok(None);
+    };
+
+    Ok(Some(serde_json::from_slice(&b64_decode(webhook)?)?))
+}
+
+fn to_prefix(user: &User) -> String {
+    format!("users/{user}/webhooks/")
+}
+
+fn to_path(user: &User, id: &str) -> String {
+    format!("{}{id}", to_prefix(user))
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::UserPasteId;
+
+    use super::*;
+
+    fn payload(event: Event) -> Payload {
+        let metadata: PasteMetadata = serde_json::from_value(json!({
+            "title": "My Build",
+            "ascendancy_or_class": "Juggernaut",
+            "main_skill_name": "Boneshatter",
+        }))
+        .unwrap();
+        let id = PasteId::UserPaste(UserPasteId {
+            user: User::new("foo"),
+            id: "buildid".parse().unwrap(),
+        });
+
+        Payload::new(event, id, Some(&metadata), "https://pobb.in", 1)
+    }
+
+    #[test]
+    fn test_webhook_json_body() {
+        let body = to_body(WebhookFormat::Json, &payload(Event::Created)).unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["event"], "created");
+        assert_eq!(body["title"], "My Build");
+        assert_eq!(body["ascendancy_or_class"], "Juggernaut");
+        assert_eq!(body["main_skill_name"], "Boneshatter");
+        assert_eq!(body["url"], "https://pobb.in/u/foo/buildid");
+    }
+
+    #[test]
+    fn test_webhook_discord_body() {
+        let body = to_body(WebhookFormat::Discord, &payload(Event::Updated)).unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let embed = &body["embeds"][0];
+        assert_eq!(embed["title"], "My Build");
+        assert_eq!(embed["description"], "Build updated");
+        assert_eq!(embed["url"], "https://pobb.in/u/foo/buildid");
+        assert_eq!(embed["fields"][0]["value"], "Juggernaut");
+        assert_eq!(embed["fields"][1]["value"], "Boneshatter");
+
+        let body = to_body(WebhookFormat::Discord, &payload(Event::Deleted)).unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["embeds"][0].get("url").is_none());
+    }
+
+    #[test]
+    fn test_webhook_url() {
+        assert!(is_valid_url("https://discord.com/api/webhooks/1/abc"));
+        assert!(!is_valid_url("http://discord.com/api/webhooks/1/abc"));
+        assert!(!is_valid_url("ftp://example.com"));
+        assert!(!is_valid_url("not a url"));
+        assert!(!is_valid_url(&format!(
+            "https://example.com/{}",
+            "a".repeat(500)
+        )));
+    }
+
+    #[test]
+    fn test_webhook_hex() {
+        assert_eq!(to_hex(&[0x00, 0x0f, 0xab, 0xff]), "000fabff");
+    }
+}